@@ -0,0 +1,171 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! `$ cargo bench --bench next_id`
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "global")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snowflaker::clock::Clock;
+use snowflaker::generator::{
+    BitLayout, Constants, Generator, SnowflakeError, SnowflakeGenerator, SnowflakeGeneratorLocal,
+};
+
+// ----------------------------------------------------------------
+
+const WARM_UP: Duration = Duration::from_millis(500);
+const MEASUREMENT: Duration = Duration::from_secs(2);
+const BATCH: u64 = 1_000;
+#[cfg(feature = "global")]
+const THREADS: u64 = 8;
+
+// ----------------------------------------------------------------
+
+/// A [`Clock`] advancing one millisecond per 4096 reads, a new millisecond for
+/// every sequence, so the generation is measured without the rollover waits.
+#[derive(Default)]
+struct TickClock(AtomicU64);
+
+impl Clock for TickClock {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        let reads = self.0.fetch_add(1, Ordering::Relaxed);
+        Ok(Constants::EPOCH + (reads >> Constants::SEQUENCE_BITS))
+    }
+}
+
+// ----------------------------------------------------------------
+
+fn bench<F>(name: &str, mut f: F)
+where
+    F: FnMut(),
+{
+    let started = Instant::now();
+    while started.elapsed() < WARM_UP {
+        f();
+    }
+
+    let mut iterations = 0u64;
+    let started = Instant::now();
+    while started.elapsed() < MEASUREMENT {
+        for _ in 0..BATCH {
+            f();
+        }
+        iterations += BATCH;
+    }
+
+    let elapsed = started.elapsed();
+    println!(
+        "{:<32} {:>10.2} ns/iter ({} iterations)",
+        name,
+        elapsed.as_nanos() as f64 / iterations as f64,
+        iterations
+    );
+}
+
+/// Measures the builtin global generator called from [`THREADS`] threads at once,
+/// `ns/iter` is the wall-clock time per ID of all the threads together.
+///
+/// The builtin generator is called without a lock, so the threads are capped by
+/// the same 4096 IDs per millisecond as a single thread, not serialized beyond it.
+#[cfg(feature = "global")]
+fn bench_global_contended(name: &str) {
+    let started = Instant::now();
+    while started.elapsed() < WARM_UP {
+        black_box(snowflaker::next_id().unwrap());
+    }
+
+    let started = Instant::now();
+    let iterations: u64 = thread::scope(|scope| {
+        let workers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut iterations = 0u64;
+                    while started.elapsed() < MEASUREMENT {
+                        for _ in 0..BATCH {
+                            black_box(snowflaker::next_id().unwrap());
+                        }
+                        iterations += BATCH;
+                    }
+
+                    iterations
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .sum()
+    });
+
+    let elapsed = started.elapsed();
+    println!(
+        "{:<32} {:>10.2} ns/iter ({} iterations)",
+        name,
+        elapsed.as_nanos() as f64 / iterations as f64,
+        iterations
+    );
+}
+
+fn main() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    bench("next_id", || {
+        black_box(gen.next_id().unwrap());
+    });
+
+    // The default 4096 IDs per millisecond cap the rate at ~244 ns/iter,
+    // a wide sequence measures the cost of the clock reads instead.
+    let layout = BitLayout::new(1, 1, 20).unwrap();
+    for (name, cache) in [
+        ("next_id/wide", false),
+        ("next_id/wide/timestamp_cache", true),
+    ] {
+        let gen = SnowflakeGenerator::builder()
+            .center_id(1)
+            .worker_id(1)
+            .layout(layout)
+            .timestamp_cache(cache)
+            .build()
+            .unwrap();
+        bench(name, || {
+            black_box(gen.next_id().unwrap());
+        });
+    }
+
+    // The atomic and the single-owner generators on the same cheap clock.
+    let gen = SnowflakeGenerator::builder()
+        .center_id(3)
+        .worker_id(7)
+        .clock(Arc::new(TickClock::default()))
+        .build()
+        .unwrap();
+    bench("next_id/atomic", || {
+        black_box(gen.next_id().unwrap());
+    });
+    let gen = SnowflakeGeneratorLocal::with_clock(3, 7, TickClock::default()).unwrap();
+    bench("next_id/local", || {
+        black_box(gen.next_id().unwrap());
+    });
+
+    #[cfg(feature = "global")]
+    bench_global_contended("snowflaker::next_id/8 threads");
+}
@@ -0,0 +1,131 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::generator::SnowflakeError;
+
+// ----------------------------------------------------------------
+
+/// The time source of a [`crate::generator::SnowflakeGenerator`].
+///
+/// Implementors return the current unix-millis, which allows to inject
+/// scripted timestamps to drive the clock related paths deterministically.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::clock::Clock;
+/// use snowflaker::generator::SnowflakeError;
+///
+/// struct FixedClock(u64);
+///
+/// impl Clock for FixedClock {
+///     fn now_millis(&self) -> Result<u64, SnowflakeError> {
+///         Ok(self.0)
+///     }
+/// }
+///
+/// assert_eq!(Ok(1), FixedClock(1).now_millis().map_err(|_| ()));
+/// ```
+///
+/// @since 0.4.0
+pub trait Clock {
+    /// Returns the current unix-millis.
+    fn now_millis(&self) -> Result<u64, SnowflakeError>;
+}
+
+// ----------------------------------------------------------------
+
+/// The default [`Clock`] based on [`SystemTime::now`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        Ok(now.as_millis() as u64)
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// A [`Clock`] which never moves backwards, e.g. through the NTP corrections of the host clock.
+///
+/// It combines the initial reading of a wall clock with the [`Instant`] based monotonic
+/// elapsed time, and follows the wall clock only when it is ahead. So a wall clock stepped
+/// backwards keeps the returned millis advancing with the monotonic time instead of
+/// [`SnowflakeError::ClockMovedBackwards`], a wall clock stepped forwards is followed.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use snowflaker::clock::{Clock, MonotonicClock};
+/// use snowflaker::generator::{Generator, SnowflakeGenerator};
+///
+/// let clock = MonotonicClock::new().unwrap();
+/// assert!(clock.now_millis().unwrap() > 0);
+///
+/// let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+/// assert!(gen.next_id().is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[derive(Debug)]
+pub struct MonotonicClock<C = SystemClock> {
+    wall: C,
+    base_millis: u64,
+    started: Instant,
+    last_millis: AtomicU64,
+}
+
+impl MonotonicClock<SystemClock> {
+    /// Constructs a new [`MonotonicClock`] over the [`SystemClock`].
+    pub fn new() -> Result<Self, SnowflakeError> {
+        MonotonicClock::with_wall_clock(SystemClock)
+    }
+}
+
+impl<C: Clock> MonotonicClock<C> {
+    /// Constructs a new [`MonotonicClock`] over the given wall clock,
+    /// which is read once as the base.
+    pub fn with_wall_clock(wall: C) -> Result<Self, SnowflakeError> {
+        let base_millis = wall.now_millis()?;
+
+        Ok(Self {
+            wall,
+            base_millis,
+            started: Instant::now(),
+            last_millis: AtomicU64::new(base_millis),
+        })
+    }
+}
+
+impl<C: Clock> Clock for MonotonicClock<C> {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        let monotonic = self.base_millis + self.started.elapsed().as_millis() as u64;
+        let now = self.wall.now_millis()?.max(monotonic);
+
+        Ok(self.last_millis.fetch_max(now, Ordering::SeqCst).max(now))
+    }
+}
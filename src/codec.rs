@@ -0,0 +1,740 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! ID string codecs.
+
+// ----------------------------------------------------------------
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use chronounit::TimeUnit;
+
+#[cfg(feature = "uuid")]
+use crate::generator::{mask, Constants};
+#[cfg(feature = "std")]
+use crate::generator::{now_millis, SnowflakeGenerator};
+use crate::generator::{BitLayout, IdParts, SnowflakeError};
+
+// ----------------------------------------------------------------
+
+/// `BASE62_ALPHABET` the base62 alphabet: `[0-9A-Za-z]`
+pub const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// `CROCKFORD_ALPHABET` the Crockford base32 alphabet, in ascending ASCII order
+/// without the ambiguous `I`, `L`, `O` and `U`.
+///
+/// @since 0.4.0
+pub const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// `SORTABLE_KEY_LEN` the fixed width of a sortable key: 13
+///
+/// @since 0.4.0
+pub const SORTABLE_KEY_LEN: usize = 13;
+
+// ---------------------------------------------------------------- base62
+
+/// Encodes the given ID into a base62 string with the alphabet [`BASE62_ALPHABET`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!("0", codec::encode_base62(0));
+/// assert_eq!("LygHa16AHYF", codec::encode_base62(u64::MAX));
+/// ```
+///
+/// @since 0.4.0
+pub fn encode_base62(id: u64) -> String {
+    encode_base62_u128(id as u128)
+}
+
+/// Encodes the given 128-bit ID into a base62 string with the alphabet [`BASE62_ALPHABET`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!("LygHa16AHYF", codec::encode_base62_u128(u64::MAX as u128));
+/// assert_eq!("7n42DGM5Tflk9n8mt7Fhc7", codec::encode_base62_u128(u128::MAX));
+/// ```
+///
+/// @since 0.4.0
+pub fn encode_base62_u128(id: u128) -> String {
+    if id == 0 {
+        return String::from("0");
+    }
+
+    let mut buf = Vec::with_capacity(22);
+    let mut value = id;
+    while value > 0 {
+        buf.push(BASE62_ALPHABET[(value % 62) as usize]);
+        value /= 62;
+    }
+    buf.reverse();
+
+    String::from_utf8(buf).expect("base62 alphabet is ascii")
+}
+
+/// Decodes the given base62 string into an ID.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the string is empty,
+/// contains non-alphabet characters or overflows `u64`.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(Ok(u64::MAX), codec::decode_base62("LygHa16AHYF").map_err(|_| ()));
+/// assert!(codec::decode_base62("LygHa16AHYG").is_err());
+/// assert!(codec::decode_base62("-1").is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn decode_base62(s: &str) -> Result<u64, SnowflakeError> {
+    decode_base62_u128(s)
+        .and_then(|id| u64::try_from(id).map_err(|_| SnowflakeError::EncodingInvalid))
+}
+
+/// Decodes the given base62 string into a 128-bit ID.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the string is empty,
+/// contains non-alphabet characters or overflows `u128`.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(Ok(u128::MAX), codec::decode_base62_u128("7n42DGM5Tflk9n8mt7Fhc7").map_err(|_| ()));
+/// assert!(codec::decode_base62_u128("7n42DGM5Tflk9n8mt7Fhc8").is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn decode_base62_u128(s: &str) -> Result<u128, SnowflakeError> {
+    if s.is_empty() {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    s.bytes().try_fold(0u128, |acc, ch| {
+        let digit = match ch {
+            b'0'..=b'9' => ch - b'0',
+            b'A'..=b'Z' => ch - b'A' + 10,
+            b'a'..=b'z' => ch - b'a' + 36,
+            _ => return Err(SnowflakeError::EncodingInvalid),
+        };
+
+        acc.checked_mul(62)
+            .and_then(|v| v.checked_add(digit as u128))
+            .ok_or(SnowflakeError::EncodingInvalid)
+    })
+}
+
+// ---------------------------------------------------------------- hex
+
+/// Encodes the given ID into a fixed-width(`16`), zero-padded, lowercase hex string.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!("0000000000000000", codec::to_hex(0));
+/// assert_eq!("ffffffffffffffff", codec::to_hex(u64::MAX));
+/// ```
+///
+/// @since 0.4.0
+pub fn to_hex(id: u64) -> String {
+    alloc::format!("{:016x}", id)
+}
+
+/// Decodes the given hex string into an ID, case-insensitive.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the string is empty,
+/// longer than `16` hex digits or contains non-hex characters.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(Ok(255), codec::from_hex("00000000000000ff").map_err(|_| ()));
+/// assert!(codec::from_hex("10000000000000000").is_err());
+/// assert!(codec::from_hex("+f").is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn from_hex(s: &str) -> Result<u64, SnowflakeError> {
+    if s.is_empty() || s.len() > 16 || !s.bytes().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    u64::from_str_radix(s, 16).map_err(|_| SnowflakeError::EncodingInvalid)
+}
+
+/// Encodes the given 128-bit ID into a fixed-width(`32`), zero-padded, lowercase hex string.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!("000000000000000000000000000000ff", codec::to_hex_u128(255));
+/// ```
+///
+/// @since 0.4.0
+pub fn to_hex_u128(id: u128) -> String {
+    alloc::format!("{:032x}", id)
+}
+
+/// Decodes the given hex string of at most `32` digits into a 128-bit ID, case-insensitive.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the string is empty,
+/// longer than `32` hex digits or contains non-hex characters.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(Ok(255), codec::from_hex_u128(&codec::to_hex_u128(255)).map_err(|_| ()));
+/// assert!(codec::from_hex_u128(&"f".repeat(33)).is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn from_hex_u128(s: &str) -> Result<u128, SnowflakeError> {
+    if s.is_empty() || s.len() > 32 || !s.bytes().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    u128::from_str_radix(s, 16).map_err(|_| SnowflakeError::EncodingInvalid)
+}
+
+// ---------------------------------------------------------------- sortable key
+
+/// Encodes the given ID into a fixed-width([`SORTABLE_KEY_LEN`]), zero-padded Crockford base32 key,
+/// whose lexicographic order matches the numeric order, e.g. the keys of a KV store.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!("0000000000000", codec::to_sortable_key(0));
+/// assert_eq!("FZZZZZZZZZZZZ", codec::to_sortable_key(u64::MAX));
+/// assert!(codec::to_sortable_key(9) < codec::to_sortable_key(10));
+/// ```
+///
+/// @since 0.4.0
+pub fn to_sortable_key(id: u64) -> String {
+    (0..SORTABLE_KEY_LEN)
+        .rev()
+        .map(|digit| CROCKFORD_ALPHABET[((id >> (digit * 5)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+/// Decodes the given sortable key into an ID, case-insensitive,
+/// the ambiguous `I`/`L` read as `1` and `O` as `0` like Crockford base32.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the key is not [`SORTABLE_KEY_LEN`] long,
+/// contains non-Crockford characters or exceeds `u64`.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(Ok(u64::MAX), codec::from_sortable_key("fzzzzzzzzzzzz").map_err(|_| ()));
+/// assert_eq!(Ok(1), codec::from_sortable_key("OOOOOOOOOOOOL").map_err(|_| ()));
+/// assert!(codec::from_sortable_key("G000000000000").is_err());
+/// assert!(codec::from_sortable_key("U").is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn from_sortable_key(key: &str) -> Result<u64, SnowflakeError> {
+    if key.len() != SORTABLE_KEY_LEN {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    key.bytes().try_fold(0u64, |acc, ch| {
+        let digit = match ch.to_ascii_uppercase() {
+            b'I' | b'L' => 1,
+            b'O' => 0,
+            ch => CROCKFORD_ALPHABET
+                .iter()
+                .position(|&c| c == ch)
+                .ok_or(SnowflakeError::EncodingInvalid)? as u64,
+        };
+
+        // The leading digit carries only the top 4 bits.
+        if acc >> 59 != 0 {
+            return Err(SnowflakeError::EncodingInvalid);
+        }
+
+        Ok((acc << 5) | digit)
+    })
+}
+
+// ---------------------------------------------------------------- bytes
+
+/// Encodes the given ID into a fixed 8-byte big-endian array.
+///
+/// The big-endian bytes sort lexicographically in the same order as the IDs numerically,
+/// e.g. the keys of an LSM-tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!([0, 0, 0, 0, 0, 0, 0, 255], codec::id_to_be_bytes(255));
+/// assert!(codec::id_to_be_bytes(255) < codec::id_to_be_bytes(256));
+/// ```
+///
+/// @since 0.4.0
+pub fn id_to_be_bytes(id: u64) -> [u8; 8] {
+    id.to_be_bytes()
+}
+
+/// Decodes the given 8-byte big-endian array into an ID, see [`id_to_be_bytes`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(255, codec::id_from_be_bytes([0, 0, 0, 0, 0, 0, 0, 255]));
+/// ```
+///
+/// @since 0.4.0
+pub fn id_from_be_bytes(bytes: [u8; 8]) -> u64 {
+    u64::from_be_bytes(bytes)
+}
+
+// ---------------------------------------------------------------- radix
+
+/// Encodes the given ID in the given `radix` (`2..=36`) with the lowercase digits `[0-9a-z]`,
+/// left-padded with the `pad` char to the `width`.
+///
+/// The ID is never truncated: a `width` smaller than the encoded length returns the full string.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::RadixInvalid`] if the `radix` out of range `2..=36`.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!("00000000000000ff", codec::to_radix(255, 16, 16, '0').unwrap());
+/// assert_eq!("__zz", codec::to_radix(1295, 36, 4, '_').unwrap());
+/// assert_eq!("11111111", codec::to_radix(255, 2, 4, '0').unwrap());
+/// assert!(codec::to_radix(255, 37, 4, '0').is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn to_radix(id: u64, radix: u32, width: usize, pad: char) -> Result<String, SnowflakeError> {
+    if !(2..=36).contains(&radix) {
+        return Err(SnowflakeError::RadixInvalid);
+    }
+
+    let mut digits = Vec::with_capacity(64);
+    let mut value = id;
+    loop {
+        let digit = (value % radix as u64) as u32;
+        digits.push(char::from_digit(digit, radix).expect("digit within radix"));
+        value /= radix as u64;
+        if value == 0 {
+            break;
+        }
+    }
+
+    let mut buf = String::with_capacity(width.max(digits.len()));
+    for _ in digits.len()..width {
+        buf.push(pad);
+    }
+    buf.extend(digits.iter().rev());
+
+    Ok(buf)
+}
+
+// ---------------------------------------------------------------- prefix
+
+/// Parses the given decimal ID string with the given `prefix` attached,
+/// the inverse of [`crate::generator::SnowflakeGenerator::next_id_prefixed`].
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the `prefix` is missing
+/// or the rest is not a decimal `u64`.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(255, codec::strip_prefix_parse("ORD-255", "ORD-").unwrap());
+/// assert!(codec::strip_prefix_parse("255", "ORD-").is_err());
+/// assert!(codec::strip_prefix_parse("INV-255", "ORD-").is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn strip_prefix_parse(s: &str, prefix: &str) -> Result<u64, SnowflakeError> {
+    strip_prefix_parse_radix(s, prefix, 10)
+}
+
+/// Parses the given ID string in the given `radix` (`2..=36`) with the given `prefix` attached,
+/// the inverse of [`crate::generator::SnowflakeGenerator::next_id_prefixed_radix`].
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::RadixInvalid`] if the `radix` out of range `2..=36`,
+/// [`SnowflakeError::EncodingInvalid`] if the `prefix` is missing or the rest is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(1295, codec::strip_prefix_parse_radix("ORD-zz", "ORD-", 36).unwrap());
+/// assert!(codec::strip_prefix_parse_radix("zz", "ORD-", 36).is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn strip_prefix_parse_radix(s: &str, prefix: &str, radix: u32) -> Result<u64, SnowflakeError> {
+    if !(2..=36).contains(&radix) {
+        return Err(SnowflakeError::RadixInvalid);
+    }
+
+    let encoded = s
+        .strip_prefix(prefix)
+        .ok_or(SnowflakeError::EncodingInvalid)?;
+    if encoded.starts_with('+') {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    u64::from_str_radix(encoded, radix).map_err(|_| SnowflakeError::EncodingInvalid)
+}
+
+// ---------------------------------------------------------------- any
+
+/// Parses the given ID string in any supported encoding, trying in order:
+///
+/// 1. decimal, e.g. `"255"`
+/// 2. `0x`/`0X` prefixed hex, e.g. `"0xff"`
+/// 3. base62, e.g. `"47"`
+///
+/// The first encoding which fully consumes the input and fits `u64` wins,
+/// so an ambiguous string, e.g. `"10"`, resolves as decimal.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the string is invalid in all of them.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert_eq!(255, codec::parse_any("255").unwrap());
+/// assert_eq!(255, codec::parse_any("0xff").unwrap());
+/// assert_eq!(u64::MAX, codec::parse_any("LygHa16AHYF").unwrap());
+/// // valid in all of them, decimal wins
+/// assert_eq!(10, codec::parse_any("10").unwrap());
+/// assert!(codec::parse_any("not-an-id").is_err());
+/// ```
+///
+/// @since 0.4.0
+pub fn parse_any(s: &str) -> Result<u64, SnowflakeError> {
+    if !s.is_empty() && s.bytes().all(|ch| ch.is_ascii_digit()) {
+        if let Ok(id) = s.parse::<u64>() {
+            return Ok(id);
+        }
+    }
+
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        if let Ok(id) = from_hex(hex) {
+            return Ok(id);
+        }
+    }
+
+    decode_base62(s)
+}
+
+/// Decomposes a slice of IDs in bulk by the given [`BitLayout`], like [`BitLayout::decompose`] on each of them.
+///
+/// The shifts and the masks are computed once and applied across the slice.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+/// use snowflaker::generator::{BitLayout, SnowflakeGenerator};
+///
+/// let layout = BitLayout::default();
+/// let ids = [SnowflakeGenerator::builtin().unwrap().next_id_with_timestamp(1_700_000_000_000).unwrap()];
+///
+/// let parts = codec::decompose_many(&ids, &layout);
+/// assert_eq!(vec![layout.decompose(ids[0])], parts);
+/// assert!(codec::decompose_many(&[], &layout).is_empty());
+/// ```
+///
+/// @since 0.4.0
+pub fn decompose_many(ids: &[u64], layout: &BitLayout) -> Vec<IdParts> {
+    let timestamp_shift = layout.timestamp_shift();
+    let timestamp_mask = layout.timestamp_mask();
+    let center_id_shift = layout.center_id_shift();
+    let worker_id_shift = layout.worker_id_shift();
+    let center_id_mask = layout.max_data_center_id();
+    let worker_id_mask = layout.max_worker_id();
+    let sequence_shift = layout.sequence_shift();
+    let sequence_mask = layout.sequence_mask();
+    let tag_mask = layout.max_tag();
+
+    ids.iter()
+        .map(|&id| IdParts {
+            timestamp: (id >> timestamp_shift) & timestamp_mask,
+            center_id: (id >> center_id_shift) & center_id_mask,
+            worker_id: (id >> worker_id_shift) & worker_id_mask,
+            sequence: (id >> sequence_shift) & sequence_mask,
+            tag: id & tag_mask,
+        })
+        .collect()
+}
+
+/// Returns the inclusive `(min, max)` ID range of the IDs generated under the given [`BitLayout`]
+/// and epoch between `start_millis` and `end_millis`, e.g. `WHERE id BETWEEN min AND max`.
+///
+/// The min ID packs the start time with all the lower fields zero, the max ID packs the end time
+/// with all the lower fields set. The bounds are swapped if the start is after the end,
+/// and clamped into the epoch and [`BitLayout::max_timestamp`].
+///
+/// Note: only meaningful for the time-sortable [`PackingOrder::TimestampHigh`](crate::generator::PackingOrder::TimestampHigh)
+/// in [`TimePrecision::Millis`](crate::generator::TimePrecision::Millis).
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use snowflaker::codec;
+/// use snowflaker::generator::{BitLayout, Constants, Generator, SnowflakeGenerator};
+///
+/// let id = SnowflakeGenerator::builtin().unwrap().next_id().unwrap();
+/// let millis = SnowflakeGenerator::timestamp_of(id);
+///
+/// let (min, max) = codec::id_range_for_time(millis, millis, &BitLayout::default(), Constants::EPOCH);
+/// assert!(min <= id && id <= max);
+/// # }
+/// ```
+///
+/// @since 0.4.0
+pub fn id_range_for_time(
+    start_millis: u64,
+    end_millis: u64,
+    layout: &BitLayout,
+    epoch: u64,
+) -> (u64, u64) {
+    let ticks = |millis: u64| millis.saturating_sub(epoch).min(layout.max_timestamp());
+    let (start, end) = if start_millis <= end_millis {
+        (ticks(start_millis), ticks(end_millis))
+    } else {
+        (ticks(end_millis), ticks(start_millis))
+    };
+
+    let min = layout.compose(start, 0, 0, 0);
+    let max = layout.compose(
+        end,
+        layout.max_data_center_id(),
+        layout.max_worker_id(),
+        layout.sequence_mask(),
+    ) | layout.max_tag();
+
+    (min, max)
+}
+
+// ---------------------------------------------------------------- wait
+
+/// Blocks until the wall-clock catches up to the timestamp embedded in the given ID,
+/// i.e. `now_millis() >= SnowflakeGenerator::timestamp_of(id)`, e.g. to assert the causal ordering.
+///
+/// Returns immediately if the timestamp is already in the past.
+/// The timestamp is decoded with the default layout and [`Constants::EPOCH`](crate::generator::Constants::EPOCH).
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::SystemTimeError`] if the `SystemTime` was before the UNIX EPOCH.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+/// use snowflaker::generator::{Generator, SnowflakeGenerator};
+///
+/// let id = SnowflakeGenerator::builtin().unwrap().next_id().unwrap();
+/// assert!(codec::wait_until_current(id).is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+pub fn wait_until_current(id: u64) -> Result<(), SnowflakeError> {
+    let target = SnowflakeGenerator::timestamp_of(id);
+    loop {
+        let now = now_millis()?;
+        if now >= target {
+            return Ok(());
+        }
+
+        TimeUnit::Milliseconds.sleep(target - now);
+    }
+}
+
+// ---------------------------------------------------------------- uuid
+
+/// The version nibble of the UUID-shaped strings: `8`, the custom UUID of RFC 9562.
+#[cfg(feature = "uuid")]
+const UUID_VERSION: u128 = 0x8;
+/// The variant bits of the UUID-shaped strings: `0b10`, the RFC 9562 variant.
+#[cfg(feature = "uuid")]
+const UUID_VARIANT: u128 = 0b10;
+/// The bits of the second ID carried, the node bits are shared with the first one.
+#[cfg(feature = "uuid")]
+const UUID_SECOND_BITS: u32 =
+    (Constants::ID_BITS - Constants::DATA_CENTER_ID_BITS - Constants::WORKER_ID_BITS) as u32;
+
+/// Encodes two consecutive IDs of the same generator into a canonical `8-4-4-4-12` UUID-shaped
+/// lowercase hex string, which passes the standard UUID parsers: version `8`, variant `0b10`.
+///
+/// The IDs are in the default `5/5/12` layout, see [`Constants`]. The first ID is carried in full,
+/// the second one without its `data-center`/`worker` bits which must equal the first ID's,
+/// see [`decode_uuid_like`].
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if any ID is negative as `i64`,
+/// or the IDs were generated by different nodes.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+/// use snowflaker::generator::SnowflakeGenerator;
+///
+/// let gen = SnowflakeGenerator::new(3, 7).unwrap();
+/// let first = gen.next_id_with_timestamp(1_700_000_000_000).unwrap();
+/// let second = gen.next_id_with_timestamp(1_700_000_000_000).unwrap();
+///
+/// let uuid = codec::encode_uuid_like(first, second).unwrap();
+/// assert_eq!(36, uuid.len());
+/// assert_eq!(Some('8'), uuid.chars().nth(14));
+/// assert_eq!((first, second), codec::decode_uuid_like(&uuid).unwrap());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "uuid")]
+pub fn encode_uuid_like(first: u64, second: u64) -> Result<String, SnowflakeError> {
+    let node_mask = mask(Constants::TIMESTAMP_SHIFT) & !Constants::SEQUENCE_MASK;
+    if first >> Constants::ID_BITS != 0
+        || second >> Constants::ID_BITS != 0
+        || first & node_mask != second & node_mask
+    {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    // | timestamp | sequence | of the second ID
+    let second = ((second >> Constants::TIMESTAMP_SHIFT) << Constants::SEQUENCE_BITS)
+        | (second & Constants::SEQUENCE_MASK);
+    let payload = ((first as u128) << UUID_SECOND_BITS) | second as u128;
+
+    // | payload: 48 bits | version: 4 bits | payload: 12 bits | variant: 2 bits | payload: 62 bits |
+    let uuid = ((payload >> 74) << 80)
+        | (UUID_VERSION << 76)
+        | (((payload >> 62) & 0xFFF) << 64)
+        | (UUID_VARIANT << 62)
+        | (payload & !(!0 << 62));
+
+    let hex = to_hex_u128(uuid);
+    Ok(alloc::format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+/// Decodes a UUID-shaped string of [`encode_uuid_like`] back into the two IDs, case-insensitive.
+///
+/// # Errors
+///
+/// Returns [`SnowflakeError::EncodingInvalid`] if the string is not a canonical `8-4-4-4-12`
+/// hex string, or the version/variant/payload bits are not the ones of [`encode_uuid_like`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::codec;
+///
+/// assert!(codec::decode_uuid_like("not-a-uuid").is_err());
+/// // a random (version 4) UUID
+/// assert!(codec::decode_uuid_like("67e55044-10b1-426f-9247-bb680e5fe0c8").is_err());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "uuid")]
+pub fn decode_uuid_like(s: &str) -> Result<(u64, u64), SnowflakeError> {
+    let groups: Vec<&str> = s.split('-').collect();
+    if groups.iter().map(|group| group.len()).ne([8, 4, 4, 4, 12]) {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    let uuid = from_hex_u128(&groups.concat())?;
+    if (uuid >> 76) & 0xF != UUID_VERSION || (uuid >> 62) & 0b11 != UUID_VARIANT {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    let payload = ((uuid >> 80) << 74) | (((uuid >> 64) & 0xFFF) << 62) | (uuid & !(!0 << 62));
+    if payload >> (Constants::ID_BITS as u32 + UUID_SECOND_BITS) != 0 {
+        return Err(SnowflakeError::EncodingInvalid);
+    }
+
+    let first = (payload >> UUID_SECOND_BITS) as u64;
+    let second = (payload as u64) & !(!0 << UUID_SECOND_BITS);
+    let node_mask = mask(Constants::TIMESTAMP_SHIFT) & !Constants::SEQUENCE_MASK;
+    let second = ((second >> Constants::SEQUENCE_BITS) << Constants::TIMESTAMP_SHIFT)
+        | (first & node_mask)
+        | (second & Constants::SEQUENCE_MASK);
+
+    Ok((first, second))
+}
@@ -0,0 +1,76 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! ID comparison helpers, operate purely on the timestamp bits of the default layout,
+//! independent of any generator instance.
+
+// ----------------------------------------------------------------
+
+use crate::generator::Constants;
+
+// ----------------------------------------------------------------
+
+/// Returns the raw timestamp bits of the given ID, relative to the epoch.
+fn timestamp_of(id: u64) -> u64 {
+    id >> Constants::TIMESTAMP_SHIFT
+}
+
+/// Returns how many milliseconds the ID `a` was created after the ID `b`,
+/// negative if `a` was created before `b`, `0` if both within the same millisecond.
+///
+/// Both IDs must share the same epoch, which cancels out.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::compare;
+/// use snowflaker::generator::Constants;
+///
+/// let a = 1000 << Constants::TIMESTAMP_SHIFT;
+/// let b = (1010 << Constants::TIMESTAMP_SHIFT) | 1;
+/// assert_eq!(-10, compare::time_diff_millis(a, b));
+/// assert_eq!(10, compare::time_diff_millis(b, a));
+/// assert_eq!(0, compare::time_diff_millis(b, b + 1));
+/// ```
+///
+/// @since 0.4.0
+pub fn time_diff_millis(a: u64, b: u64) -> i64 {
+    timestamp_of(a) as i64 - timestamp_of(b) as i64
+}
+
+/// Returns whether the ID `a` was created in an earlier millisecond than the ID `b`.
+///
+/// IDs within the same millisecond are not before each other, regardless of the sequence.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::compare;
+/// use snowflaker::generator::Constants;
+///
+/// let a = 1000 << Constants::TIMESTAMP_SHIFT;
+/// let b = 1001 << Constants::TIMESTAMP_SHIFT;
+/// assert!(compare::is_before(a, b));
+/// assert!(!compare::is_before(b, a));
+/// assert!(!compare::is_before(a, a + 1));
+/// ```
+///
+/// @since 0.4.0
+pub fn is_before(a: u64, b: u64) -> bool {
+    timestamp_of(a) < timestamp_of(b)
+}
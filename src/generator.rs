@@ -16,44 +16,302 @@
 
 // ----------------------------------------------------------------
 
+use alloc::format;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::fmt::Write;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use core::hint;
+use core::str::FromStr;
+use core::time::Duration;
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::fmt;
-use std::fmt::{Display, Formatter};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(feature = "std")]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+#[cfg(feature = "std")]
 use chronounit::TimeUnit;
+use portable_atomic::{AtomicU64, Ordering};
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+use crate::clock::{Clock, SystemClock};
+use crate::identity::IdentityProvider;
+#[cfg(feature = "dynamic")]
+use crate::identity::MacIdentityProvider;
+#[cfg(feature = "std")]
+use crate::metrics::Metrics;
+#[cfg(feature = "std")]
+use crate::verify;
 
 #[cfg(feature = "dynamic")]
 use crate::infras;
+#[cfg(feature = "registry")]
+use crate::registry;
 
 // ----------------------------------------------------------------
 
 /// [`SnowflakeError`] Snowflake custom enum error.
-#[derive(Debug, Clone)]
+///
+/// @since 0.4.0 implements [`PartialEq`] and [`Eq`]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SnowflakeError {
     CenterIdInvalid,
     WorkerIdInvalid,
-    SystemTimeError,
+    /// The `SystemTime` was before the UNIX EPOCH by the carried [`Duration`].
+    ///
+    /// @since 0.4.0 carries the [`Duration`]
+    SystemTimeError(Duration),
     ClockMovedBackwards,
+    /// @since 0.4.0
+    EpochInvalid,
+    /// @since 0.4.0
+    EncodingInvalid,
+    /// @since 0.4.0
+    AlreadyInitialized,
+    /// The bit layout overflows, carries the offending layout for the description.
+    ///
+    /// @since 0.4.0
+    LayoutInvalid(BitLayout),
+    /// @since 0.4.0
+    SequenceExhausted,
+    /// The timestamp since the epoch exceeds the timestamp bits of the layout.
+    ///
+    /// @since 0.4.0
+    TimestampOverflow,
+    /// The embedded timestamp of an ID is out of the range between the epoch and now.
+    ///
+    /// @since 0.4.0
+    TimestampInvalid,
+    /// The radix out of range `2..=36`.
+    ///
+    /// @since 0.4.0
+    RadixInvalid,
+    /// The `(data-center ID, worker ID)` node was already registered.
+    ///
+    /// @since 0.4.0
+    DuplicateNode(u64, u64),
+    /// The node file is missing, unreadable or malformed, carries the description.
+    ///
+    /// @since 0.4.0
+    NodeFileInvalid(String),
+    /// The config string is malformed, carries the description.
+    ///
+    /// @since 0.4.0
+    ConfigInvalid(String),
+    /// The shard is not less than the shard count, or the shard count out of the sequence space.
+    ///
+    /// @since 0.4.0
+    ShardInvalid,
+    /// The tag exceeds the tag bits of the layout.
+    ///
+    /// @since 0.4.0
+    TagInvalid,
+    /// The lock of a global generator was poisoned by a panic while it was held.
+    ///
+    /// @since 0.4.0
+    LockPoisoned,
+    /// The clock reads a timestamp before the epoch of the generator, or a timestamp
+    /// before it is supplied, e.g. to [`crate::generator128::SnowflakeGenerator128::next_id_with_timestamp`].
+    ///
+    /// @since 0.4.0
+    ClockBeforeEpoch,
+    /// No named global generator was registered with the name.
+    ///
+    /// @since 0.4.0
+    GeneratorNotFound,
+    /// The IDs generated within the current second reached the cap,
+    /// see [`SnowflakeGeneratorBuilder::max_per_second`].
+    ///
+    /// @since 0.4.0
+    RateLimited,
+    /// The generator failed its diagnostic self-test, the reason is carried,
+    /// see [`SnowflakeGenerator::self_test`].
+    ///
+    /// @since 0.4.0
+    SelfTestFailed(String),
+}
+
+impl SnowflakeError {
+    /// Returns `true` if the failure is transient and a retry may succeed,
+    /// e.g. [`SnowflakeError::ClockMovedBackwards`], [`SnowflakeError::SystemTimeError`]
+    /// and [`SnowflakeError::SequenceExhausted`].
+    ///
+    /// Returns `false` for the permanent failures, e.g. the ID-range, the layout and the config errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeError;
+    ///
+    /// assert!(SnowflakeError::ClockMovedBackwards.is_retryable());
+    /// assert!(!SnowflakeError::WorkerIdInvalid.is_retryable());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SnowflakeError::ClockMovedBackwards
+            | SnowflakeError::SystemTimeError(_)
+            | SnowflakeError::SequenceExhausted
+            | SnowflakeError::RateLimited => true,
+            SnowflakeError::CenterIdInvalid
+            | SnowflakeError::WorkerIdInvalid
+            | SnowflakeError::EpochInvalid
+            | SnowflakeError::EncodingInvalid
+            | SnowflakeError::AlreadyInitialized
+            | SnowflakeError::LayoutInvalid(_)
+            | SnowflakeError::TimestampOverflow
+            | SnowflakeError::TimestampInvalid
+            | SnowflakeError::RadixInvalid
+            | SnowflakeError::DuplicateNode(..)
+            | SnowflakeError::NodeFileInvalid(_)
+            | SnowflakeError::ConfigInvalid(_)
+            | SnowflakeError::ShardInvalid
+            | SnowflakeError::TagInvalid
+            | SnowflakeError::LockPoisoned
+            | SnowflakeError::ClockBeforeEpoch
+            | SnowflakeError::GeneratorNotFound
+            | SnowflakeError::SelfTestFailed(_) => false,
+        }
+    }
 }
 
 impl Display for SnowflakeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match *self {
+        match self {
             SnowflakeError::CenterIdInvalid => write!(f, "Data Center ID out of range"),
             SnowflakeError::WorkerIdInvalid => write!(f, "Worker ID out of range"),
-            SnowflakeError::SystemTimeError => write!(f, "SystemTime before UNIX EPOCH!"),
+            SnowflakeError::SystemTimeError(duration) => {
+                write!(f, "SystemTime before UNIX EPOCH! ({:?} behind)", duration)
+            }
             SnowflakeError::ClockMovedBackwards => {
                 write!(f, "Clock moved backwards. Refusing to generate id")
             }
+            SnowflakeError::EpochInvalid => write!(f, "Epoch is in the future"),
+            SnowflakeError::EncodingInvalid => write!(f, "Encoded ID invalid"),
+            SnowflakeError::AlreadyInitialized => write!(f, "Generator already initialized"),
+            SnowflakeError::LayoutInvalid(layout) => {
+                write!(f, "Bit layout out of range: ")?;
+                layout.fmt_overflow(f)
+            }
+            SnowflakeError::SequenceExhausted => {
+                write!(f, "Sequence exhausted within the current millisecond")
+            }
+            SnowflakeError::TimestampOverflow => {
+                write!(
+                    f,
+                    "Timestamp exceeds the available bits. Refusing to generate id"
+                )
+            }
+            SnowflakeError::TimestampInvalid => write!(f, "Timestamp of the ID out of range"),
+            SnowflakeError::RadixInvalid => write!(f, "Radix out of range 2..=36"),
+            SnowflakeError::DuplicateNode(center_id, worker_id) => write!(
+                f,
+                "Node (data-center ID: {}, worker ID: {}) already registered",
+                center_id, worker_id
+            ),
+            SnowflakeError::NodeFileInvalid(reason) => write!(f, "Node file invalid: {}", reason),
+            SnowflakeError::ConfigInvalid(reason) => write!(f, "Config invalid: {}", reason),
+            SnowflakeError::ShardInvalid => write!(f, "Shard out of range"),
+            SnowflakeError::TagInvalid => write!(f, "Tag exceeds the tag bits"),
+            SnowflakeError::LockPoisoned => write!(f, "Generator lock poisoned"),
+            SnowflakeError::ClockBeforeEpoch => {
+                write!(f, "Clock before the epoch. Refusing to generate id")
+            }
+            SnowflakeError::GeneratorNotFound => write!(f, "Named generator not found"),
+            SnowflakeError::RateLimited => {
+                write!(f, "Rate limit reached within the current second")
+            }
+            SnowflakeError::SelfTestFailed(reason) => write!(f, "Self-test failed: {}", reason),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl Error for SnowflakeError {}
 
+/// @since 0.4.0
+#[cfg(feature = "std")]
+impl From<std::time::SystemTimeError> for SnowflakeError {
+    fn from(err: std::time::SystemTimeError) -> Self {
+        SnowflakeError::SystemTimeError(err.duration())
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// Returns the mask of the low `bits`, saturating at 64 bits.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::mask;
+///
+/// assert_eq!(4095, mask(12));
+/// assert_eq!(0, mask(0));
+/// assert_eq!(u64::MAX, mask(64));
+/// ```
+///
+/// @since 0.4.0
+pub const fn mask(bits: u64) -> u64 {
+    if bits >= u64::BITS as u64 {
+        return u64::MAX;
+    }
+
+    !(!0 << bits)
+}
+
+/// Returns the max value of a field of `bits`, e.g. the max `worker` ID of 5 bits: 31.
+///
+/// @since 0.4.0
+pub const fn max_id(bits: u64) -> u64 {
+    mask(bits)
+}
+
+/// Returns the left shift of a field packed above the fields of the given `bits`,
+/// e.g. the timestamp shift above the `data-center`, `worker` and sequence fields: 22.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::shift_for;
+///
+/// assert_eq!(12, shift_for(&[12]));
+/// assert_eq!(22, shift_for(&[12, 5, 5]));
+/// ```
+///
+/// @since 0.4.0
+pub const fn shift_for(lower_bits: &[u64]) -> u64 {
+    let mut shift = 0;
+    let mut i = 0;
+    while i < lower_bits.len() {
+        shift += lower_bits[i];
+        i += 1;
+    }
+
+    shift
+}
+
 // ----------------------------------------------------------------
 
 /// [`Constants`] Generator common constants.
@@ -63,6 +321,16 @@ impl Constants {
     /// `EPOCH` `2023-04-05 06:07:08`
     pub const EPOCH: u64 = 1680646028000;
 
+    /// `TWITTER_EPOCH` the epoch of the original Twitter snowflake: `2010-11-04 01:42:54.657`
+    ///
+    /// @since 0.4.0
+    pub const TWITTER_EPOCH: u64 = 1288834974657;
+
+    /// `COMPACT_EPOCH` the recent epoch of [`SnowflakeGenerator::compact`]: `2026-01-01 00:00:00`
+    ///
+    /// @since 0.4.0
+    pub const COMPACT_EPOCH: u64 = 1767225600000;
+
     /// `DATA_CENTER_ID_BITS` data-center bits: 5
     pub const DATA_CENTER_ID_BITS: u64 = 5;
     /// `WORKER_ID_BITS` worker bits: 5
@@ -72,337 +340,4348 @@ impl Constants {
     pub const SEQUENCE_BITS: u64 = 12;
 
     /// `MAX_DATA_CENTER_ID` max data-center ID: 31
-    pub const MAX_DATA_CENTER_ID: u64 = !(!0 << Constants::DATA_CENTER_ID_BITS);
+    pub const MAX_DATA_CENTER_ID: u64 = max_id(Constants::DATA_CENTER_ID_BITS);
     /// `SEQUENCE_MASK` max worker ID: 31
-    pub const MAX_WORKER_ID: u64 = !(!0 << Constants::WORKER_ID_BITS);
+    pub const MAX_WORKER_ID: u64 = max_id(Constants::WORKER_ID_BITS);
 
     /// `SEQUENCE_MASK` sequence mask: 4095
-    pub const SEQUENCE_MASK: u64 = !(!0 << Constants::SEQUENCE_BITS);
+    pub const SEQUENCE_MASK: u64 = mask(Constants::SEQUENCE_BITS);
 
     /// `WORKER_ID_SHIFT` worker ID shift: 12
-    pub const WORKER_ID_SHIFT: u64 = Constants::SEQUENCE_BITS;
+    pub const WORKER_ID_SHIFT: u64 = shift_for(&[Constants::SEQUENCE_BITS]);
     /// `CENTER_ID_SHIFT` center ID shift: 17
-    pub const CENTER_ID_SHIFT: u64 = Constants::SEQUENCE_BITS + Constants::WORKER_ID_BITS;
+    pub const CENTER_ID_SHIFT: u64 =
+        shift_for(&[Constants::SEQUENCE_BITS, Constants::WORKER_ID_BITS]);
 
     /// `TIMESTAMP_SHIFT` timestamp left shift: 22
-    pub const TIMESTAMP_SHIFT: u64 =
-        Constants::DATA_CENTER_ID_BITS + Constants::WORKER_ID_BITS + Constants::SEQUENCE_BITS;
+    pub const TIMESTAMP_SHIFT: u64 = shift_for(&[
+        Constants::SEQUENCE_BITS,
+        Constants::WORKER_ID_BITS,
+        Constants::DATA_CENTER_ID_BITS,
+    ]);
+
+    /// `MAX_LAYOUT_BITS` max bits of the data-center, worker and sequence fields: 22
+    ///
+    /// @since 0.4.0
+    pub const MAX_LAYOUT_BITS: u64 = Constants::TIMESTAMP_SHIFT;
 
     // ----------------------------------------------------------------
 
+    /// `ID_BITS` the usable bits of an ID, the sign bit is reserved: 63
+    ///
+    /// @since 0.4.0
+    pub const ID_BITS: u64 = 63;
+
+    /// `COMPACT_ID_BITS` the usable bits of an ID of [`SnowflakeGenerator::compact`]: 48
+    ///
+    /// @since 0.4.0
+    pub const COMPACT_ID_BITS: u64 = 48;
+
     /// `DEFAULT_DATA_CENTER_ID` default data-center ID: 1
     pub const DEFAULT_DATA_CENTER_ID: u64 = 1;
 
     /// `DEFAULT_WORKER_ID` default worker ID: 1
     pub const DEFAULT_WORKER_ID: u64 = 1;
+
+    /// `DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS` default max clock-backwards tolerance: 8ms
+    ///
+    /// @since 0.4.0
+    pub const DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS: u64 = 1 << 3;
 }
 
 // ----------------------------------------------------------------
 
-/// Unique ID generator trait
-pub trait Generator {
-    /// Generate next ID.
-    fn next_id(&self) -> Result<u64, SnowflakeError>;
+/// [`WaitStrategy`] How the generator waits for the clock to catch up
+/// when the clock moved backwards within the tolerance.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WaitStrategy {
+    /// Sleep the current thread for twice the backwards delta, then re-read the clock.
+    #[default]
+    Sleep,
+    /// Busy-wait polling the clock until it catches up, at most twice the backwards delta.
+    Spin,
+}
 
-    /// Get current timestamp.
-    fn time_gen() -> Result<u64, SnowflakeError>;
+// ----------------------------------------------------------------
 
-    /// Get next timestamp.
-    fn til_next_millis(last_timestamp: u64) -> Result<u64, SnowflakeError>;
+/// [`SpinStrategy`] How the generator polls the clock for the next millisecond
+/// when the sequence exhausted within the current one.
+///
+/// It returns only once the clock passed the last timestamp, no matter which strategy.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SpinStrategy {
+    /// Yield the current thread between polls, see [`std::thread::yield_now`].
+    #[default]
+    Yield,
+    /// Hint the processor between polls, see [`core::hint::spin_loop`].
+    Hint,
+    /// Busy-loop polling without any pause, pins a core until the millisecond rolls over.
+    Busy,
+}
+
+#[cfg(feature = "std")]
+impl SpinStrategy {
+    /// Pauses between two polls of the clock.
+    fn pause(&self) {
+        match self {
+            SpinStrategy::Yield => std::thread::yield_now(),
+            SpinStrategy::Hint => hint::spin_loop(),
+            SpinStrategy::Busy => {}
+        }
+    }
 }
 
 // ----------------------------------------------------------------
 
-/// The builtin impl of [`Generator`]
-#[derive(Clone, Debug)] // @since 0.3.6
-pub struct SnowflakeGenerator {
-    center_id: u64,
-    worker_id: u64,
-    /// issue#https:///github.com/photowey/snowflake/issues/16
-    ///
-    /// ### planA
-    /// `AtomicU64` wrapped by `Arc<T>`
-    /// |- Support multi-thread
-    /// |- -> Ok
-    ///
-    /// ```rust
-    /// use std::sync::Arc;
-    /// use std::sync::atomic::AtomicU64;
-    ///
-    /// #[derive(Clone, Debug)]
-    /// pub struct SnowflakeGenerator {
-    ///     center_id: u64,
-    ///     worker_id: u64,
-    ///     sequence: Arc<AtomicU64>,
-    ///     last_timestamp: Arc<AtomicU64>,
-    /// }
-    /// ```
-    ///
-    /// ### planB
-    /// Customize the struct `CloneableAtomicU64` for the [`Clone`] trait
-    /// |- `CloneableAtomicU64` does not support multi-thread
-    /// |- -> PASS
-    ///
-    /// ```rust
-    /// use std::sync::atomic::{AtomicU64, Ordering};
-    ///
-    /// #[derive(Debug)]
-    /// struct CloneableAtomicU64(AtomicU64);
-    ///
-    /// impl Clone for CloneableAtomicU64 {
-    ///     fn clone(&self) -> Self {
-    ///         CloneableAtomicU64(AtomicU64::new(self.0.load(Ordering::SeqCst)))
-    ///     }
-    /// }
-    ///
-    /// impl CloneableAtomicU64 {
-    ///     fn new(value: u64) -> Self {
-    ///         CloneableAtomicU64(AtomicU64::new(value))
-    ///     }
-    ///
-    ///     fn load(&self, ordering: Ordering) -> u64 {
-    ///         self.0.load(ordering)
-    ///     }
-    ///
-    ///     fn store(&self, value: u64, ordering: Ordering) {
-    ///         self.0.store(value, ordering)
-    ///     }
-    /// }
-    ///
-    /// #[derive(Debug)]
-    /// struct SnowflakeGenerator {
-    ///     center_id: u64,
-    ///     worker_id: u64,
-    ///     sequence: CloneableAtomicU64,
-    ///     last_timestamp: CloneableAtomicU64,
-    /// }
-    ///
-    /// impl Clone for SnowflakeGenerator {
-    ///     fn clone(&self) -> Self {
-    ///         Self {
-    ///             center_id: self.center_id,
-    ///             worker_id: self.worker_id,
-    ///             // clone: Will be relatively independent
-    ///             sequence: self.sequence.clone(),
-    ///             last_timestamp: self.last_timestamp.clone(),
-    ///         }
-    ///     }
-    /// }
-    /// ```
-    ///
-    /// @since 0.3.6
+/// [`OverflowPolicy`] What the generator does when the sequence exhausted within the current millisecond.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Wait for the next millisecond by the [`SpinStrategy`], then generate.
+    #[default]
+    WaitNextMillis,
+    /// Return [`SnowflakeError::SequenceExhausted`] immediately, so a latency-sensitive caller can back off.
+    Error,
+}
+
+// ----------------------------------------------------------------
+
+/// [`ClockBackwardsDecision`] What the generator does when the clock moved backwards,
+/// decided by the hook of [`SnowflakeGeneratorBuilder::on_clock_backwards`].
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClockBackwardsDecision {
+    /// Sleep for the delta, then re-read the clock and retry, the hook is consulted again
+    /// if the clock is still behind.
+    WaitThenRetry,
+    /// Return [`SnowflakeError::ClockMovedBackwards`] immediately.
+    Fail,
+    /// Generate at the last timestamp instead of the clock, the IDs stay strictly increasing,
+    /// returns [`SnowflakeError::SequenceExhausted`] once its sequence runs out.
+    ProceedWithLastTimestamp,
+}
+
+/// The hook deciding on the clock-backwards delta in millis,
+/// see [`SnowflakeGeneratorBuilder::on_clock_backwards`].
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+pub type ClockBackwardsHook = Arc<dyn Fn(u64) -> ClockBackwardsDecision + Send + Sync>;
+
+// ----------------------------------------------------------------
+
+/// Caps the IDs generated per second of the timestamps, shared between the clones,
+/// see [`SnowflakeGeneratorBuilder::max_per_second`].
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct RateLimiter {
+    max_per_second: u64,
+    /// The `(second, count)` of the current window.
+    window: Mutex<(u64, u64)>,
+}
+
+#[cfg(feature = "std")]
+impl RateLimiter {
+    fn new(max_per_second: u64) -> Self {
+        Self {
+            max_per_second,
+            window: Mutex::new((0, 0)),
+        }
+    }
+
+    /// Returns an independent copy of this limiter with the same window.
+    fn fork(&self) -> Self {
+        Self {
+            max_per_second: self.max_per_second,
+            window: Mutex::new(*self.lock()),
+        }
+    }
+
+    /// Takes one from the budget of the second of `now_millis`, a newer second resets the budget,
+    /// an older one, e.g. a timestamp supplied behind the clock, takes from the current window.
     ///
-    sequence: Arc<AtomicU64>,
-    last_timestamp: Arc<AtomicU64>,
+    /// Returns the second of the window taken from.
+    fn try_acquire(&self, now_millis: u64) -> Result<u64, SnowflakeError> {
+        let second = now_millis / 1000;
+        let mut window = self.lock();
+        if second > window.0 {
+            *window = (second, 0);
+        }
+
+        if window.1 >= self.max_per_second {
+            return Err(SnowflakeError::RateLimited);
+        }
+        window.1 += 1;
+
+        Ok(window.0)
+    }
+
+    /// Gives back the one taken by [`RateLimiter::try_acquire`] from the window of the `second`
+    /// if no ID was generated.
+    fn release(&self, second: u64) {
+        let mut window = self.lock();
+        if window.0 == second {
+            window.1 = window.1.saturating_sub(1);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, (u64, u64)> {
+        // The window is always consistent, a panic elsewhere never leaves it torn.
+        self.window
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 }
 
-// @since 0.3.6
-// `Getter` & `Setter` for `sequence` & `last_timestamp`
-impl SnowflakeGenerator {
-    fn increment_sequence(&self) -> u64 {
-        self.sequence.fetch_add(1, Ordering::SeqCst)
+// ----------------------------------------------------------------
+
+/// Bounds the timestamp cache to the millisecond it was read in, shared between the clones,
+/// see [`SnowflakeGeneratorBuilder::timestamp_cache`].
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct TimestampCache {
+    origin: Instant,
+    /// The micros since the origin of the last [`Clock`] read, [`TimestampCache::NEVER_READ`] before the first one.
+    read_at: AtomicU64,
+    /// The timestamp of the last [`Clock`] read, only this one is reused,
+    /// never a timestamp supplied to [`SnowflakeGenerator::next_id_with_timestamp`].
+    timestamp: AtomicU64,
+}
+
+#[cfg(feature = "std")]
+impl TimestampCache {
+    /// `NEVER_READ` the `read_at` sentinel of a cache which has not read the [`Clock`] yet
+    const NEVER_READ: u64 = u64::MAX;
+
+    fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            read_at: AtomicU64::new(Self::NEVER_READ),
+            timestamp: AtomicU64::new(0),
+        }
     }
 
-    //
-    // ---------------------------------------------------------------- getter/setter
-    //
+    /// Returns an independent copy of this cache with the same last read.
+    fn fork(&self) -> Self {
+        Self {
+            origin: self.origin,
+            read_at: AtomicU64::new(self.read_at.load(Ordering::Acquire)),
+            timestamp: AtomicU64::new(self.timestamp.load(Ordering::Acquire)),
+        }
+    }
 
-    #[allow(dead_code)]
-    pub(crate) fn get_sequence(&self) -> u64 {
-        self.sequence.load(Ordering::SeqCst)
+    fn micros(&self) -> u64 {
+        self.origin.elapsed().as_micros() as u64
     }
 
-    pub(crate) fn set_sequence(&self, value: u64) {
-        self.sequence.store(value, Ordering::SeqCst)
+    /// Records a [`Clock`] read of the given timestamp.
+    fn mark_read(&self, timestamp: u64) {
+        self.timestamp.store(timestamp, Ordering::Release);
+        self.read_at.store(self.micros(), Ordering::Release);
     }
 
-    fn get_last_timestamp(&self) -> u64 {
-        self.last_timestamp.load(Ordering::SeqCst)
+    /// Returns the timestamp of the last [`Clock`] read if it is less than a millisecond ago.
+    fn fresh_timestamp(&self) -> Option<u64> {
+        let read_at = self.read_at.load(Ordering::Acquire);
+        if read_at == Self::NEVER_READ || self.micros().saturating_sub(read_at) >= 1000 {
+            return None;
+        }
+
+        Some(self.timestamp.load(Ordering::Acquire))
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`AtomicOrdering`] The memory ordering of the atomic operations on the shared state.
+///
+/// The `last_timestamp` and `sequence` are packed into a single atomic, and each ID is derived
+/// from exactly one successful `CAS` on it. Read-modify-write operations on a single atomic
+/// are totally ordered under any memory ordering, so both options generate unique, strictly
+/// increasing IDs across clones.
+///
+/// The trade-off: [`AtomicOrdering::SeqCst`] additionally orders the state against the other
+/// `SeqCst` operations of the program, which costs full fences on some architectures (e.g. `ARM`),
+/// [`AtomicOrdering::AcquireRelease`] is cheaper but only synchronizes through the state itself.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AtomicOrdering {
+    /// Sequentially consistent, the safe default.
+    #[default]
+    SeqCst,
+    /// `Acquire` loads and `AcqRel` read-modify-writes.
+    AcquireRelease,
+}
+
+impl AtomicOrdering {
+    /// Returns the [`Ordering`] of the loads.
+    pub const fn load(&self) -> Ordering {
+        match self {
+            AtomicOrdering::SeqCst => Ordering::SeqCst,
+            AtomicOrdering::AcquireRelease => Ordering::Acquire,
+        }
     }
 
-    fn set_last_timestamp(&self, value: u64) {
-        self.last_timestamp.store(value, Ordering::SeqCst)
+    /// Returns the [`Ordering`] of the successful read-modify-writes.
+    pub const fn success(&self) -> Ordering {
+        match self {
+            AtomicOrdering::SeqCst => Ordering::SeqCst,
+            AtomicOrdering::AcquireRelease => Ordering::AcqRel,
+        }
+    }
+
+    /// Returns the [`Ordering`] of the failed read-modify-writes.
+    pub const fn failure(&self) -> Ordering {
+        self.load()
     }
 }
 
-impl SnowflakeGenerator {
-    /// Returns a new instance of [`SnowflakeGenerator`] with built-in defaults.
-    ///
-    /// This function, `builtin`, instantiates a `SnowflakeGenerator` using the predefined constants for
-    /// `data-center` ID and `worker` ID. These constants are [`Constants::DEFAULT_DATA_CENTER_ID`] and
-    /// [`Constants::DEFAULT_WORKER_ID`] respectively.
+// ----------------------------------------------------------------
+
+/// [`TimePrecision`] The unit of the timestamp bits of the generated IDs.
+///
+/// In [`TimePrecision::Seconds`] mode the timestamp bits count seconds since the epoch,
+/// the sequence is shared by the whole second and its overflow rolls to the next second.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimePrecision {
+    /// Millisecond precision, compatible with the previous versions.
+    #[default]
+    Millis,
+    /// Second precision, for low-throughput systems that want IDs to fit a smaller numeric range.
+    Seconds,
+}
+
+impl TimePrecision {
+    /// Returns the [`TimeUnit`] of this precision.
+    #[cfg(feature = "std")]
+    pub fn time_unit(&self) -> TimeUnit {
+        match self {
+            TimePrecision::Millis => TimeUnit::Milliseconds,
+            TimePrecision::Seconds => TimeUnit::Seconds,
+        }
+    }
+
+    /// Returns the millis of one tick of this precision.
+    pub const fn unit_millis(&self) -> u64 {
+        match self {
+            TimePrecision::Millis => 1,
+            TimePrecision::Seconds => 1000,
+        }
+    }
+
+    /// Truncates the given unix-millis down to this precision.
+    pub const fn truncate(&self, millis: u64) -> u64 {
+        millis - millis % self.unit_millis()
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`PackingOrder`] The order of the fields packed into an ID from high to low,
+/// see [`BitLayout::with_packing_order`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PackingOrder {
+    /// `| timestamp | data-center | worker | sequence | tag |`, the IDs sort by their timestamps.
+    #[default]
+    TimestampHigh,
+    /// `| sequence | timestamp | data-center | worker | tag |`, the IDs generated within
+    /// the same millisecond spread across the high bits for a better hash distribution across shards.
     ///
-    /// The return type is a `Result` where the success variant contains the initialized
-    /// `Self` (a [`SnowflakeGenerator`]) and the error variant contains a [`SnowflakeError`].
+    /// Note: breaks the time-sortability, a later ID may be numerically smaller than an earlier one.
+    SequenceHigh,
+}
+
+// ----------------------------------------------------------------
+
+/// [`BitLayout`] The bit widths of the `data-center`, `worker` and sequence fields of an ID.
+///
+/// The fields are packed from high to low as `| timestamp | data-center | worker | sequence |`
+/// by default, see [`PackingOrder`], the three widths must sum to at most [`Constants::MAX_LAYOUT_BITS`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{BitLayout, Constants};
+///
+/// let layout = BitLayout::new(0, 10, 12).unwrap();
+/// assert_eq!(1023, layout.max_worker_id());
+/// assert_eq!(22, layout.timestamp_shift());
+///
+/// let rvt = BitLayout::new(6, 6, 12);
+/// assert_eq!(
+///     "Bit layout out of range: data-center(6) + worker(6) + sequence(12) = 24 bits, exceeds 22 by 2",
+///     rvt.unwrap_err().to_string()
+/// );
+/// ```
+///
+/// `Serialize`/`Deserialize` are derived by features = [`"serde"`], the missing fields default
+/// to [`BitLayout::default`], a deserialized layout is validated on [`SnowflakeGenerator::from_config`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct BitLayout {
+    datacenter_bits: u64,
+    worker_id_bits: u64,
+    sequence_bits: u64,
+    /// The low bits reserved from the sequence field for a caller-supplied tag,
+    /// see [`BitLayout::with_tag_bits`].
     ///
-    /// # Examples
+    /// @since 0.4.0
+    tag_bits: u64,
+    /// The usable bits of an ID, the timestamp takes the rest of them after the three fields,
+    /// see [`BitLayout::with_id_bits`].
     ///
-    /// ```rust
-    /// use snowflaker::generator::SnowflakeGenerator;
+    /// @since 0.4.0
+    id_bits: u64,
+    /// The order of the fields, see [`BitLayout::with_packing_order`].
     ///
-    /// let gen = SnowflakeGenerator::builtin();
-    /// assert!(gen.is_ok());
-    /// ```
-    pub fn builtin() -> Result<Self, SnowflakeError> {
-        SnowflakeGenerator::new(
-            Constants::DEFAULT_DATA_CENTER_ID,
-            Constants::DEFAULT_WORKER_ID,
+    /// @since 0.4.0
+    order: PackingOrder,
+}
+
+impl Default for BitLayout {
+    /// The default `5/5/12` layout, see [`Constants`].
+    fn default() -> Self {
+        BitLayout::unchecked(
+            Constants::DATA_CENTER_ID_BITS,
+            Constants::WORKER_ID_BITS,
+            Constants::SEQUENCE_BITS,
         )
     }
+}
 
-    /// Creates a new [`SnowflakeGenerator`] instance with `dynamic` parameters.
-    ///
-    /// This function is available when the `dynamic` feature is enabled.
+impl BitLayout {
+    /// Constructs a new [`BitLayout`], validated by [`BitLayout::validate`].
     ///
     /// # Errors
     ///
-    /// Returns a [`SnowflakeError`] if the `data-center` ID or `worker` ID invalid.
+    /// Returns [`SnowflakeError::LayoutInvalid`] if the widths overflow,
+    /// its `Display` tells the fields and by how many bits.
     ///
-    /// # Examples
+    /// For the `const` contexts see [`BitLayout::new_const`].
+    ///
+    /// @since 0.4.0 returns a `Result`
+    pub const fn new(
+        datacenter_bits: u64,
+        worker_id_bits: u64,
+        sequence_bits: u64,
+    ) -> Result<Self, SnowflakeError> {
+        let layout = Self::unchecked(datacenter_bits, worker_id_bits, sequence_bits);
+        if layout.overflows() {
+            return Err(SnowflakeError::LayoutInvalid(layout));
+        }
+
+        Ok(layout)
+    }
+
+    /// Constructs a new [`BitLayout`] like [`BitLayout::new`] in the `const` contexts,
+    /// an overflowing layout fails the compilation instead of returning an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the widths overflow, see [`BitLayout::validate`].
+    ///
+    /// The constructor and the derived max/shift computations are `const fn`s:
+    ///
+    /// ```rust
+    /// use snowflaker::generator::BitLayout;
+    ///
+    /// const LAYOUT: BitLayout = BitLayout::new_const(0, 10, 12);
+    /// const MAX_WORKER_ID: u64 = LAYOUT.max_worker_id();
+    /// assert_eq!(1023, MAX_WORKER_ID);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use snowflaker::generator::BitLayout;
+    ///
+    /// const LAYOUT: BitLayout = BitLayout::new_const(6, 6, 12);
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub const fn new_const(datacenter_bits: u64, worker_id_bits: u64, sequence_bits: u64) -> Self {
+        let layout = Self::unchecked(datacenter_bits, worker_id_bits, sequence_bits);
+        if layout.overflows() {
+            panic!("bit layout out of range");
+        }
+
+        layout
+    }
+
+    /// Constructs a new [`BitLayout`] without the validation, for the known-valid widths.
+    const fn unchecked(datacenter_bits: u64, worker_id_bits: u64, sequence_bits: u64) -> Self {
+        Self {
+            datacenter_bits,
+            worker_id_bits,
+            sequence_bits,
+            tag_bits: 0,
+            id_bits: Constants::ID_BITS,
+            order: PackingOrder::TimestampHigh,
+        }
+    }
+
+    /// Reserves the low `tag_bits` of the sequence field for a caller-supplied tag,
+    /// see [`SnowflakeGenerator::next_id_tagged`].
+    ///
+    /// The fields are packed as `| timestamp | data-center | worker | sequence | tag |`,
+    /// the sequence within a millisecond shrinks to `sequence_bits - tag_bits`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::BitLayout;
+    ///
+    /// let layout = BitLayout::default().with_tag_bits(4);
+    /// assert!(layout.validate().is_ok());
+    /// assert_eq!(15, layout.max_tag());
+    /// assert_eq!(255, layout.sequence_mask());
+    ///
+    /// assert!(BitLayout::default().with_tag_bits(13).validate().is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub const fn with_tag_bits(mut self, tag_bits: u64) -> Self {
+        self.tag_bits = tag_bits;
+        self
+    }
+
+    /// Caps the usable bits of an ID at `id_bits`, defaults to [`Constants::ID_BITS`],
+    /// the timestamp bits shrink to `id_bits` minus the three widths.
+    ///
+    /// The IDs stay below `1 << id_bits`, beyond the max timestamp the generation returns
+    /// [`SnowflakeError::TimestampOverflow`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::BitLayout;
+    ///
+    /// let layout = BitLayout::new(0, 7, 12).unwrap().with_id_bits(48);
+    /// assert!(layout.validate().is_ok());
+    /// assert_eq!(29, layout.timestamp_bits());
+    ///
+    /// // no timestamp bit left
+    /// assert!(BitLayout::default().with_id_bits(22).validate().is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub const fn with_id_bits(mut self, id_bits: u64) -> Self {
+        self.id_bits = id_bits;
+        self
+    }
+
+    /// Sets the order of the fields packed into an ID, defaults to [`PackingOrder::TimestampHigh`].
+    ///
+    /// Note: [`PackingOrder::SequenceHigh`] breaks the time-sortability of the IDs,
+    /// and [`SnowflakeGenerator::next_id_for_shard`] rejects it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{BitLayout, PackingOrder};
+    ///
+    /// let layout = BitLayout::default().with_packing_order(PackingOrder::SequenceHigh);
+    /// assert_eq!(51, layout.sequence_shift());
+    /// assert_eq!(10, layout.timestamp_shift());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub const fn with_packing_order(mut self, order: PackingOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Validates the three widths sum to at most [`Constants::MAX_LAYOUT_BITS`],
+    /// the tag bits fit in the sequence bits, and the ID bits leave at least one timestamp bit
+    /// within [`Constants::ID_BITS`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SnowflakeError::LayoutInvalid`] if the widths overflow.
+    pub const fn validate(&self) -> Result<(), SnowflakeError> {
+        if self.overflows() {
+            return Err(SnowflakeError::LayoutInvalid(*self));
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the widths sum past [`Constants::MAX_LAYOUT_BITS`],
+    /// the tag bits exceed the sequence bits, or the ID bits leave no timestamp bit.
+    const fn overflows(&self) -> bool {
+        self.tag_bits > self.sequence_bits
+            || self.total_bits() > Constants::MAX_LAYOUT_BITS
+            || self.id_bits > Constants::ID_BITS
+            || self.id_bits <= self.total_bits()
+    }
+
+    /// Returns the sum of the three widths, saturated at `u64::MAX`.
+    const fn total_bits(&self) -> u64 {
+        self.datacenter_bits
+            .saturating_add(self.worker_id_bits)
+            .saturating_add(self.sequence_bits)
+    }
+
+    /// Describes which fields of this invalid layout overflow and by how many bits.
+    fn fmt_overflow(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.tag_bits > self.sequence_bits {
+            return write!(
+                f,
+                "tag({}) exceeds sequence({}) by {}",
+                self.tag_bits,
+                self.sequence_bits,
+                self.tag_bits - self.sequence_bits
+            );
+        }
+
+        let bits = self.total_bits();
+        if bits <= Constants::MAX_LAYOUT_BITS {
+            return write!(
+                f,
+                "id({}) must exceed data-center({}) + worker({}) + sequence({}) = {} bits, up to {}",
+                self.id_bits,
+                self.datacenter_bits,
+                self.worker_id_bits,
+                self.sequence_bits,
+                bits,
+                Constants::ID_BITS
+            );
+        }
+
+        write!(
+            f,
+            "data-center({}) + worker({}) + sequence({}) = {} bits, exceeds {} by {}",
+            self.datacenter_bits,
+            self.worker_id_bits,
+            self.sequence_bits,
+            bits,
+            Constants::MAX_LAYOUT_BITS,
+            bits.saturating_sub(Constants::MAX_LAYOUT_BITS)
+        )
+    }
+
+    /// Returns the `data-center` bits.
+    pub const fn datacenter_bits(&self) -> u64 {
+        self.datacenter_bits
+    }
+
+    /// Returns the `worker` bits.
+    pub const fn worker_id_bits(&self) -> u64 {
+        self.worker_id_bits
+    }
+
+    /// Returns the sequence bits, including the tag bits.
+    pub const fn sequence_bits(&self) -> u64 {
+        self.sequence_bits
+    }
+
+    /// Returns the tag bits reserved from the sequence field.
+    ///
+    /// @since 0.4.0
+    pub const fn tag_bits(&self) -> u64 {
+        self.tag_bits
+    }
+
+    /// Returns the usable bits of an ID, see [`BitLayout::with_id_bits`].
+    ///
+    /// @since 0.4.0
+    pub const fn id_bits(&self) -> u64 {
+        self.id_bits
+    }
+
+    /// Returns the order of the fields, see [`BitLayout::with_packing_order`].
+    ///
+    /// @since 0.4.0
+    pub const fn packing_order(&self) -> PackingOrder {
+        self.order
+    }
+
+    /// Returns the max tag, zero if no tag bits are reserved.
+    ///
+    /// @since 0.4.0
+    pub const fn max_tag(&self) -> u64 {
+        mask(self.tag_bits)
+    }
+
+    /// Returns the max `data-center` ID.
+    pub const fn max_data_center_id(&self) -> u64 {
+        max_id(self.datacenter_bits)
+    }
+
+    /// Returns the max `worker` ID.
+    pub const fn max_worker_id(&self) -> u64 {
+        max_id(self.worker_id_bits)
+    }
+
+    /// Returns the sequence mask, excluding the tag bits.
+    pub const fn sequence_mask(&self) -> u64 {
+        mask(self.sequence_bits.saturating_sub(self.tag_bits))
+    }
+
+    /// Returns the sequence shift, above the tag bits in [`PackingOrder::TimestampHigh`]
+    /// or on top of the ID bits in [`PackingOrder::SequenceHigh`].
+    ///
+    /// @since 0.4.0
+    pub const fn sequence_shift(&self) -> u64 {
+        match self.order {
+            PackingOrder::TimestampHigh => self.tag_bits,
+            PackingOrder::SequenceHigh => self.id_bits - self.sequence_bits + self.tag_bits,
+        }
+    }
+
+    /// Returns the `worker` ID shift.
+    ///
+    /// @since 0.4.0 follows the [`PackingOrder`]
+    pub const fn worker_id_shift(&self) -> u64 {
+        match self.order {
+            PackingOrder::TimestampHigh => self.sequence_bits,
+            PackingOrder::SequenceHigh => self.tag_bits,
+        }
+    }
+
+    /// Returns the `data-center` ID shift.
+    ///
+    /// @since 0.4.0 follows the [`PackingOrder`]
+    pub const fn center_id_shift(&self) -> u64 {
+        self.worker_id_shift() + self.worker_id_bits
+    }
+
+    /// Returns the timestamp left shift.
+    ///
+    /// @since 0.4.0 follows the [`PackingOrder`]
+    pub const fn timestamp_shift(&self) -> u64 {
+        self.center_id_shift() + self.datacenter_bits
+    }
+
+    /// Returns the timestamp bits, the sign bit is reserved to keep IDs positive as `i64`.
+    ///
+    /// @since 0.4.0
+    pub const fn timestamp_bits(&self) -> u64 {
+        self.id_bits - self.total_bits()
+    }
+
+    /// Returns the max timestamp since the epoch.
+    ///
+    /// @since 0.4.0
+    pub const fn max_timestamp(&self) -> u64 {
+        mask(self.timestamp_bits())
+    }
+
+    /// Packs the component fields into an ID.
+    pub(crate) const fn compose(
+        &self,
+        timestamp: u64,
+        center_id: u64,
+        worker_id: u64,
+        sequence: u64,
+    ) -> u64 {
+        (timestamp << self.timestamp_shift())
+            | (center_id << self.center_id_shift())
+            | (worker_id << self.worker_id_shift())
+            | (sequence << self.sequence_shift())
+    }
+
+    /// Returns the mask of the timestamp bits shifted down, no-op in [`PackingOrder::TimestampHigh`]
+    /// to keep the top bits of the IDs beyond the layout.
+    pub(crate) const fn timestamp_mask(&self) -> u64 {
+        match self.order {
+            PackingOrder::TimestampHigh => u64::MAX,
+            PackingOrder::SequenceHigh => self.max_timestamp(),
+        }
+    }
+
+    /// Decomposes an ID generated under this layout back into its component fields.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{BitLayout, Generator, SnowflakeGenerator};
+    ///
+    /// let layout = BitLayout::new(0, 10, 12).unwrap();
+    /// let gen = SnowflakeGenerator::builder().center_id(0).worker_id(1000).layout(layout).build().unwrap();
+    ///
+    /// let parts = layout.decompose(gen.next_id().unwrap());
+    /// assert_eq!(0, parts.center_id);
+    /// assert_eq!(1000, parts.worker_id);
+    /// # }
+    /// ```
+    pub const fn decompose(&self, id: u64) -> IdParts {
+        IdParts {
+            timestamp: (id >> self.timestamp_shift()) & self.timestamp_mask(),
+            center_id: (id >> self.center_id_shift()) & self.max_data_center_id(),
+            worker_id: (id >> self.worker_id_shift()) & self.max_worker_id(),
+            sequence: (id >> self.sequence_shift()) & self.sequence_mask(),
+            tag: id & self.max_tag(),
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`IdParts`] The component fields packed into a generated ID.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdParts {
+    /// The raw timestamp bits, relative to [`Constants::EPOCH`].
+    pub timestamp: u64,
+    /// The `data-center` ID.
+    pub center_id: u64,
+    /// The `worker` ID.
+    pub worker_id: u64,
+    /// The sequence within the millisecond.
+    pub sequence: u64,
+    /// The caller-supplied tag, zero unless the layout reserves tag bits.
+    ///
+    /// @since 0.4.0
+    pub tag: u64,
+}
+
+/// [`GeneratedId`] A generated ID along with the values packed into it,
+/// see [`SnowflakeGenerator::next_id_with_parts`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GeneratedId {
+    /// The generated ID.
+    pub id: u64,
+    /// The absolute unix-millis, truncated to the [`TimePrecision`] of the generator.
+    pub timestamp_millis: u64,
+    /// The `data-center` ID.
+    pub center_id: u64,
+    /// The `worker` ID.
+    pub worker_id: u64,
+    /// The sequence within the millisecond.
+    pub sequence: u64,
+}
+
+/// [`LayoutInfo`] The capacity of the layout of a generator, for documentation and tooling,
+/// see [`SnowflakeGenerator::layout_info`].
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayoutInfo {
+    /// The IDs one worker can generate per millisecond, rounded down in [`TimePrecision::Seconds`].
+    pub ids_per_millisecond: u64,
+    /// The count of the `worker` IDs within one `data-center`.
+    pub max_workers: u64,
+    /// The count of the `data-center` IDs.
+    pub max_datacenters: u64,
+    /// The last instant the timestamp bits can represent, the epoch plus the max timestamp,
+    /// capped at `2^42 - 1` unix-millis the state of the generator holds.
+    pub valid_until: SystemTime,
+}
+
+impl IdParts {
+    /// Returns the absolute unix-millis, adding [`Constants::EPOCH`] back to the raw timestamp.
+    ///
+    /// The raw timestamp is assumed in [`TimePrecision::Millis`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Constants, SnowflakeGenerator};
+    ///
+    /// let parts = SnowflakeGenerator::decompose(1 << Constants::TIMESTAMP_SHIFT);
+    /// assert_eq!(1, parts.timestamp);
+    /// assert_eq!(Constants::EPOCH + 1, parts.unix_millis());
+    /// ```
+    pub const fn unix_millis(&self) -> u64 {
+        self.timestamp + Constants::EPOCH
+    }
+
+    /// Returns the tenant, carried in the tag bits, see [`SnowflakeGenerator::next_id_for_tenant`].
+    ///
+    /// @since 0.4.0
+    pub const fn tenant(&self) -> u64 {
+        self.tag
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// Unique ID generator trait
+///
+/// The trait is object-safe, heterogeneous generators can be held as `Box<dyn Generator>`.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use snowflaker::generator::{BitLayout, Generator, SnowflakeGenerator};
+///
+/// let generators: Vec<Box<dyn Generator>> = vec![
+///     Box::new(SnowflakeGenerator::new(1, 1).unwrap()),
+///     Box::new(
+///         SnowflakeGenerator::builder()
+///             .center_id(0)
+///             .worker_id(1000)
+///             .layout(BitLayout::new(0, 10, 12).unwrap())
+///             .build()
+///             .unwrap(),
+///     ),
+/// ];
+///
+/// for gen in &generators {
+///     assert!(gen.next_id().is_ok());
+/// }
+/// # }
+/// ```
+///
+/// @since 0.4.0 the clock helpers moved to [`Timing`]
+pub trait Generator {
+    /// Generate next ID.
+    fn next_id(&self) -> Result<u64, SnowflakeError>;
+}
+
+/// The clock helpers of a [`Generator`], split out of it to keep [`Generator`] object-safe.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{SnowflakeGenerator, Timing};
+///
+/// let now = SnowflakeGenerator::time_gen().unwrap();
+/// assert!(SnowflakeGenerator::til_next_millis(now).unwrap() > now);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+pub trait Timing {
+    /// Get current timestamp.
+    fn time_gen() -> Result<u64, SnowflakeError>;
+
+    /// Get next timestamp.
+    fn til_next_millis(last_timestamp: u64) -> Result<u64, SnowflakeError>;
+}
+
+/// Returns the current unix-millis by the [`SystemClock`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{self, Constants};
+///
+/// assert!(generator::now_millis().unwrap() > Constants::EPOCH);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+pub fn now_millis() -> Result<u64, SnowflakeError> {
+    SystemClock.now_millis()
+}
+
+/// Waits until the [`SystemClock`] passes the given unix-millis, returns the first unix-millis after it.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator;
+///
+/// let now = generator::now_millis().unwrap();
+/// assert!(generator::wait_next_millis(now).unwrap() > now);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+pub fn wait_next_millis(after: u64) -> Result<u64, SnowflakeError> {
+    let mut next = now_millis()?;
+    while next <= after {
+        SpinStrategy::default().pause();
+        next = now_millis()?;
+    }
+
+    Ok(next)
+}
+
+// ----------------------------------------------------------------
+
+/// The builtin impl of [`Generator`]
+///
+/// [`Clone`] shares the runtime state: the clones are one logical generator and never
+/// emit the same ID, see [`SnowflakeGenerator::fork`] for an independent copy.
+#[derive(Clone)] // @since 0.3.6
+pub struct SnowflakeGenerator {
+    center_id: u64,
+    worker_id: u64,
+    /// The custom epoch in unix-millis, defaults to [`Constants::EPOCH`].
+    ///
+    /// @since 0.4.0
+    epoch: u64,
+    /// The bit layout, defaults to the `5/5/12` layout of [`Constants`].
+    ///
+    /// @since 0.4.0
+    layout: BitLayout,
+    /// issue#https:///github.com/photowey/snowflake/issues/16
+    ///
+    /// ### planA
+    /// `AtomicU64` wrapped by `Arc<T>`
+    /// |- Support multi-thread
+    /// |- -> Ok
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::AtomicU64;
+    ///
+    /// #[derive(Clone, Debug)]
+    /// pub struct SnowflakeGenerator {
+    ///     center_id: u64,
+    ///     worker_id: u64,
+    ///     sequence: Arc<AtomicU64>,
+    ///     last_timestamp: Arc<AtomicU64>,
+    /// }
+    /// ```
+    ///
+    /// ### planB
+    /// Customize the struct `CloneableAtomicU64` for the [`Clone`] trait
+    /// |- `CloneableAtomicU64` does not support multi-thread
+    /// |- -> PASS
+    ///
+    /// ```rust
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// #[derive(Debug)]
+    /// struct CloneableAtomicU64(AtomicU64);
+    ///
+    /// impl Clone for CloneableAtomicU64 {
+    ///     fn clone(&self) -> Self {
+    ///         CloneableAtomicU64(AtomicU64::new(self.0.load(Ordering::SeqCst)))
+    ///     }
+    /// }
+    ///
+    /// impl CloneableAtomicU64 {
+    ///     fn new(value: u64) -> Self {
+    ///         CloneableAtomicU64(AtomicU64::new(value))
+    ///     }
+    ///
+    ///     fn load(&self, ordering: Ordering) -> u64 {
+    ///         self.0.load(ordering)
+    ///     }
+    ///
+    ///     fn store(&self, value: u64, ordering: Ordering) {
+    ///         self.0.store(value, ordering)
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug)]
+    /// struct SnowflakeGenerator {
+    ///     center_id: u64,
+    ///     worker_id: u64,
+    ///     sequence: CloneableAtomicU64,
+    ///     last_timestamp: CloneableAtomicU64,
+    /// }
+    ///
+    /// impl Clone for SnowflakeGenerator {
+    ///     fn clone(&self) -> Self {
+    ///         Self {
+    ///             center_id: self.center_id,
+    ///             worker_id: self.worker_id,
+    ///             // clone: Will be relatively independent
+    ///             sequence: self.sequence.clone(),
+    ///             last_timestamp: self.last_timestamp.clone(),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// @since 0.3.6
+    ///
+    /// ### planC
+    /// `last_timestamp` and `sequence` packed into a single `AtomicU64`
+    /// |- Updated by a `compare_exchange_weak` retry loop
+    /// |- Each ID is derived from exactly one successful `CAS`
+    /// |- -> Ok
+    ///
+    /// `| last_timestamp: 42 bits | sequence: 22 bits |`
+    ///
+    /// @since 0.4.0
+    state: Arc<AtomicU64>,
+    /// The time source, defaults to [`SystemClock`].
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    clock: Arc<dyn Clock + Send + Sync>,
+    /// The observability hooks, `None` keeps the hot path free.
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    metrics: Option<Arc<dyn Metrics + Send + Sync>>,
+    /// The max clock-backwards delta in millis the generator waits through,
+    /// defaults to [`Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS`].
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    max_backward_tolerance_millis: u64,
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    wait_strategy: WaitStrategy,
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    spin_strategy: SpinStrategy,
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    overflow_policy: OverflowPolicy,
+    /// Reuses the last timestamp within a millisecond while the sequence has room instead of
+    /// reading the [`Clock`] on every ID, see [`SnowflakeGeneratorBuilder::timestamp_cache`].
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    timestamp_cache: Option<Arc<TimestampCache>>,
+    /// The cap of the IDs per second, `None` is unlimited, see [`SnowflakeGeneratorBuilder::max_per_second`].
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// Decides on the clock moved backwards instead of the tolerance,
+    /// see [`SnowflakeGeneratorBuilder::on_clock_backwards`].
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    on_clock_backwards: Option<ClockBackwardsHook>,
+    /// The last timestamp restored by [`SnowflakeGenerator::restore`], shared between the clones,
+    /// the clock behind it is waited out instead of erroring.
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    restored_timestamp: Arc<AtomicU64>,
+    /// The peak sequence ever emitted, shared between the clones.
+    ///
+    /// @since 0.4.0
+    peak_sequence: Arc<AtomicU64>,
+    /// The unit of the timestamp bits, defaults to [`TimePrecision::Millis`].
+    ///
+    /// @since 0.4.0
+    precision: TimePrecision,
+    /// The memory ordering of the atomic operations on the `state`, defaults to [`AtomicOrdering::SeqCst`].
+    ///
+    /// @since 0.4.0
+    ordering: AtomicOrdering,
+    /// Starts the sequence of each millisecond at a pseudo-random offset instead of zero,
+    /// see [`SnowflakeGeneratorBuilder::randomize_sequence`].
+    ///
+    /// @since 0.4.0
+    randomize_sequence: bool,
+}
+
+impl Debug for SnowflakeGenerator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("SnowflakeGenerator");
+        debug
+            .field("center_id", &self.center_id)
+            .field("worker_id", &self.worker_id)
+            .field("epoch", &self.epoch)
+            .field("layout", &self.layout)
+            .field("precision", &self.precision)
+            .field("ordering", &self.ordering)
+            .field("randomize_sequence", &self.randomize_sequence)
+            .field("state", &self.state);
+        #[cfg(feature = "std")]
+        debug
+            .field(
+                "max_backward_tolerance_millis",
+                &self.max_backward_tolerance_millis,
+            )
+            .field("wait_strategy", &self.wait_strategy)
+            .field("spin_strategy", &self.spin_strategy)
+            .field("overflow_policy", &self.overflow_policy)
+            .field("timestamp_cache", &self.timestamp_cache.is_some())
+            .field(
+                "max_per_second",
+                &self
+                    .rate_limiter
+                    .as_ref()
+                    .map(|limiter| limiter.max_per_second),
+            )
+            .field("on_clock_backwards", &self.on_clock_backwards.is_some());
+        debug.finish_non_exhaustive()
+    }
+}
+
+/// Equality is configuration-based, not state-based: two generators are equal if they share
+/// the same `data-center` ID, `worker` ID, epoch, [`BitLayout`] and [`TimePrecision`],
+/// see [`SnowflakeGenerator::to_config`].
+///
+/// The runtime `sequence`/`last_timestamp` state is mutable and ignored, so equal generators
+/// may still be independent and generate duplicated IDs if both are used.
+///
+/// @since 0.4.0
+impl PartialEq for SnowflakeGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_config() == other.to_config()
+    }
+}
+
+/// @since 0.4.0
+impl Eq for SnowflakeGenerator {}
+
+/// Hashes the configuration consistent with [`PartialEq`], the runtime state is ignored.
+///
+/// @since 0.4.0
+impl Hash for SnowflakeGenerator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_config().hash(state);
+    }
+}
+
+impl Default for SnowflakeGenerator {
+    /// The [`SnowflakeGenerator::builtin`] equivalent with the always-valid
+    /// [`Constants::DEFAULT_DATA_CENTER_ID`] and [`Constants::DEFAULT_WORKER_ID`].
+    ///
+    /// It never panics: the default IDs are within range, the default layout is valid
+    /// and the default epoch is not validated against the clock.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::default();
+    /// assert!(gen.next_id().is_ok());
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    fn default() -> Self {
+        SnowflakeGenerator::builder()
+            .build()
+            .expect("the default `data-center` ID and `worker` ID are always valid")
+    }
+}
+
+/// `STATE_SEQUENCE_BITS` the low bits of the packed state reserved for the sequence: 22
+const STATE_SEQUENCE_BITS: u64 = 22;
+/// `STATE_SEQUENCE_MASK` the sequence mask of the packed state
+const STATE_SEQUENCE_MASK: u64 = mask(STATE_SEQUENCE_BITS);
+/// `STATE_MAX_TIMESTAMP` the max unix-millis the packed state holds: `2^42 - 1`,
+/// the generation beyond it fails with [`SnowflakeError::TimestampOverflow`] whatever the layout
+const STATE_MAX_TIMESTAMP: u64 = mask(64 - STATE_SEQUENCE_BITS);
+
+// @since 0.3.6
+// `Getter` & `Setter` for `sequence` & `last_timestamp`
+impl SnowflakeGenerator {
+    fn pack_state(last_timestamp: u64, sequence: u64) -> u64 {
+        (last_timestamp << STATE_SEQUENCE_BITS) | (sequence & STATE_SEQUENCE_MASK)
+    }
+
+    fn unpack_state(state: u64) -> (u64, u64) {
+        (state >> STATE_SEQUENCE_BITS, state & STATE_SEQUENCE_MASK)
+    }
+
+    fn update_state<F>(&self, f: F)
+    where
+        F: Fn(u64, u64) -> (u64, u64),
+    {
+        let _ =
+            self.state
+                .fetch_update(self.ordering.success(), self.ordering.failure(), |state| {
+                    let (last_timestamp, sequence) = Self::unpack_state(state);
+                    let (last_timestamp, sequence) = f(last_timestamp, sequence);
+                    Some(Self::pack_state(last_timestamp, sequence))
+                });
+    }
+
+    //
+    // ---------------------------------------------------------------- getter/setter
+    //
+
+    #[allow(dead_code)]
+    pub(crate) fn get_sequence(&self) -> u64 {
+        Self::unpack_state(self.state.load(self.ordering.load())).1
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn set_sequence(&self, value: u64) {
+        self.update_state(|last_timestamp, _| (last_timestamp, value))
+    }
+
+    #[allow(dead_code)]
+    fn get_last_timestamp(&self) -> u64 {
+        Self::unpack_state(self.state.load(self.ordering.load())).0
+    }
+}
+
+impl SnowflakeGenerator {
+    /// Returns a new instance of [`SnowflakeGenerator`] with built-in defaults.
+    ///
+    /// This function, `builtin`, instantiates a `SnowflakeGenerator` using the predefined constants for
+    /// `data-center` ID and `worker` ID. These constants are [`Constants::DEFAULT_DATA_CENTER_ID`] and
+    /// [`Constants::DEFAULT_WORKER_ID`] respectively.
+    ///
+    /// The return type is a `Result` where the success variant contains the initialized
+    /// `Self` (a [`SnowflakeGenerator`]) and the error variant contains a [`SnowflakeError`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin();
+    /// assert!(gen.is_ok());
+    /// ```
+    pub fn builtin() -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::new(
+            Constants::DEFAULT_DATA_CENTER_ID,
+            Constants::DEFAULT_WORKER_ID,
+        )
+    }
+
+    /// Creates a new [`SnowflakeGenerator`] instance with `dynamic` parameters.
+    ///
+    /// This function is available when the `dynamic` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SnowflakeError`] if the `data-center` ID or `worker` ID invalid.
+    ///
+    /// # Examples
     ///
     /// ``` rust
     /// use snowflaker::generator::{Generator, SnowflakeGenerator};
     ///
-    /// let gen = SnowflakeGenerator::dynamic();
-    /// assert!(gen.is_ok());
+    /// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+    /// # std::env::set_var("SNOWFLAKE_CACHE_DIR", &dir);
+    /// let gen = SnowflakeGenerator::dynamic();
+    /// assert!(gen.is_ok());
+    /// let rvt = gen.unwrap().next_id();
+    /// assert!(rvt.is_ok());
+    /// # let _ = std::fs::remove_dir_all(dir);
+    /// ```
+    ///
+    /// # Version
+    ///
+    /// This function was introduced in version `0.2.0` of the crate.
+    ///
+    /// # Notes
+    ///
+    /// This function retrieves the `data-center` ID and `worker` ID dynamically from the network interface(`non-loopback `).
+    ///
+    /// The env vars [`infras::SNOWFLAKE_DATACENTER_ID`] and [`infras::SNOWFLAKE_WORKER_ID`] take precedence
+    /// if present, a present but invalid value returns [`SnowflakeError::CenterIdInvalid`]
+    /// or [`SnowflakeError::WorkerIdInvalid`] rather than falling back. (@since 0.4.0)
+    ///
+    /// @since 0.2.0
+    #[cfg(feature = "dynamic")]
+    pub fn dynamic() -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::dynamic_with_env(|key| std::env::var(key))
+    }
+
+    /// Creates a new [`SnowflakeGenerator`] instance with `dynamic` parameters,
+    /// looks up the env vars by the given `env`.
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "dynamic")]
+    pub(crate) fn dynamic_with_env<F>(env: F) -> Result<Self, SnowflakeError>
+    where
+        F: Fn(&str) -> Result<String, std::env::VarError>,
+    {
+        let center_id = match infras::try_get_env_id(
+            &env,
+            infras::SNOWFLAKE_DATACENTER_ID,
+            Constants::MAX_DATA_CENTER_ID,
+            SnowflakeError::CenterIdInvalid,
+        )? {
+            Some(center_id) => center_id,
+            None => MacIdentityProvider.datacenter_id()?,
+        };
+        let worker_id = match infras::try_get_env_id(
+            &env,
+            infras::SNOWFLAKE_WORKER_ID,
+            Constants::MAX_WORKER_ID,
+            SnowflakeError::WorkerIdInvalid,
+        )? {
+            Some(worker_id) => worker_id,
+            None => MacIdentityProvider.worker_id(center_id)?,
+        };
+
+        SnowflakeGenerator::new(center_id, worker_id)
+    }
+
+    /// Creates a new [`SnowflakeGenerator`] instance with the `data-center` ID and `worker` ID
+    /// derived by the given [`IdentityProvider`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SnowflakeError`] if the provider failed, or the derived IDs invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{SnowflakeError, SnowflakeGenerator};
+    /// use snowflaker::identity::IdentityProvider;
+    ///
+    /// struct Fixed;
+    ///
+    /// impl IdentityProvider for Fixed {
+    ///     fn datacenter_id(&self) -> Result<u64, SnowflakeError> {
+    ///         Ok(3)
+    ///     }
+    ///
+    ///     fn worker_id(&self, _datacenter_id: u64) -> Result<u64, SnowflakeError> {
+    ///         Ok(7)
+    ///     }
+    /// }
+    ///
+    /// let gen = SnowflakeGenerator::with_identity(&Fixed).unwrap();
+    /// assert_eq!(3, gen.center_id());
+    /// assert_eq!(7, gen.worker_id());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn with_identity<P>(provider: &P) -> Result<Self, SnowflakeError>
+    where
+        P: IdentityProvider + ?Sized,
+    {
+        let center_id = provider.datacenter_id()?;
+        let worker_id = provider.worker_id(center_id)?;
+
+        SnowflakeGenerator::new(center_id, worker_id)
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance.
+    ///
+    /// # Arguments
+    ///
+    /// - `center_id`: An identifier for the `data-center`, represented as a `u64`.
+    ///   It must be within the defined maximum limit.
+    /// - `worker_id`: An identifier for the `worker` node within the `data-center`,
+    ///   also represented as a `u64`. This too must not exceed its predefined maximum value.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Self)`: If both `center_id` and `worker_id` are valid, returns a new [`SnowflakeGenerator`] instance.
+    /// - `Err(SnowflakeError)`: If either `center_id` or `worker_id` is invalid, returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new(31, 31);
+    /// assert!(gen.is_ok());
+    ///
+    /// let gen = SnowflakeGenerator::new(32, 32);
+    /// assert!(gen.is_err());
+    ///
+    /// let gen = SnowflakeGenerator::new(1, 32);
+    /// assert!(gen.is_err());
+    /// ```
+    pub fn new(center_id: u64, worker_id: u64) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(center_id)
+            .worker_id(worker_id)
+            .build()
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance like [`SnowflakeGenerator::new`],
+    /// with the typed IDs: the `data-center` and the `worker` can not be swapped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{DatacenterId, SnowflakeGenerator, WorkerId};
+    ///
+    /// let center_id = DatacenterId::new(3).unwrap();
+    /// let worker_id = WorkerId::new(7).unwrap();
+    ///
+    /// let gen = SnowflakeGenerator::from_ids(center_id, worker_id).unwrap();
+    /// assert_eq!(3, gen.center_id());
+    /// assert_eq!(7, gen.worker_id());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn from_ids(center_id: DatacenterId, worker_id: WorkerId) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::new(center_id.get(), worker_id.get())
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance like [`SnowflakeGenerator::new`],
+    /// and registers its node in the process-global registry.
+    ///
+    /// The check is opt-in per call, [`SnowflakeGenerator::new`] never consults the registry,
+    /// see [`registry`].
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(RegisteredGenerator)`: The generator, its node is unregistered on drop.
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::DuplicateNode`] if another generator
+    ///   with the same node is alive, or the errors of [`SnowflakeGenerator::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new_registered(5, 7);
+    /// assert!(gen.is_ok());
+    ///
+    /// let gen = SnowflakeGenerator::new_registered(5, 7);
+    /// assert!(gen.is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "registry")]
+    pub fn new_registered(
+        center_id: u64,
+        worker_id: u64,
+    ) -> Result<registry::RegisteredGenerator, SnowflakeError> {
+        registry::register(SnowflakeGenerator::new(center_id, worker_id)?)
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance with the node provisioned on disk,
+    /// e.g. `/etc/snowflake/node-id`, containing `datacenter:worker`.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Self)`: The generator with the `data-center` ID and `worker` ID of the file.
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::NodeFileInvalid`] if the file is missing,
+    ///   unreadable or malformed, or the errors of [`SnowflakeGenerator::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let path = std::env::temp_dir().join("snowflaker-doc-node-id");
+    /// std::fs::write(&path, "3:9\n").unwrap();
+    ///
+    /// let gen = SnowflakeGenerator::from_node_file(&path).unwrap();
+    /// assert_eq!(3, gen.center_id());
+    /// assert_eq!(9, gen.worker_id());
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn from_node_file<P: AsRef<Path>>(path: P) -> Result<Self, SnowflakeError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|err| {
+            SnowflakeError::NodeFileInvalid(format!("{}: {}", path.display(), err))
+        })?;
+
+        let content = content.trim();
+        let malformed = || {
+            SnowflakeError::NodeFileInvalid(format!(
+                "{}: expected `datacenter:worker`, got {:?}",
+                path.display(),
+                content
+            ))
+        };
+
+        let (center_id, worker_id) = content.split_once(':').ok_or_else(malformed)?;
+        let center_id = center_id.trim().parse().map_err(|_| malformed())?;
+        let worker_id = worker_id.trim().parse().map_err(|_| malformed())?;
+
+        SnowflakeGenerator::new(center_id, worker_id)
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance with a custom epoch.
+    ///
+    /// # Arguments
+    ///
+    /// - `center_id`: An identifier for the `data-center`, see [`SnowflakeGenerator::new`].
+    /// - `worker_id`: An identifier for the `worker`, see [`SnowflakeGenerator::new`].
+    /// - `epoch_millis`: The custom epoch in unix-millis, it must not be in the future.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Self)`: If the IDs and the epoch are valid, returns a new [`SnowflakeGenerator`] instance.
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::EpochInvalid`] if the epoch is in the future,
+    ///   or the errors of [`SnowflakeGenerator::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// // 2024-01-01 00:00:00
+    /// let gen = SnowflakeGenerator::with_epoch(1, 1, 1704067200000);
+    /// assert!(gen.is_ok());
+    /// assert!(gen.unwrap().next_id().is_ok());
+    ///
+    /// let gen = SnowflakeGenerator::with_epoch(1, 1, u64::MAX);
+    /// assert!(gen.is_err());
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn with_epoch(
+        center_id: u64,
+        worker_id: u64,
+        epoch_millis: u64,
+    ) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(center_id)
+            .worker_id(worker_id)
+            .epoch(epoch_millis)
+            .build()
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance compatible with the original Twitter snowflake:
+    /// the `41/5/5/12` layout and the [`Constants::TWITTER_EPOCH`],
+    /// so the IDs are directly comparable with the other Twitter-snowflake libraries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Constants, Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::twitter(3, 7).unwrap();
+    /// assert_eq!(Constants::TWITTER_EPOCH, gen.epoch());
+    ///
+    /// let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    /// assert_eq!(7, parts.worker_id);
+    /// assert!(parts.timestamp + Constants::TWITTER_EPOCH > Constants::EPOCH);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn twitter(center_id: u64, worker_id: u64) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(center_id)
+            .worker_id(worker_id)
+            .epoch(Constants::TWITTER_EPOCH)
+            .layout(BitLayout::default())
+            .build()
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance of the compact IDs below `1 << 48`,
+    /// for the short-lived data, e.g. the ephemeral caches.
+    ///
+    /// The preset counts the seconds since the [`Constants::COMPACT_EPOCH`] in `29` timestamp bits,
+    /// drops the `data-center` field and keeps `7` worker bits and `12` sequence bits:
+    ///
+    /// - up to `128` workers, `4096` IDs per second each.
+    /// - valid for about `17` years, until `2043`, then the generation returns
+    ///   [`SnowflakeError::TimestampOverflow`], see [`SnowflakeGenerator::layout_info`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SnowflakeError::WorkerIdInvalid`] if `worker_id` exceeds `127`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Constants, Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::compact(7).unwrap();
+    /// assert_eq!(Constants::COMPACT_EPOCH, gen.epoch());
+    ///
+    /// let id = gen.next_id().unwrap();
+    /// assert!(id < 1 << Constants::COMPACT_ID_BITS);
+    /// assert_eq!(7, gen.layout().decompose(id).worker_id);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn compact(worker_id: u64) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(0)
+            .worker_id(worker_id)
+            .epoch(Constants::COMPACT_EPOCH)
+            .layout(BitLayout::new(0, 7, 12)?.with_id_bits(Constants::COMPACT_ID_BITS))
+            .precision(TimePrecision::Seconds)
+            .build()
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance, clamps the out-of-range IDs into range
+    /// by masking them with [`Constants::MAX_DATA_CENTER_ID`]/[`Constants::MAX_WORKER_ID`]
+    /// instead of erroring, e.g. `40` becomes `40 & 31 = 8`.
+    ///
+    /// A warning is emitted as a `tracing` event by features = [`"tracing"`] if any ID was clamped,
+    /// silently clamped otherwise, see [`SnowflakeGenerator::new_clamped_with`] to plug a custom hook.
+    ///
+    /// Note: clamping can cause two differently-configured nodes to collide,
+    /// e.g. the `worker` IDs `1` and `33` are both clamped to `1`, and generate duplicated IDs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new_clamped(40, 33);
+    /// assert_eq!(8, gen.center_id());
+    /// assert_eq!(1, gen.worker_id());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn new_clamped(center_id: u64, worker_id: u64) -> Self {
+        SnowflakeGenerator::new_clamped_with(center_id, worker_id, |_warning| {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("{}", _warning);
+        })
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance like [`SnowflakeGenerator::new_clamped`],
+    /// calls the given `hook` with the warning message if any ID was clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let mut warnings = Vec::new();
+    /// let gen = SnowflakeGenerator::new_clamped_with(3, 33, |warning| warnings.push(warning.to_string()));
+    /// assert_eq!(1, gen.worker_id());
+    /// assert_eq!(1, warnings.len());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn new_clamped_with<F>(center_id: u64, worker_id: u64, hook: F) -> Self
+    where
+        F: FnOnce(&str),
+    {
+        let clamped_center_id = center_id & Constants::MAX_DATA_CENTER_ID;
+        let clamped_worker_id = worker_id & Constants::MAX_WORKER_ID;
+
+        if clamped_center_id != center_id || clamped_worker_id != worker_id {
+            hook(&alloc::format!(
+                "the data-center ID {} and worker ID {} clamped to {} and {}, which may collide with other nodes",
+                center_id, worker_id, clamped_center_id, clamped_worker_id
+            ));
+        }
+
+        SnowflakeGenerator::builder()
+            .center_id(clamped_center_id)
+            .worker_id(clamped_worker_id)
+            .build()
+            .expect("the clamped `data-center` ID and `worker` ID are always valid")
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance with a custom [`Clock`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    ///
+    /// use snowflaker::clock::SystemClock;
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(SystemClock));
+    /// assert!(gen.is_ok());
+    /// assert!(gen.unwrap().next_id().is_ok());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn new_with_clock(
+        center_id: u64,
+        worker_id: u64,
+        clock: Arc<dyn Clock + Send + Sync>,
+    ) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(center_id)
+            .worker_id(worker_id)
+            .clock(clock)
+            .build()
+    }
+
+    /// Constructs a new [`SnowflakeGenerator`] instance with the observability hooks,
+    /// see [`Metrics`].
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn with_metrics(
+        center_id: u64,
+        worker_id: u64,
+        metrics: Arc<dyn Metrics + Send + Sync>,
+    ) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(center_id)
+            .worker_id(worker_id)
+            .metrics(metrics)
+            .build()
+    }
+
+    /// Returns the `data-center` ID of this generator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new(12, 7).unwrap();
+    /// assert_eq!(12, gen.center_id());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn center_id(&self) -> u64 {
+        self.center_id
+    }
+
+    /// Returns the `worker` ID of this generator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new(12, 7).unwrap();
+    /// assert_eq!(7, gen.worker_id());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn worker_id(&self) -> u64 {
+        self.worker_id
+    }
+
+    /// Returns the custom epoch in unix-millis of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the [`BitLayout`] of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn layout(&self) -> BitLayout {
+        self.layout
+    }
+
+    /// Returns the effective max `data-center` ID for the layout of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn max_datacenter_id(&self) -> u64 {
+        self.layout.max_data_center_id()
+    }
+
+    /// Returns the effective max `worker` ID for the layout of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn max_worker_id(&self) -> u64 {
+        self.layout.max_worker_id()
+    }
+
+    /// Returns the effective max sequence within a millisecond for the layout of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn max_sequence(&self) -> u64 {
+        self.layout.sequence_mask()
+    }
+
+    /// Returns the [`TimePrecision`] of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn precision(&self) -> TimePrecision {
+        self.precision
+    }
+
+    /// Returns the [`LayoutInfo`] of this generator, computed from the layout, epoch and precision.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let info = SnowflakeGenerator::new(1, 1).unwrap().layout_info();
+    /// assert_eq!(4096, info.ids_per_millisecond);
+    /// assert_eq!(32, info.max_workers);
+    /// assert_eq!(32, info.max_datacenters);
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn layout_info(&self) -> LayoutInfo {
+        let unit_millis = self.precision.unit_millis();
+        let valid_until = self
+            .layout
+            .max_timestamp()
+            .saturating_mul(unit_millis)
+            .saturating_add(self.epoch)
+            .min(STATE_MAX_TIMESTAMP);
+
+        LayoutInfo {
+            ids_per_millisecond: (self.layout.sequence_mask() + 1) / unit_millis,
+            max_workers: self.layout.max_worker_id() + 1,
+            max_datacenters: self.layout.max_data_center_id() + 1,
+            valid_until: UNIX_EPOCH + Duration::from_millis(valid_until),
+        }
+    }
+
+    /// Returns a new [`SnowflakeGeneratorBuilder`] to construct a [`SnowflakeGenerator`] step by step.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builder().center_id(3).worker_id(7).build();
+    /// assert!(gen.is_ok());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn builder() -> SnowflakeGeneratorBuilder {
+        SnowflakeGeneratorBuilder::default()
+    }
+
+    /// Decomposes a generated ID back into its component fields.
+    ///
+    /// The ID is unpacked with the same shift/mask constants used by [`Generator::next_id`],
+    /// the returned [`IdParts::timestamp`] is the raw value relative to [`Constants::EPOCH`].
+    ///
+    /// IDs generated under a custom layout are decomposed by [`BitLayout::decompose`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    /// let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    /// assert_eq!(3, parts.center_id);
+    /// assert_eq!(7, parts.worker_id);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn decompose(id: u64) -> IdParts {
+        BitLayout::default().decompose(id)
+    }
+
+    /// Returns the absolute unix-millis at which the given ID was generated.
+    ///
+    /// The timestamp bits are shifted out by [`Constants::TIMESTAMP_SHIFT`] and
+    /// [`Constants::EPOCH`] is added back, so IDs close to the epoch never underflow.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Constants, SnowflakeGenerator};
+    ///
+    /// assert_eq!(Constants::EPOCH, SnowflakeGenerator::timestamp_of(0));
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn timestamp_of(id: u64) -> u64 {
+        Self::decompose(id).unix_millis()
+    }
+
+    /// Validates the given ID is a plausible ID generated by this generator,
+    /// see [`SnowflakeGenerator::validate`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    /// assert!(gen.is_valid(gen.next_id().unwrap()));
+    ///
+    /// let other = SnowflakeGenerator::new(3, 8).unwrap();
+    /// assert!(!gen.is_valid(other.next_id().unwrap()));
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn is_valid(&self, id: u64) -> bool {
+        self.validate(id).is_ok()
+    }
+
+    /// Validates the given ID is a plausible ID generated by this generator, for IDs read from untrusted input.
+    ///
+    /// The ID is decomposed by the layout of this generator, the `data-center` ID and
+    /// `worker` ID must match, and the embedded timestamp must be between the epoch and now.
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::CenterIdInvalid`]: The `data-center` ID mismatched.
+    /// - [`SnowflakeError::WorkerIdInvalid`]: The `worker` ID mismatched.
+    /// - [`SnowflakeError::TimestampInvalid`]: The sign bit set, or the timestamp in the future.
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn validate(&self, id: u64) -> Result<(), SnowflakeError> {
+        let parts = self.layout.decompose(id);
+
+        if parts.center_id != self.center_id {
+            return Err(SnowflakeError::CenterIdInvalid);
+        }
+
+        if parts.worker_id != self.worker_id {
+            return Err(SnowflakeError::WorkerIdInvalid);
+        }
+
+        if parts.timestamp > self.layout.max_timestamp() {
+            return Err(SnowflakeError::TimestampInvalid);
+        }
+
+        // A crafted ID may overflow with a wide timestamp of a coarse precision.
+        let timestamp = parts
+            .timestamp
+            .checked_mul(self.precision.unit_millis())
+            .and_then(|millis| millis.checked_add(self.epoch))
+            .ok_or(SnowflakeError::TimestampInvalid)?;
+        if timestamp > self.now_millis()? {
+            return Err(SnowflakeError::TimestampInvalid);
+        }
+
+        Ok(())
+    }
+
+    /// Runs a quick diagnostic self-test, e.g. on startup, returns the first problem found:
+    ///
+    /// 1. the clock is not before the epoch.
+    /// 2. a small batch of the generated IDs is strictly increasing, or unique in [`PackingOrder::SequenceHigh`].
+    /// 3. each ID decomposes back into the fields it was generated from, see [`SnowflakeGenerator::validate`].
+    ///
+    /// The batch consumes the real IDs of this generator, they are discarded.
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::ClockBeforeEpoch`]: The clock is before the epoch.
+    /// - [`SnowflakeError::SelfTestFailed`]: The IDs regressed, duplicated or mis-decomposed.
+    /// - The errors of [`Generator::next_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    /// assert!(gen.self_test().is_ok());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn self_test(&self) -> Result<(), SnowflakeError> {
+        const BATCH: usize = 64;
+
+        self.ticks_since_epoch(self.now_millis()?)?;
+
+        let generated = (0..BATCH)
+            .map(|_| self.generate())
+            .collect::<Result<Vec<_>, _>>()?;
+        let ids: Vec<u64> = generated.iter().map(|generated| generated.id).collect();
+
+        let ordered = match self.layout.packing_order() {
+            PackingOrder::TimestampHigh => {
+                verify::verify_monotonic(&ids).map_err(|err| err.to_string())
+            }
+            PackingOrder::SequenceHigh => {
+                verify::verify_unique(&ids).map_err(|err| err.to_string())
+            }
+        };
+        ordered.map_err(SnowflakeError::SelfTestFailed)?;
+
+        for generated in &generated {
+            let parts = self.layout.decompose(generated.id);
+            if self.validate(generated.id).is_err() || parts.sequence != generated.sequence {
+                return Err(SnowflakeError::SelfTestFailed(format!(
+                    "ID {} decomposed into {:?}",
+                    generated.id, parts
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Converts the given ID into `i64` losslessly, for databases storing IDs as signed 64-bit.
+    ///
+    /// Returns `None` if the high bit is set, which never happens to the generated IDs
+    /// since the sign bit is reserved, see [`Constants::ID_BITS`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// assert_eq!(Some(i64::MAX), SnowflakeGenerator::as_i64(i64::MAX as u64));
+    /// assert_eq!(None, SnowflakeGenerator::as_i64(1 << 63));
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn as_i64(id: u64) -> Option<i64> {
+        i64::try_from(id).ok()
+    }
+
+    /// Returns the [`SystemTime`] at which the given ID was generated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflaker::generator::{Constants, SnowflakeGenerator};
+    ///
+    /// let at = SnowflakeGenerator::system_time_of(0);
+    /// assert_eq!(UNIX_EPOCH + Duration::from_millis(Constants::EPOCH), at);
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn system_time_of(id: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(Self::timestamp_of(id))
+    }
+
+    /// Returns an independent copy of this generator with the same configuration,
+    /// its runtime state starts from the current one of this generator but is no longer shared.
+    ///
+    /// Unlike [`Clone`], which shares the runtime state, the fork and this generator both
+    /// continue from the same `(last_timestamp, sequence)` and emit the same IDs within the
+    /// same millisecond, so the fork should not generate IDs in the same ID space,
+    /// e.g. rebuild it with another `worker` ID.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// assert!(gen.next_id().is_ok());
+    ///
+    /// let fork = gen.fork();
+    /// assert_eq!(gen.snapshot(), fork.snapshot());
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn fork(&self) -> SnowflakeGenerator {
+        let mut forked = self.clone();
+        forked.state = Arc::new(AtomicU64::new(self.state.load(self.ordering.load())));
+        #[cfg(feature = "std")]
+        {
+            forked.restored_timestamp = Arc::new(AtomicU64::new(
+                self.restored_timestamp.load(self.ordering.load()),
+            ));
+            forked.rate_limiter = self
+                .rate_limiter
+                .as_ref()
+                .map(|limiter| Arc::new(limiter.fork()));
+            forked.timestamp_cache = self
+                .timestamp_cache
+                .as_ref()
+                .map(|cache| Arc::new(cache.fork()));
+        }
+        forked.peak_sequence = Arc::new(AtomicU64::new(
+            self.peak_sequence.load(self.ordering.load()),
+        ));
+
+        forked
+    }
+
+    /// Returns a snapshot of the runtime state, e.g. to persist it on a graceful shutdown
+    /// and [`SnowflakeGenerator::restore`] it on restart.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let id = gen.next_id().unwrap();
+    ///
+    /// let state = gen.snapshot();
+    /// assert_eq!(SnowflakeGenerator::timestamp_of(id), state.last_timestamp);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn snapshot(&self) -> GeneratorState {
+        let (last_timestamp, sequence) = Self::unpack_state(self.state.load(self.ordering.load()));
+
+        GeneratorState {
+            last_timestamp,
+            sequence,
+        }
+    }
+
+    /// Returns the last-observed sequence divided by the sequence mask, e.g. `4095`,
+    /// in `[0.0, 1.0]`: how close the current millisecond is to [`SnowflakeError::SequenceExhausted`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// assert_eq!(0.0, gen.current_sequence_utilization());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn current_sequence_utilization(&self) -> f32 {
+        let mask = self.layout.sequence_mask();
+        if mask == 0 {
+            return 0.0;
+        }
+
+        self.snapshot().sequence as f32 / mask as f32
+    }
+
+    /// Returns how many IDs remain before the sequence rolls over in the current tick of the clock:
+    /// the sequence mask minus the last sequence while the clock still reads the last timestamp,
+    /// otherwise the full window of the sequence mask plus one.
+    ///
+    /// A `0` means the next [`Generator::next_id`] waits for the next tick,
+    /// or fails fast with the [`OverflowPolicy::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Constants, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// assert_eq!(Constants::SEQUENCE_MASK + 1, gen.remaining_in_window());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn remaining_in_window(&self) -> u64 {
+        let mask = self.layout.sequence_mask();
+        let state = self.snapshot();
+
+        match self.now_millis() {
+            Ok(now_millis) if now_millis != state.last_timestamp => {
+                let ticks = self.ticks_since_epoch(now_millis).unwrap_or(0);
+                mask + 1 - self.sequence_start(ticks)
+            }
+            // Unknown clock, assume the worst case of the same tick.
+            _ => mask - state.sequence,
+        }
+    }
+
+    /// Waits until the clock moves past the last timestamp, so the next ID starts a fresh window
+    /// with the sequence reset, without generating IDs to exhaust the sequence,
+    /// e.g. the rollover-edge tests.
+    ///
+    /// Returns immediately if no ID was emitted yet, it polls like the sequence rollover
+    /// with the [`SpinStrategy`] of this generator.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the [`Clock`] read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let id = gen.next_id().unwrap();
+    ///
+    /// gen.advance_window().unwrap();
+    /// let next = gen.next_id().unwrap();
+    /// assert!(SnowflakeGenerator::timestamp_of(next) > SnowflakeGenerator::timestamp_of(id));
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn advance_window(&self) -> Result<(), SnowflakeError> {
+        self.til_next_millis_by_clock(self.get_last_timestamp())
+            .map(|_| ())
+    }
+
+    /// Returns the peak sequence ever emitted, shared between the clones.
+    ///
+    /// A peak close to the sequence mask is a hint to give the sequence more bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// assert!(gen.next_id_with_timestamp(1_700_000_000_000).is_ok());
+    /// assert!(gen.next_id_with_timestamp(1_700_000_000_000).is_ok());
+    /// assert_eq!(1, gen.peak_sequence());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn peak_sequence(&self) -> u64 {
+        self.peak_sequence.load(self.ordering.load())
+    }
+
+    /// Restores the runtime state of a [`SnowflakeGenerator::snapshot`].
+    ///
+    /// The state never moves backwards: a state behind the current one is ignored.
+    /// If the restored timestamp is ahead of the clock, the next IDs wait until the clock
+    /// catches up instead of returning [`SnowflakeError::ClockMovedBackwards`],
+    /// except [`SnowflakeGenerator::next_id_with_timestamp`] which never waits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let id = gen.next_id().unwrap();
+    /// let state = gen.snapshot();
+    ///
+    /// // restarted
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// gen.restore(state);
+    /// assert!(gen.next_id().unwrap() > id);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn restore(&self, state: GeneratorState) {
+        let restored = (state.last_timestamp, state.sequence & STATE_SEQUENCE_MASK);
+        self.update_state(|last_timestamp, sequence| (last_timestamp, sequence).max(restored));
+
+        #[cfg(feature = "std")]
+        self.restored_timestamp
+            .fetch_max(state.last_timestamp, self.ordering.success());
+    }
+
+    #[cfg(feature = "std")]
+    fn is_restored_ahead(&self, last_timestamp: u64) -> bool {
+        last_timestamp <= self.restored_timestamp.load(self.ordering.load())
+    }
+
+    /// Returns the last timestamp if the timestamp cache is enabled, the clock read it less than
+    /// a millisecond ago and its sequence has room, a restored timestamp ahead of the clock
+    /// or a supplied one of [`SnowflakeGenerator::next_id_with_timestamp`] is never reused.
+    #[cfg(feature = "std")]
+    fn cached_timestamp(&self) -> Option<u64> {
+        let read = self.timestamp_cache.as_ref()?.fresh_timestamp()?;
+
+        let (last_timestamp, sequence) = Self::unpack_state(self.state.load(self.ordering.load()));
+        if last_timestamp != read
+            || sequence >= self.layout.sequence_mask()
+            || self.is_restored_ahead(last_timestamp)
+        {
+            return None;
+        }
+
+        Some(last_timestamp)
+    }
+
+    /// Returns an endless [`SnowflakeIter`] which generates the next ID on each iteration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let ids: Result<Vec<u64>, _> = gen.iter().take(1000).collect();
+    /// assert_eq!(1000, ids.unwrap().len());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn iter(&self) -> SnowflakeIter<'_> {
+        SnowflakeIter { generator: self }
+    }
+
+    /// Generates and returns a unique ID at the externally supplied timestamp,
+    /// which is available without the `std` feature (`no_std`).
+    ///
+    /// It never waits: a timestamp before the last one returns
+    /// [`SnowflakeError::ClockMovedBackwards`], and an exhausted sequence within the same millisecond
+    /// returns [`SnowflakeError::SequenceExhausted`], the caller retries with a later timestamp.
+    /// A timestamp beyond [`BitLayout::max_timestamp`] or `2^42 - 1` unix-millis (the width of the state)
+    /// returns [`SnowflakeError::TimestampOverflow`].
+    /// A timestamp before the epoch returns [`SnowflakeError::ClockBeforeEpoch`].
+    ///
+    /// # Arguments
+    ///
+    /// - `now_millis`: The current unix-millis supplied by the caller's time provider.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Constants, SnowflakeError, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let now = Constants::EPOCH + 1000;
+    ///
+    /// let id = gen.next_id_with_timestamp(now).unwrap();
+    /// assert_eq!(now, SnowflakeGenerator::timestamp_of(id));
+    ///
+    /// let rvt = gen.next_id_with_timestamp(now - 1);
+    /// assert!(matches!(rvt, Err(SnowflakeError::ClockMovedBackwards)));
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn next_id_with_timestamp(&self, now_millis: u64) -> Result<u64, SnowflakeError> {
+        self.generate_with_timestamp(now_millis)
+            .map(|generated| generated.id)
+    }
+
+    /// Generates and returns a unique ID at the given historical timestamp, e.g. to backfill
+    /// the records with the IDs embedding their original creation time.
+    ///
+    /// The repeated calls at the same timestamp increment the sequence like
+    /// [`SnowflakeGenerator::next_id_with_timestamp`], so the IDs stay unique.
+    /// The timestamps must be supplied in ascending order, a dedicated generator,
+    /// e.g. with a distinct `worker` ID, keeps the live IDs from blocking the backfill.
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::TimestampInvalid`]: The timestamp is in the future of the [`Clock`].
+    /// - [`SnowflakeError::ClockBeforeEpoch`]: The timestamp is before the epoch.
+    /// - The errors of [`SnowflakeGenerator::next_id_with_timestamp`], e.g.
+    ///   [`SnowflakeError::ClockMovedBackwards`] for a timestamp before the last one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Constants, SnowflakeError, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    /// let created_at = Constants::EPOCH + 1000;
+    ///
+    /// let id = gen.next_id_at(created_at).unwrap();
+    /// assert_eq!(created_at, SnowflakeGenerator::timestamp_of(id));
+    ///
+    /// let rvt = gen.next_id_at(u64::MAX);
+    /// assert!(matches!(rvt, Err(SnowflakeError::TimestampInvalid)));
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_at(&self, timestamp_millis: u64) -> Result<u64, SnowflakeError> {
+        // Both truncated to the precision, a timestamp within the current second is not in the future.
+        if self.precision.truncate(timestamp_millis) > self.now_millis()? {
+            return Err(SnowflakeError::TimestampInvalid);
+        }
+
+        self.next_id_with_timestamp(timestamp_millis)
+    }
+
+    /// Generates and returns a unique ID which lands in the target shard: `id % shard_count == shard`.
+    ///
+    /// The sequence is skipped forward within the current millisecond to the nearest matching ID,
+    /// waits for the next millisecond if the sequence runs out first.
+    /// The skipped sequences are never emitted, so the uniqueness is preserved.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(u64)`: The ID in the target shard.
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::ShardInvalid`] if the `shard_count` is zero
+    ///   or exceeds the sequence space (a match within a millisecond is not guaranteed),
+    ///   the `shard` is not less than the `shard_count`, the layout reserves tag bits,
+    ///   or packs the sequence in the high bits, see [`PackingOrder::SequenceHigh`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    ///
+    /// let id = gen.next_id_for_shard(3, 8).unwrap();
+    /// assert_eq!(3, id % 8);
+    ///
+    /// assert!(gen.next_id_for_shard(8, 8).is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_for_shard(&self, shard: u64, shard_count: u64) -> Result<u64, SnowflakeError> {
+        if shard_count == 0
+            || shard_count > self.layout.sequence_mask() + 1
+            || shard >= shard_count
+            || self.layout.tag_bits() > 0
+            || self.layout.packing_order() == PackingOrder::SequenceHigh
+        {
+            return Err(SnowflakeError::ShardInvalid);
+        }
+
+        self.generate_for(Some((shard, shard_count)))
+            .map(|generated| generated.id)
+    }
+
+    /// Generates and returns a unique ID carrying the caller-supplied tag in the tag bits
+    /// of the layout, see [`BitLayout::with_tag_bits`].
+    ///
+    /// The tag is surfaced by [`BitLayout::decompose`], e.g. for a router to dispatch
+    /// by the entity-type without a lookup.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(u64)`: The tagged ID.
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::TagInvalid`] if the tag exceeds [`BitLayout::max_tag`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{BitLayout, SnowflakeGenerator};
+    ///
+    /// let layout = BitLayout::default().with_tag_bits(4);
+    /// let gen = SnowflakeGenerator::builder().layout(layout).build().unwrap();
+    ///
+    /// let id = gen.next_id_tagged(9).unwrap();
+    /// assert_eq!(9, layout.decompose(id).tag);
+    ///
+    /// assert!(gen.next_id_tagged(16).is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_tagged(&self, tag: u64) -> Result<u64, SnowflakeError> {
+        if tag > self.layout.max_tag() {
+            return Err(SnowflakeError::TagInvalid);
+        }
+
+        // The tag bits of a generated ID are always zero.
+        self.generate().map(|generated| generated.id | tag)
+    }
+
+    /// Generates and returns a unique ID of the given tenant, for the multiple tenants
+    /// hosted by one generator without allocating a `worker` ID for each of them.
+    ///
+    /// The tenant field is carved from the sequence bits as the tag bits of the layout,
+    /// its width is configured by [`BitLayout::with_tag_bits`] and it is surfaced by [`IdParts::tenant`].
+    /// The tenants share the sequence, so their IDs never collide.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(u64)`: The ID of the tenant.
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::TagInvalid`] if the tenant exceeds [`BitLayout::max_tag`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{BitLayout, SnowflakeGenerator};
+    ///
+    /// // up to 16 tenants
+    /// let layout = BitLayout::default().with_tag_bits(4);
+    /// let gen = SnowflakeGenerator::builder().layout(layout).build().unwrap();
+    ///
+    /// let id = gen.next_id_for_tenant(3).unwrap();
+    /// assert_eq!(3, layout.decompose(id).tenant());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_for_tenant(&self, tenant: u64) -> Result<u64, SnowflakeError> {
+        self.next_id_tagged(tenant)
+    }
+
+    /// Generates and returns a unique ID as `i64`, see [`SnowflakeGenerator::as_i64`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let rvt = gen.next_id_i64();
+    /// assert!(rvt.unwrap() > 0);
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_i64(&self) -> Result<i64, SnowflakeError> {
+        let id = self.generate()?.id;
+        Self::as_i64(id).ok_or(SnowflakeError::TimestampOverflow)
+    }
+
+    /// Generates and returns a unique ID as a fixed 8-byte big-endian array,
+    /// see [`crate::codec::id_to_be_bytes`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::codec;
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let bytes = gen.next_id_bytes().unwrap();
+    /// assert!(codec::id_from_be_bytes(bytes) > 0);
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_bytes(&self) -> Result<[u8; 8], SnowflakeError> {
+        self.generate()
+            .map(|generated| crate::codec::id_to_be_bytes(generated.id))
+    }
+
+    /// Generates and returns a unique decimal ID with the given `prefix` attached,
+    /// e.g. `ORD-122235238222008321`, see [`crate::codec::strip_prefix_parse`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::codec;
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let prefixed = gen.next_id_prefixed("ORD-").unwrap();
+    /// assert!(prefixed.starts_with("ORD-"));
+    /// assert!(codec::strip_prefix_parse(&prefixed, "ORD-").unwrap() > 0);
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_prefixed(&self, prefix: &str) -> Result<String, SnowflakeError> {
+        self.generate()
+            .map(|generated| format!("{}{}", prefix, generated.id))
+    }
+
+    /// Generates and returns a unique ID in the given `radix` (`2..=36`) with the given `prefix`
+    /// attached, see [`crate::codec::to_radix`] and [`crate::codec::strip_prefix_parse_radix`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SnowflakeError::RadixInvalid`] if the `radix` out of range `2..=36`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::codec;
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let prefixed = gen.next_id_prefixed_radix("ORD-", 36).unwrap();
+    /// assert!(codec::strip_prefix_parse_radix(&prefixed, "ORD-", 36).unwrap() > 0);
+    /// assert!(gen.next_id_prefixed_radix("ORD-", 37).is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_prefixed_radix(
+        &self,
+        prefix: &str,
+        radix: u32,
+    ) -> Result<String, SnowflakeError> {
+        if !(2..=36).contains(&radix) {
+            return Err(SnowflakeError::RadixInvalid);
+        }
+
+        let id = self.generate()?.id;
+        crate::codec::to_radix(id, radix, 0, '0').map(|encoded| format!("{}{}", prefix, encoded))
+    }
+
+    /// Generates and returns a unique ID along with its component fields in one call.
+    ///
+    /// The fields are the same values packed into the ID, not re-decomposed from it,
+    /// so they are guaranteed consistent with [`GeneratedId::id`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    /// let generated = gen.next_id_with_parts().unwrap();
+    /// assert_eq!(7, generated.worker_id);
+    /// assert_eq!(generated.sequence, SnowflakeGenerator::decompose(generated.id).sequence);
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_with_parts(&self) -> Result<GeneratedId, SnowflakeError> {
+        self.generate()
+    }
+
+    /// Generates and returns a unique ID, retrying on the retryable errors,
+    /// see [`SnowflakeError::is_retryable`], e.g. [`SnowflakeError::ClockMovedBackwards`].
+    ///
+    /// Tries at most `attempts` times (at least once), sleeping `backoff` between the tries.
+    ///
+    /// # Errors
+    ///
+    /// Returns the permanent error immediately, or the last retryable error if all the tries failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let rvt = gen.next_id_retry(3, Duration::from_millis(1));
+    /// assert!(rvt.is_ok());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_id_retry(&self, attempts: usize, backoff: Duration) -> Result<u64, SnowflakeError> {
+        let mut rvt = self.next_id();
+        for _ in 1..attempts {
+            match &rvt {
+                Err(err) if err.is_retryable() => {
+                    thread::sleep(backoff);
+                    rvt = self.next_id();
+                }
+                _ => break,
+            }
+        }
+
+        rvt
+    }
+
+    /// Generates and returns up to `n` unique IDs, waiting at most `max_wait` in total
+    /// for the next millisecond instead of blocking indefinitely, e.g. on a stalled clock.
+    ///
+    /// If the time budget is exceeded, returns the fewer IDs generated before it,
+    /// they are still unique and strictly increasing.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`Generator::next_id`], e.g. [`SnowflakeError::ClockMovedBackwards`]
+    /// if the clock moved backwards beyond the tolerance, or as decided by the hook of
+    /// [`SnowflakeGeneratorBuilder::on_clock_backwards`], a [`ClockBackwardsDecision::WaitThenRetry`]
+    /// waits within the `max_wait`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let ids = gen.next_ids_timeout(10_000, Duration::from_secs(1)).unwrap();
+    /// assert_eq!(10_000, ids.len());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_ids_timeout(
+        &self,
+        n: usize,
+        max_wait: Duration,
+    ) -> Result<Vec<u64>, SnowflakeError> {
+        let deadline = Instant::now() + max_wait;
+        let mut ids = Vec::with_capacity(n);
+
+        while ids.len() < n {
+            let timestamp = self.now_millis()?;
+
+            match self.generate_with_timestamp(timestamp) {
+                Ok(generated) => ids.push(generated.id),
+                Err(SnowflakeError::SequenceExhausted) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.on_sequence_rollover(timestamp);
+                    }
+
+                    if !self.wait_next_millis_until(timestamp, deadline)? {
+                        break;
+                    }
+                }
+                Err(SnowflakeError::ClockMovedBackwards) => {
+                    // Re-read the clock after the state like `SnowflakeGenerator::generate_for`.
+                    let last_timestamp = self.get_last_timestamp();
+                    let timestamp = self.now_millis()?;
+                    if timestamp >= last_timestamp {
+                        continue;
+                    }
+
+                    let delta = last_timestamp - timestamp;
+                    if let Some(metrics) = &self.metrics {
+                        metrics.on_clock_backwards(delta);
+                    }
+
+                    if !self.is_restored_ahead(last_timestamp) {
+                        match self.on_clock_backwards.as_ref().map(|hook| hook(delta)) {
+                            // Waits below within the time budget.
+                            Some(ClockBackwardsDecision::WaitThenRetry) => {}
+                            Some(ClockBackwardsDecision::Fail) => {
+                                return Err(SnowflakeError::ClockMovedBackwards);
+                            }
+                            Some(ClockBackwardsDecision::ProceedWithLastTimestamp) => {
+                                match self.generate_with_timestamp(last_timestamp) {
+                                    Ok(generated) => ids.push(generated.id),
+                                    // A sibling clone advanced the shared state in the meantime.
+                                    Err(SnowflakeError::ClockMovedBackwards) => {}
+                                    Err(err) => return Err(err),
+                                }
+                                continue;
+                            }
+                            None if delta > self.max_backward_tolerance_millis => {
+                                return Err(SnowflakeError::ClockMovedBackwards);
+                            }
+                            None => {}
+                        }
+                    }
+
+                    if !self.wait_next_millis_until(last_timestamp - 1, deadline)? {
+                        break;
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Generates `n` unique IDs and joins their decimal strings with the `sep`,
+    /// e.g. for a CSV or a log line. Returns an empty string if `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    ///
+    /// let joined = gen.next_ids_joined(3, ",").unwrap();
+    /// assert_eq!(3, joined.split(',').count());
+    ///
+    /// assert_eq!("", gen.next_ids_joined(0, ",").unwrap());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn next_ids_joined(&self, n: usize, sep: &str) -> Result<String, SnowflakeError> {
+        // The max `u64` has 20 decimal digits.
+        let mut joined = String::with_capacity(n * (20 + sep.len()));
+        for (i, id) in self.iter().take(n).enumerate() {
+            if i > 0 {
+                joined.push_str(sep);
+            }
+            let _ = write!(joined, "{}", id?);
+        }
+
+        Ok(joined)
+    }
+
+    /// Spawns a producer thread which generates the IDs continuously into a bounded channel
+    /// of the `buffer` capacity, so the consumers pull the pre-generated IDs with minimal latency.
+    ///
+    /// The thread blocks while the channel is full, and exits once the [`Receiver`] is dropped.
+    /// An error is delivered as is, the producer keeps going.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// let rx = SnowflakeGenerator::builtin().unwrap().spawn_producer(1024);
+    /// let id = rx.recv().unwrap();
+    /// assert!(id.is_ok());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn spawn_producer(self, buffer: usize) -> Receiver<Result<u64, SnowflakeError>> {
+        let (tx, rx) = mpsc::sync_channel(buffer);
+        thread::spawn(move || while tx.send(self.next_id()).is_ok() {});
+
+        rx
+    }
+
+    /// Generates and returns a unique ID like [`Generator::next_id`], but awaits instead of
+    /// blocking the executor thread when it needs to wait for the next millisecond
+    /// or ride out a small clock rollback within the tolerance.
+    ///
+    /// The clock moved backwards is decided by the hook of [`SnowflakeGeneratorBuilder::on_clock_backwards`]
+    /// if any, like [`Generator::next_id`].
+    ///
+    /// This function is available when the `async` feature is enabled, waits by [`tokio::time::sleep`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::SnowflakeGenerator;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+    /// let gen = SnowflakeGenerator::builtin().unwrap();
+    /// let rvt = gen.next_id_async().await;
+    /// assert!(rvt.is_ok());
+    /// # });
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "async")]
+    pub async fn next_id_async(&self) -> Result<u64, SnowflakeError> {
+        loop {
+            let timestamp = self.now_millis()?;
+
+            match self.generate_with_timestamp(timestamp) {
+                Err(SnowflakeError::ClockMovedBackwards) => {
+                    let last_timestamp = self.get_last_timestamp();
+                    let timestamp = self.now_millis()?;
+                    if timestamp >= last_timestamp {
+                        continue;
+                    }
+
+                    let delta = last_timestamp - timestamp;
+                    if let Some(metrics) = &self.metrics {
+                        metrics.on_clock_backwards(delta);
+                    }
+
+                    if self.is_restored_ahead(last_timestamp) {
+                        // Restored ahead of the clock, wait until the clock catches up.
+                        tokio::time::sleep(Duration::from_millis(delta)).await;
+                        continue;
+                    }
+
+                    if let Some(hook) = &self.on_clock_backwards {
+                        match hook(delta) {
+                            ClockBackwardsDecision::WaitThenRetry => {
+                                tokio::time::sleep(Duration::from_millis(delta)).await;
+                                continue;
+                            }
+                            ClockBackwardsDecision::Fail => {
+                                return Err(SnowflakeError::ClockMovedBackwards);
+                            }
+                            ClockBackwardsDecision::ProceedWithLastTimestamp => {
+                                match self.generate_with_timestamp(last_timestamp) {
+                                    // A sibling clone advanced the shared state in the meantime.
+                                    Err(SnowflakeError::ClockMovedBackwards) => continue,
+                                    rvt => return rvt.map(|generated| generated.id),
+                                }
+                            }
+                        }
+                    }
+
+                    if delta > self.max_backward_tolerance_millis {
+                        return Err(SnowflakeError::ClockMovedBackwards);
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(delta << 1)).await;
+                    if self.now_millis()? < last_timestamp {
+                        return Err(SnowflakeError::ClockMovedBackwards);
+                    }
+                }
+                Err(SnowflakeError::SequenceExhausted) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.on_sequence_rollover(timestamp);
+                    }
+
+                    if self.overflow_policy == OverflowPolicy::Error {
+                        return Err(SnowflakeError::SequenceExhausted);
+                    }
+
+                    // Sleep until the next tick of the precision, at least `1ms`.
+                    let next = timestamp + self.precision.unit_millis();
+                    let wait = next.saturating_sub(self.clock.now_millis()?).max(1);
+                    tokio::time::sleep(Duration::from_millis(wait)).await;
+                }
+                rvt => return rvt.map(|generated| generated.id),
+            }
+        }
+    }
+
+    /// Generates a unique ID at the given timestamp along with the values packed into it.
+    fn generate_with_timestamp(&self, now_millis: u64) -> Result<GeneratedId, SnowflakeError> {
+        self.generate_with_timestamp_for(now_millis, None)
+    }
+
+    /// Generates a unique ID at the given timestamp like [`SnowflakeGenerator::generate_with_timestamp`],
+    /// skips the sequence forward until `id % shard_count == shard` for the `(shard, shard_count)`.
+    ///
+    /// Takes one from the budget of the rate limiter if any, given back if no ID was generated.
+    fn generate_with_timestamp_for(
+        &self,
+        now_millis: u64,
+        shard: Option<(u64, u64)>,
+    ) -> Result<GeneratedId, SnowflakeError> {
+        #[cfg(feature = "std")]
+        if let Some(limiter) = &self.rate_limiter {
+            let second = limiter.try_acquire(now_millis)?;
+
+            let rvt = self.emit_with_timestamp_for(now_millis, shard);
+            if rvt.is_err() {
+                limiter.release(second);
+            }
+
+            return rvt;
+        }
+
+        self.emit_with_timestamp_for(now_millis, shard)
+    }
+
+    /// Emits a unique ID at the given timestamp by one successful `CAS` on the shared state,
+    /// see [`SnowflakeGenerator::generate_with_timestamp_for`].
+    fn emit_with_timestamp_for(
+        &self,
+        now_millis: u64,
+        shard: Option<(u64, u64)>,
+    ) -> Result<GeneratedId, SnowflakeError> {
+        let now_millis = self.precision.truncate(now_millis);
+
+        loop {
+            let state = self.state.load(self.ordering.load());
+            let (last_timestamp, last_sequence) = Self::unpack_state(state);
+
+            if now_millis < last_timestamp {
+                return Err(SnowflakeError::ClockMovedBackwards);
+            }
+
+            let ticks = self.ticks_since_epoch(now_millis)?;
+            // The packed state keeps 42 bits of the unix-millis, a wider one would wrap around.
+            if ticks > self.layout.max_timestamp() || now_millis > STATE_MAX_TIMESTAMP {
+                return Err(SnowflakeError::TimestampOverflow);
+            }
+
+            // Nothing emitted yet, the first ID starts with the initial sequence.
+            let mut sequence = if last_timestamp == 0 {
+                last_sequence
+            } else {
+                self.sequence_start(ticks)
+            };
+
+            if now_millis == last_timestamp {
+                sequence = (last_sequence + 1) & self.layout.sequence_mask();
+                if sequence == 0 {
+                    return Err(SnowflakeError::SequenceExhausted);
+                }
+            }
+
+            if let Some((shard, shard_count)) = shard {
+                let id = self
+                    .layout
+                    .compose(ticks, self.center_id, self.worker_id, sequence);
+                // The skipped sequences are never emitted, the uniqueness is preserved.
+                sequence += (shard + shard_count - id % shard_count) % shard_count;
+                if sequence > self.layout.sequence_mask() {
+                    return Err(SnowflakeError::SequenceExhausted);
+                }
+            }
+
+            // Each ID is derived from exactly one successful `CAS`,
+            // retry if a sibling clone updated the shared state in the meantime.
+            if self
+                .state
+                .compare_exchange_weak(
+                    state,
+                    Self::pack_state(now_millis, sequence),
+                    self.ordering.success(),
+                    self.ordering.failure(),
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            let id = self
+                .layout
+                .compose(ticks, self.center_id, self.worker_id, sequence);
+
+            #[cfg(debug_assertions)]
+            self.debug_assert_monotonic(last_timestamp, last_sequence, id);
+
+            self.peak_sequence
+                .fetch_max(sequence, self.ordering.success());
+
+            #[cfg(feature = "std")]
+            if let Some(metrics) = &self.metrics {
+                metrics.on_id_generated(id);
+            }
+
+            return Ok(GeneratedId {
+                id,
+                timestamp_millis: now_millis,
+                center_id: self.center_id,
+                worker_id: self.worker_id,
+                sequence,
+            });
+        }
+    }
+
+    /// Returns the sequence a new tick starts with: zero, or a pseudo-random offset of the tick
+    /// within the lower half of the sequence space if [`SnowflakeGeneratorBuilder::randomize_sequence`].
+    fn sequence_start(&self, ticks: u64) -> u64 {
+        if !self.randomize_sequence {
+            return 0;
+        }
+
+        let node = (self.center_id << self.layout.worker_id_bits()) | self.worker_id;
+        crate::generator128::splitmix64(ticks ^ (node << 32)) & (self.layout.sequence_mask() >> 1)
+    }
+
+    /// Asserts the new `id` is strictly greater than the last emitted one across all clones.
+    ///
+    /// The shared `state` replaced by the successful `CAS` records the last emitted
+    /// `(timestamp, sequence)`, so the last emitted ID is derived from it, a separate atomic
+    /// could not be updated together with the `state` and would race between siblings.
+    #[cfg(debug_assertions)]
+    pub(crate) fn debug_assert_monotonic(&self, last_timestamp: u64, last_sequence: u64, id: u64) {
+        let Ok(last_ticks) = self.ticks_since_epoch(last_timestamp) else {
+            // Nothing emitted yet.
+            return;
+        };
+        if self.layout.packing_order() == PackingOrder::SequenceHigh {
+            // Not time-sortable by design.
+            return;
+        }
+
+        let last_id =
+            self.layout
+                .compose(last_ticks, self.center_id, self.worker_id, last_sequence);
+        debug_assert!(
+            id > last_id,
+            "snowflake id regressed: {} after {}",
+            id,
+            last_id
+        );
+    }
+
+    /// Returns the ticks of the [`TimePrecision`] since the epoch of the given unix-millis,
+    /// or [`SnowflakeError::ClockBeforeEpoch`] if the unix-millis precede the epoch.
+    fn ticks_since_epoch(&self, millis: u64) -> Result<u64, SnowflakeError> {
+        millis
+            .checked_sub(self.epoch)
+            .map(|elapsed| elapsed / self.precision.unit_millis())
+            .ok_or(SnowflakeError::ClockBeforeEpoch)
+    }
+
+    /// Reads the [`Clock`] of this generator, truncated to the [`TimePrecision`].
+    #[cfg(feature = "std")]
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        Ok(self.precision.truncate(self.clock.now_millis()?))
+    }
+
+    /// Generates a unique ID like [`Generator::next_id`] in a `next_id` span.
+    #[cfg(feature = "tracing")]
+    fn next_id_traced(&self) -> Result<u64, SnowflakeError> {
+        use tracing::field;
+
+        let span = tracing::debug_span!(
+            "next_id",
+            id = field::Empty,
+            rollover = false,
+            clock_backwards = false,
+            waited_micros = field::Empty,
+            error = field::Empty,
+        )
+        .entered();
+
+        let mut waited = Duration::ZERO;
+        let rvt = self
+            .generate_for_waited(None, &mut waited)
+            .map(|generated| generated.id);
+
+        span.record("waited_micros", waited.as_micros() as u64);
+        match &rvt {
+            Ok(id) => span.record("id", id),
+            Err(err) => span.record("error", field::display(err)),
+        };
+
+        rvt
+    }
+
+    /// Waits for the clock moved backwards by `delta` to catch up `last_timestamp`
+    /// with the [`WaitStrategy`] of this generator, returns the re-read timestamp.
+    #[cfg(feature = "std")]
+    fn wait_backwards(&self, last_timestamp: u64, delta: u64) -> Result<u64, SnowflakeError> {
+        match self.wait_strategy {
+            WaitStrategy::Sleep => {
+                TimeUnit::Milliseconds.sleep(delta << 1);
+                self.now_millis()
+            }
+            WaitStrategy::Spin => {
+                let deadline = Instant::now() + Duration::from_millis(delta << 1);
+                loop {
+                    let timestamp = self.now_millis()?;
+                    if timestamp >= last_timestamp || Instant::now() >= deadline {
+                        return Ok(timestamp);
+                    }
+                    hint::spin_loop();
+                }
+            }
+        }
+    }
+
+    /// Generates a unique ID by the [`Clock`] of this generator along with the values packed into it,
+    /// waits through the clock moved backwards within the tolerance and the exhausted sequence.
+    #[cfg(feature = "std")]
+    fn generate(&self) -> Result<GeneratedId, SnowflakeError> {
+        self.generate_for(None)
+    }
+
+    /// Generates a unique ID by the [`Clock`] of this generator like [`SnowflakeGenerator::generate`],
+    /// for the `(shard, shard_count)` if any, see [`SnowflakeGenerator::generate_with_timestamp_for`].
+    #[cfg(feature = "std")]
+    fn generate_for(&self, shard: Option<(u64, u64)>) -> Result<GeneratedId, SnowflakeError> {
+        self.generate_for_waited(shard, &mut Duration::default())
+    }
+
+    /// Generates a unique ID like [`SnowflakeGenerator::generate_for`],
+    /// adds the time spent waiting on the rollover and the clock moved backwards to `waited`.
+    #[cfg(feature = "std")]
+    pub(crate) fn generate_for_waited(
+        &self,
+        shard: Option<(u64, u64)>,
+        waited: &mut Duration,
+    ) -> Result<GeneratedId, SnowflakeError> {
+        loop {
+            let timestamp = match self.cached_timestamp() {
+                Some(timestamp) => timestamp,
+                None => {
+                    let timestamp = self.now_millis()?;
+                    if let Some(cache) = &self.timestamp_cache {
+                        cache.mark_read(timestamp);
+                    }
+                    timestamp
+                }
+            };
+
+            match self.generate_with_timestamp_for(timestamp, shard) {
+                Err(SnowflakeError::ClockMovedBackwards) => {
+                    // Re-read the clock after the state, the timestamp may be stale
+                    // if a sibling clone advanced the shared state in the meantime.
+                    let last_timestamp = self.get_last_timestamp();
+                    let timestamp = self.now_millis()?;
+                    if timestamp >= last_timestamp {
+                        continue;
+                    }
+
+                    let delta = last_timestamp - timestamp;
+                    if let Some(metrics) = &self.metrics {
+                        metrics.on_clock_backwards(delta);
+                    }
+
+                    if self.is_restored_ahead(last_timestamp) {
+                        // Restored ahead of the clock, wait until the clock catches up.
+                        Self::timed(waited, || TimeUnit::Milliseconds.sleep(delta));
+                        continue;
+                    }
+
+                    if let Some(hook) = &self.on_clock_backwards {
+                        match hook(delta) {
+                            ClockBackwardsDecision::WaitThenRetry => {
+                                Self::timed(waited, || TimeUnit::Milliseconds.sleep(delta));
+                                continue;
+                            }
+                            ClockBackwardsDecision::Fail => {
+                                return Err(SnowflakeError::ClockMovedBackwards);
+                            }
+                            ClockBackwardsDecision::ProceedWithLastTimestamp => {
+                                match self.generate_with_timestamp_for(last_timestamp, shard) {
+                                    // A sibling clone advanced the shared state in the meantime.
+                                    Err(SnowflakeError::ClockMovedBackwards) => continue,
+                                    rvt => return rvt,
+                                }
+                            }
+                        }
+                    }
+
+                    if delta > self.max_backward_tolerance_millis {
+                        return Err(SnowflakeError::ClockMovedBackwards);
+                    }
+
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("clock_backwards", true);
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(delta, "clock moved backwards, waiting");
+
+                    if Self::timed(waited, || self.wait_backwards(last_timestamp, delta))?
+                        < last_timestamp
+                    {
+                        return Err(SnowflakeError::ClockMovedBackwards);
+                    }
+                }
+                Err(SnowflakeError::SequenceExhausted) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.on_sequence_rollover(timestamp);
+                    }
+
+                    if self.overflow_policy == OverflowPolicy::Error {
+                        return Err(SnowflakeError::SequenceExhausted);
+                    }
+
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("rollover", true);
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(timestamp, "sequence rollover, waiting for the next millis");
+
+                    Self::timed(waited, || self.til_next_millis_by_clock(timestamp))?;
+                }
+                rvt => return rvt,
+            }
+        }
+    }
+
+    /// Runs the `wait`, adds the time it took to `waited`.
+    #[cfg(feature = "std")]
+    fn timed<T>(waited: &mut Duration, wait: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let rvt = wait();
+        *waited += started.elapsed();
+
+        rvt
+    }
+
+    /// Polls the [`Clock`] of this generator past `last_timestamp` until the `deadline`,
+    /// returns `false` if the deadline passed first.
+    #[cfg(feature = "std")]
+    fn wait_next_millis_until(
+        &self,
+        last_timestamp: u64,
+        deadline: Instant,
+    ) -> Result<bool, SnowflakeError> {
+        while self.now_millis()? <= last_timestamp {
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            self.spin_strategy.pause();
+        }
+
+        Ok(true)
+    }
+
+    /// Get next timestamp by the [`Clock`] of this generator,
+    /// pausing between polls with the [`SpinStrategy`] of this generator.
+    #[cfg(feature = "std")]
+    fn til_next_millis_by_clock(&self, last_timestamp: u64) -> Result<u64, SnowflakeError> {
+        let mut next = self.now_millis()?;
+        while next <= last_timestamp {
+            self.spin_strategy.pause();
+            next = self.now_millis()?;
+        }
+
+        Ok(next)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Generator for SnowflakeGenerator {
+    /// Generates and returns a unique ID based on the
+    /// current timestamp, `data-center` ID, `worker` ID, and an incrementing sequence number.
+    /// It ensures that IDs are strictly increasing and handles potential clock drift or time going backwards.
+    ///
+    /// ## Return
+    ///
+    /// Returns a `Result<u64, SnowflakeError>` where:
+    ///
+    /// - `Ok(u64)`: Represents a successfully generated unique ID.
+    /// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::new(31, 31);
     /// let rvt = gen.unwrap().next_id();
     /// assert!(rvt.is_ok());
     /// ```
     ///
-    /// # Version
+    /// By features = [`"tracing"`], the generation runs in a `next_id` debug span recording
+    /// the `id`, whether a `rollover` or `clock_backwards` happened, and the `waited_micros`
+    /// actually spent waiting on the rollover or the clock moved backwards,
+    /// compiled out otherwise. (@since 0.4.0)
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        #[cfg(feature = "tracing")]
+        return self.next_id_traced();
+
+        #[cfg(not(feature = "tracing"))]
+        self.generate().map(|generated| generated.id)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Timing for SnowflakeGenerator {
+    /// Get current timestamp, see [`now_millis`].
+    fn time_gen() -> Result<u64, SnowflakeError> {
+        now_millis()
+    }
+
+    /// Get next timestamp, see [`wait_next_millis`].
+    fn til_next_millis(last_timestamp: u64) -> Result<u64, SnowflakeError> {
+        wait_next_millis(last_timestamp)
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`SnowflakeGeneratorLocal`] A single-owner snowflake generator without atomics,
+/// for a generator created per request/task and never shared.
+///
+/// The state lives in a plain [`Cell`] instead of the shared `Arc<AtomicU64>` of a
+/// [`SnowflakeGenerator`], so it is `!Sync` and can not be cloned: the IDs of two
+/// instances of the same node collide, keep one instance per node and thread.
+///
+/// The IDs are packed by the [`BitLayout`] since the epoch like a [`SnowflakeGenerator`] of
+/// the defaults: the generation waits for the next millisecond on the sequence rollover,
+/// and sleeps through the clock moved backwards within [`Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS`],
+/// beyond it fails with [`SnowflakeError::ClockMovedBackwards`]. The [`TimePrecision`] is always millis.
+///
+/// ```rust,compile_fail
+/// use snowflaker::generator::SnowflakeGeneratorLocal;
+///
+/// fn shared<T: Sync>(_: T) {}
+/// shared(SnowflakeGeneratorLocal::new(3, 7).unwrap());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SnowflakeGeneratorLocal<C = SystemClock> {
+    center_id: u64,
+    worker_id: u64,
+    epoch: u64,
+    layout: BitLayout,
+    /// `(last_timestamp, sequence)`
+    state: Cell<(u64, u64)>,
+    clock: C,
+}
+
+#[cfg(feature = "std")]
+impl SnowflakeGeneratorLocal<SystemClock> {
+    /// Constructs a new [`SnowflakeGeneratorLocal`] instance with the given `data-center` ID
+    /// and `worker` ID, validated like [`SnowflakeGenerator::new`].
     ///
-    /// This function was introduced in version `0.2.0` of the crate.
+    /// # Examples
     ///
-    /// # Notes
+    /// ```rust
+    /// use snowflaker::generator::{Generator, SnowflakeGeneratorLocal};
     ///
-    /// This function retrieves the `data-center` ID and `worker` ID dynamically from the network interface(`non-loopback `).
+    /// let gen = SnowflakeGeneratorLocal::new(3, 7).unwrap();
+    /// assert!(gen.next_id().unwrap() < gen.next_id().unwrap());
     ///
-    /// @since 0.2.0
-    #[cfg(feature = "dynamic")]
-    pub fn dynamic() -> Result<Self, SnowflakeError> {
-        let center_id = infras::try_get_data_center_id();
-        let worker_id = infras::try_get_worker_id(center_id);
+    /// assert!(SnowflakeGeneratorLocal::new(32, 7).is_err());
+    /// ```
+    pub fn new(center_id: u64, worker_id: u64) -> Result<Self, SnowflakeError> {
+        SnowflakeGeneratorLocal::with_clock(center_id, worker_id, SystemClock)
+    }
+}
 
-        SnowflakeGenerator::new(center_id, worker_id)
+#[cfg(feature = "std")]
+impl<C: Clock> SnowflakeGeneratorLocal<C> {
+    /// Constructs a new [`SnowflakeGeneratorLocal`] instance reading the given [`Clock`],
+    /// with the default [`BitLayout`] and [`Constants::EPOCH`].
+    pub fn with_clock(center_id: u64, worker_id: u64, clock: C) -> Result<Self, SnowflakeError> {
+        SnowflakeGeneratorLocal::with_layout(
+            center_id,
+            worker_id,
+            BitLayout::default(),
+            Constants::EPOCH,
+            clock,
+        )
     }
 
-    /// Constructs a new [`SnowflakeGenerator`] instance.
-    ///
-    /// # Arguments
-    ///
-    /// - `center_id`: An identifier for the `data-center`, represented as a `u64`.
-    /// It must be within the defined maximum limit.
-    /// - `worker_id`: An identifier for the `worker` node within the `data-center`,
-    /// also represented as a `u64`. This too must not exceed its predefined maximum value.
+    /// Constructs a new [`SnowflakeGeneratorLocal`] instance of the given [`BitLayout`] and epoch
+    /// in unix-millis, reading the given [`Clock`].
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// - `Ok(Self)`: If both `center_id` and `worker_id` are valid, returns a new [`SnowflakeGenerator`] instance.
-    /// - `Err(SnowflakeError)`: If either `center_id` or `worker_id` is invalid, returns an error.
+    /// [`SnowflakeError::CenterIdInvalid`]/[`SnowflakeError::WorkerIdInvalid`] if the IDs beyond the layout,
+    /// the clock is not read: an epoch in the future fails the generation with [`SnowflakeError::ClockBeforeEpoch`].
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use snowflaker::generator::SnowflakeGenerator;
-    ///
-    /// let gen = SnowflakeGenerator::new(31, 31);
-    /// assert!(gen.is_ok());
+    /// use snowflaker::clock::SystemClock;
+    /// use snowflaker::generator::{BitLayout, Constants, Generator, SnowflakeGeneratorLocal};
     ///
-    /// let gen = SnowflakeGenerator::new(32, 32);
-    /// assert!(gen.is_err());
+    /// let layout = BitLayout::new(0, 10, 12).unwrap();
+    /// let gen = SnowflakeGeneratorLocal::with_layout(0, 1023, layout, Constants::TWITTER_EPOCH, SystemClock);
+    /// assert_eq!(1023, layout.decompose(gen.unwrap().next_id().unwrap()).worker_id);
     /// ```
-    pub fn new(center_id: u64, worker_id: u64) -> Result<Self, SnowflakeError> {
-        if center_id > Constants::MAX_DATA_CENTER_ID {
+    pub fn with_layout(
+        center_id: u64,
+        worker_id: u64,
+        layout: BitLayout,
+        epoch: u64,
+        clock: C,
+    ) -> Result<Self, SnowflakeError> {
+        if center_id > layout.max_data_center_id() {
             return Err(SnowflakeError::CenterIdInvalid);
         }
-
-        if center_id > Constants::MAX_WORKER_ID {
+        if worker_id > layout.max_worker_id() {
             return Err(SnowflakeError::WorkerIdInvalid);
         }
 
-        Ok(SnowflakeGenerator {
+        Ok(Self {
             center_id,
             worker_id,
-            sequence: Arc::new(AtomicU64::new(0)),
-            last_timestamp: Arc::new(AtomicU64::new(0)),
+            epoch,
+            layout,
+            state: Cell::new((0, 0)),
+            clock,
         })
     }
+
+    /// Returns the `data-center` ID.
+    pub fn center_id(&self) -> u64 {
+        self.center_id
+    }
+
+    /// Returns the `worker` ID.
+    pub fn worker_id(&self) -> u64 {
+        self.worker_id
+    }
+
+    /// Reads the [`Clock`], sleeps through it moved backwards within the default tolerance.
+    fn now_millis(&self, last_timestamp: u64) -> Result<u64, SnowflakeError> {
+        let timestamp = self.clock.now_millis()?;
+        if timestamp >= last_timestamp {
+            return Ok(timestamp);
+        }
+
+        let delta = last_timestamp - timestamp;
+        if delta > Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS {
+            return Err(SnowflakeError::ClockMovedBackwards);
+        }
+
+        TimeUnit::Milliseconds.sleep(delta << 1);
+        let timestamp = self.clock.now_millis()?;
+        if timestamp < last_timestamp {
+            return Err(SnowflakeError::ClockMovedBackwards);
+        }
+
+        Ok(timestamp)
+    }
 }
 
-impl Generator for SnowflakeGenerator {
-    /// Generates and returns a unique ID based on the
-    /// current timestamp, `data-center` ID, `worker` ID, and an incrementing sequence number.
-    /// It ensures that IDs are strictly increasing and handles potential clock drift or time going backwards.
+#[cfg(feature = "std")]
+impl<C: Clock> Generator for SnowflakeGeneratorLocal<C> {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        let (last_timestamp, last_sequence) = self.state.get();
+        let mut timestamp = self.now_millis(last_timestamp)?;
+
+        let mut sequence = 0;
+        if timestamp == last_timestamp {
+            sequence = (last_sequence + 1) & self.layout.sequence_mask();
+            if sequence == 0 {
+                while timestamp <= last_timestamp {
+                    hint::spin_loop();
+                    timestamp = self.now_millis(last_timestamp)?;
+                }
+            }
+        }
+
+        let ticks = timestamp
+            .checked_sub(self.epoch)
+            .ok_or(SnowflakeError::ClockBeforeEpoch)?;
+        if ticks > self.layout.max_timestamp() {
+            return Err(SnowflakeError::TimestampOverflow);
+        }
+        self.state.set((timestamp, sequence));
+
+        Ok(self
+            .layout
+            .compose(ticks, self.center_id, self.worker_id, sequence))
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`SnowflakeConfig`] The persistable configuration of a [`SnowflakeGenerator`].
+///
+/// The runtime state (`sequence` & `last_timestamp`) is not part of the configuration,
+/// which resets to zero on [`SnowflakeGenerator::from_config`].
+///
+/// `Serialize`/`Deserialize` are derived by features = [`"serde"`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SnowflakeConfig {
+    /// The `data-center` ID.
+    pub center_id: u64,
+    /// The `worker` ID.
+    pub worker_id: u64,
+    /// The custom epoch in unix-millis.
+    pub epoch: u64,
+    /// The bit layout, defaults to the `5/5/12` layout of [`Constants`] if missing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub layout: BitLayout,
+    /// The unit of the timestamp bits, defaults to [`TimePrecision::Millis`] if missing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub precision: TimePrecision,
+}
+
+/// [`GeneratorState`] The runtime state of a [`SnowflakeGenerator`],
+/// see [`SnowflakeGenerator::snapshot`] and [`SnowflakeGenerator::restore`].
+///
+/// `Serialize`/`Deserialize` are derived by features = [`"serde"`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GeneratorState {
+    /// The timestamp in unix-millis of the last generated ID.
+    pub last_timestamp: u64,
+    /// The sequence of the last generated ID.
+    pub sequence: u64,
+}
+
+/// [`DatacenterId`] A validated `data-center` ID, see [`SnowflakeGenerator::from_ids`].
+///
+/// A distinct type from [`WorkerId`], swapping the arguments does not compile:
+///
+/// ```rust,compile_fail
+/// use snowflaker::generator::{DatacenterId, SnowflakeGenerator, WorkerId};
+///
+/// let gen = SnowflakeGenerator::from_ids(WorkerId::new(7).unwrap(), DatacenterId::new(3).unwrap());
+/// ```
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DatacenterId(u64);
+
+/// [`WorkerId`] A validated `worker` ID, see [`SnowflakeGenerator::from_ids`].
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WorkerId(u64);
+
+impl DatacenterId {
+    /// Constructs a new [`DatacenterId`].
     ///
-    /// ## Return
+    /// # Returns
     ///
-    /// Returns a `Result<u64, SnowflakeError>` where:
+    /// - `Ok(Self)`: The `center_id` is within [`Constants::MAX_DATA_CENTER_ID`].
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::CenterIdInvalid`] otherwise.
     ///
-    /// - `Ok(u64)`: Represents a successfully generated unique ID.
-    /// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::DatacenterId;
+    ///
+    /// assert!(DatacenterId::new(31).is_ok());
+    /// assert!(DatacenterId::new(32).is_err());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn new(center_id: u64) -> Result<Self, SnowflakeError> {
+        if center_id > Constants::MAX_DATA_CENTER_ID {
+            return Err(SnowflakeError::CenterIdInvalid);
+        }
+
+        Ok(Self(center_id))
+    }
+
+    /// @since 0.4.0
+    pub const fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl WorkerId {
+    /// Constructs a new [`WorkerId`].
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Self)`: The `worker_id` is within [`Constants::MAX_WORKER_ID`].
+    /// - `Err(SnowflakeError)`: [`SnowflakeError::WorkerIdInvalid`] otherwise.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    /// use snowflaker::generator::WorkerId;
     ///
-    /// let gen = SnowflakeGenerator::new(31, 31);
-    /// let rvt = gen.unwrap().next_id();
-    /// assert!(rvt.is_ok());
+    /// assert!(WorkerId::new(31).is_ok());
+    /// assert!(WorkerId::new(32).is_err());
     /// ```
-    fn next_id(&self) -> Result<u64, SnowflakeError> {
-        let mut timestamp = Self::time_gen().unwrap();
-        let last_timestamp = self.get_last_timestamp();
+    ///
+    /// @since 0.4.0
+    pub fn new(worker_id: u64) -> Result<Self, SnowflakeError> {
+        if worker_id > Constants::MAX_WORKER_ID {
+            return Err(SnowflakeError::WorkerIdInvalid);
+        }
 
-        if timestamp < last_timestamp {
-            let delta = last_timestamp - timestamp;
-            if delta <= 1 << 3 {
-                TimeUnit::Milliseconds.sleep(delta << 1);
-                timestamp = Self::time_gen().unwrap();
+        Ok(Self(worker_id))
+    }
+
+    /// @since 0.4.0
+    pub const fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for SnowflakeConfig {
+    fn default() -> Self {
+        Self {
+            center_id: Constants::DEFAULT_DATA_CENTER_ID,
+            worker_id: Constants::DEFAULT_WORKER_ID,
+            epoch: Constants::EPOCH,
+            layout: BitLayout::default(),
+            precision: TimePrecision::Millis,
+        }
+    }
+}
+
+/// Formats the [`SnowflakeConfig`] as `dc=3,worker=9,epoch=1700000000000`,
+/// which parses back with [`FromStr`].
+///
+/// A non-default layout appends `layout=D/W/S` of the data-center, worker and sequence bits,
+/// and the non-default `tag_bits`, `id_bits` and `order` of it, a non-default precision appends `precision`.
+///
+/// @since 0.4.0
+impl Display for SnowflakeConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dc={},worker={},epoch={}",
+            self.center_id, self.worker_id, self.epoch
+        )?;
+
+        let layout = self.layout;
+        let bits = (
+            layout.datacenter_bits(),
+            layout.worker_id_bits(),
+            layout.sequence_bits(),
+        );
+        if bits
+            != (
+                Constants::DATA_CENTER_ID_BITS,
+                Constants::WORKER_ID_BITS,
+                Constants::SEQUENCE_BITS,
+            )
+        {
+            write!(f, ",layout={}/{}/{}", bits.0, bits.1, bits.2)?;
+        }
+        if layout.tag_bits() != 0 {
+            write!(f, ",tag_bits={}", layout.tag_bits())?;
+        }
+        if layout.id_bits() != Constants::ID_BITS {
+            write!(f, ",id_bits={}", layout.id_bits())?;
+        }
+        if layout.packing_order() == PackingOrder::SequenceHigh {
+            write!(f, ",order=sequence-high")?;
+        }
+        if self.precision == TimePrecision::Seconds {
+            write!(f, ",precision=seconds")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the comma-separated `key=value` config string, e.g. `dc=3,worker=9,epoch=1700000000000`.
+///
+/// The keys are `dc` (or `center`), `worker`, `epoch`, `layout` (`D/W/S` bits), `tag_bits`, `id_bits`,
+/// `order` (`timestamp-high` or `sequence-high`) and `precision` (`millis` or `seconds`),
+/// the missing ones default to [`SnowflakeConfig::default`]. The IDs are validated against the layout.
+///
+/// # Errors
+///
+/// - [`SnowflakeError::ConfigInvalid`]: An entry is not `key=value`, the key is unknown
+///   or duplicated, or the value is malformed.
+/// - [`SnowflakeError::LayoutInvalid`]: The layout overflows, see [`BitLayout::validate`].
+/// - [`SnowflakeError::CenterIdInvalid`]: The `data-center` ID out of range.
+/// - [`SnowflakeError::WorkerIdInvalid`]: The `worker` ID out of range.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{SnowflakeConfig, TimePrecision};
+///
+/// let config: SnowflakeConfig = "dc=3,worker=9,epoch=1700000000000".parse().unwrap();
+/// assert_eq!(3, config.center_id);
+/// assert_eq!(9, config.worker_id);
+/// assert_eq!(1700000000000, config.epoch);
+///
+/// let config: SnowflakeConfig = "dc=0,worker=1000,layout=0/10/12,precision=seconds".parse().unwrap();
+/// assert_eq!(1023, config.layout.max_worker_id());
+/// assert_eq!(TimePrecision::Seconds, config.precision);
+///
+/// assert!("dc=3,worker=32".parse::<SnowflakeConfig>().is_err());
+/// assert!("dc=3,shard=1".parse::<SnowflakeConfig>().is_err());
+/// ```
+///
+/// @since 0.4.0
+impl FromStr for SnowflakeConfig {
+    type Err = SnowflakeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const KEYS: [&str; 8] = [
+            "dc",
+            "worker",
+            "epoch",
+            "layout",
+            "tag_bits",
+            "id_bits",
+            "order",
+            "precision",
+        ];
 
-                if timestamp < last_timestamp {
-                    return Err(SnowflakeError::ClockMovedBackwards);
+        let mut values: [Option<&str>; 8] = [None; 8];
+        for entry in s.split(',') {
+            let (key, value) = entry.split_once('=').ok_or_else(|| {
+                SnowflakeError::ConfigInvalid(format!(
+                    "expected `key=value`, got {:?}",
+                    entry.trim()
+                ))
+            })?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let canonical = if key == "center" { "dc" } else { key };
+            let slot = match KEYS.iter().position(|k| *k == canonical) {
+                Some(index) => &mut values[index],
+                None => {
+                    return Err(SnowflakeError::ConfigInvalid(format!(
+                        "unknown key `{}`, expected one of `{}`",
+                        key,
+                        KEYS.join("`, `")
+                    )))
                 }
+            };
+            if slot.is_some() {
+                return Err(SnowflakeError::ConfigInvalid(format!(
+                    "duplicate key `{}`",
+                    key
+                )));
             }
+
+            *slot = Some(value);
         }
 
-        let mut sequence = self.increment_sequence();
+        let [center_id, worker_id, epoch, layout, tag_bits, id_bits, order, precision] = values;
 
-        if timestamp == last_timestamp {
-            sequence = (sequence + 1) & Constants::SEQUENCE_MASK;
-            if sequence == 0 {
-                timestamp = Self::til_next_millis(timestamp).unwrap();
+        let mut config = SnowflakeConfig::default();
+        config.center_id = parse_config_u64("dc", center_id)?.unwrap_or(config.center_id);
+        config.worker_id = parse_config_u64("worker", worker_id)?.unwrap_or(config.worker_id);
+        config.epoch = parse_config_u64("epoch", epoch)?.unwrap_or(config.epoch);
+
+        if let Some(value) = layout {
+            let bits = value
+                .split('/')
+                .map(|bits| parse_config_u64("layout", Some(bits.trim())))
+                .collect::<Result<Vec<_>, _>>()?;
+            let [Some(datacenter_bits), Some(worker_id_bits), Some(sequence_bits)] = bits[..]
+            else {
+                return Err(SnowflakeError::ConfigInvalid(format!(
+                    "`layout` expects `D/W/S` bits, got {:?}",
+                    value
+                )));
+            };
+            config.layout = BitLayout::unchecked(datacenter_bits, worker_id_bits, sequence_bits);
+        }
+        if let Some(tag_bits) = parse_config_u64("tag_bits", tag_bits)? {
+            config.layout = config.layout.with_tag_bits(tag_bits);
+        }
+        if let Some(id_bits) = parse_config_u64("id_bits", id_bits)? {
+            config.layout = config.layout.with_id_bits(id_bits);
+        }
+        match order {
+            None | Some("timestamp-high") => {}
+            Some("sequence-high") => {
+                config.layout = config.layout.with_packing_order(PackingOrder::SequenceHigh);
             }
-        } else {
-            sequence &= Constants::SEQUENCE_MASK;
+            Some(value) => {
+                return Err(SnowflakeError::ConfigInvalid(format!(
+                    "`order` expects `timestamp-high` or `sequence-high`, got {:?}",
+                    value
+                )))
+            }
+        }
+        config.precision = match precision {
+            None | Some("millis") => TimePrecision::Millis,
+            Some("seconds") => TimePrecision::Seconds,
+            Some(value) => {
+                return Err(SnowflakeError::ConfigInvalid(format!(
+                    "`precision` expects `millis` or `seconds`, got {:?}",
+                    value
+                )))
+            }
+        };
+
+        config.layout.validate()?;
+        if config.center_id > config.layout.max_data_center_id() {
+            return Err(SnowflakeError::CenterIdInvalid);
+        }
+        if config.worker_id > config.layout.max_worker_id() {
+            return Err(SnowflakeError::WorkerIdInvalid);
         }
 
-        self.set_sequence(sequence);
-        self.set_last_timestamp(timestamp);
+        Ok(config)
+    }
+}
+
+/// Parses the value of the `key` of a config string as an unsigned integer if present.
+fn parse_config_u64(key: &str, value: Option<&str>) -> Result<Option<u64>, SnowflakeError> {
+    value
+        .map(|value| {
+            value.parse::<u64>().map_err(|_| {
+                SnowflakeError::ConfigInvalid(format!(
+                    "`{}` expects an unsigned integer, got {:?}",
+                    key, value
+                ))
+            })
+        })
+        .transpose()
+}
 
-        let id = ((timestamp - Constants::EPOCH) << Constants::TIMESTAMP_SHIFT)
-            | (self.center_id << Constants::CENTER_ID_SHIFT)
-            | (self.worker_id << Constants::WORKER_ID_SHIFT)
-            | sequence;
+/// Parses the config string of [`SnowflakeConfig`] and constructs the generator,
+/// see [`SnowflakeGenerator::from_config`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::SnowflakeGenerator;
+///
+/// let gen: SnowflakeGenerator = "dc=3,worker=9".parse().unwrap();
+/// assert_eq!(9, gen.worker_id());
+/// ```
+///
+/// @since 0.4.0
+impl FromStr for SnowflakeGenerator {
+    type Err = SnowflakeError;
 
-        Ok(id)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SnowflakeGenerator::from_config(s.parse()?)
     }
+}
 
-    /// Get current timestamp
-    fn time_gen() -> Result<u64, SnowflakeError> {
-        match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(now) => Ok(now.as_millis() as u64),
-            Err(_) => Err(SnowflakeError::SystemTimeError),
+impl SnowflakeGenerator {
+    /// Constructs a new [`SnowflakeGenerator`] instance by the given [`SnowflakeConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{SnowflakeConfig, SnowflakeGenerator};
+    ///
+    /// let config = SnowflakeConfig {
+    ///     center_id: 3,
+    ///     worker_id: 7,
+    ///     epoch: 1704067200000,
+    ///     ..SnowflakeConfig::default()
+    /// };
+    /// let gen = SnowflakeGenerator::from_config(config).unwrap();
+    /// assert_eq!(config, gen.to_config());
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn from_config(config: SnowflakeConfig) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator::builder()
+            .center_id(config.center_id)
+            .worker_id(config.worker_id)
+            .epoch(config.epoch)
+            .layout(config.layout)
+            .precision(config.precision)
+            .build()
+    }
+
+    /// Returns the [`SnowflakeConfig`] of this generator.
+    ///
+    /// @since 0.4.0
+    pub fn to_config(&self) -> SnowflakeConfig {
+        SnowflakeConfig {
+            center_id: self.center_id,
+            worker_id: self.worker_id,
+            epoch: self.epoch,
+            layout: self.layout,
+            precision: self.precision,
         }
     }
+}
 
-    /// Get next timestamp
-    fn til_next_millis(last_timestamp: u64) -> Result<u64, SnowflakeError> {
-        let mut next = Self::time_gen().unwrap();
-        while next <= last_timestamp {
-            next = Self::time_gen().unwrap();
+// ----------------------------------------------------------------
+
+/// An endless iterator over the IDs of a borrowed [`SnowflakeGenerator`],
+/// see [`SnowflakeGenerator::iter`].
+///
+/// @since 0.4.0
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct SnowflakeIter<'a> {
+    generator: &'a SnowflakeGenerator,
+}
+
+#[cfg(feature = "std")]
+impl Iterator for SnowflakeIter<'_> {
+    type Item = Result<u64, SnowflakeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generator.next_id())
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// The builder of [`SnowflakeGenerator`].
+///
+/// Unset fields default to the values used by [`SnowflakeGenerator::builtin`].
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use snowflaker::generator::{Generator, SnowflakeGeneratorBuilder};
+///
+/// let gen = SnowflakeGeneratorBuilder::default()
+///     .center_id(3)
+///     .worker_id(7)
+///     .epoch(1704067200000)
+///     .build();
+/// assert!(gen.is_ok());
+/// assert!(gen.unwrap().next_id().is_ok());
+///
+/// let gen = SnowflakeGeneratorBuilder::default().worker_id(32).build();
+/// assert!(gen.is_err());
+/// # }
+/// ```
+///
+/// @since 0.4.0
+#[derive(Clone, Default)]
+pub struct SnowflakeGeneratorBuilder {
+    center_id: Option<u64>,
+    worker_id: Option<u64>,
+    epoch: Option<u64>,
+    layout: Option<BitLayout>,
+    #[cfg(feature = "std")]
+    clock: Option<Arc<dyn Clock + Send + Sync>>,
+    #[cfg(feature = "std")]
+    metrics: Option<Arc<dyn Metrics + Send + Sync>>,
+    #[cfg(feature = "std")]
+    max_backward_tolerance_millis: Option<u64>,
+    #[cfg(feature = "std")]
+    wait_strategy: Option<WaitStrategy>,
+    #[cfg(feature = "std")]
+    spin_strategy: Option<SpinStrategy>,
+    #[cfg(feature = "std")]
+    overflow_policy: Option<OverflowPolicy>,
+    #[cfg(feature = "std")]
+    timestamp_cache: Option<bool>,
+    #[cfg(feature = "std")]
+    max_per_second: Option<u64>,
+    #[cfg(feature = "std")]
+    on_clock_backwards: Option<ClockBackwardsHook>,
+    precision: Option<TimePrecision>,
+    ordering: Option<AtomicOrdering>,
+    initial_sequence: Option<u64>,
+    randomize_sequence: Option<bool>,
+}
+
+impl Debug for SnowflakeGeneratorBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("SnowflakeGeneratorBuilder");
+        debug
+            .field("center_id", &self.center_id)
+            .field("worker_id", &self.worker_id)
+            .field("epoch", &self.epoch)
+            .field("layout", &self.layout)
+            .field("precision", &self.precision)
+            .field("ordering", &self.ordering)
+            .field("initial_sequence", &self.initial_sequence)
+            .field("randomize_sequence", &self.randomize_sequence);
+        #[cfg(feature = "std")]
+        debug
+            .field(
+                "max_backward_tolerance_millis",
+                &self.max_backward_tolerance_millis,
+            )
+            .field("wait_strategy", &self.wait_strategy)
+            .field("spin_strategy", &self.spin_strategy)
+            .field("overflow_policy", &self.overflow_policy)
+            .field("timestamp_cache", &self.timestamp_cache)
+            .field("max_per_second", &self.max_per_second)
+            .field("on_clock_backwards", &self.on_clock_backwards.is_some());
+        debug.finish_non_exhaustive()
+    }
+}
+
+impl SnowflakeGeneratorBuilder {
+    /// Sets the `data-center` ID, defaults to [`Constants::DEFAULT_DATA_CENTER_ID`].
+    pub fn center_id(mut self, center_id: u64) -> Self {
+        self.center_id = Some(center_id);
+        self
+    }
+
+    /// Sets the `worker` ID, defaults to [`Constants::DEFAULT_WORKER_ID`].
+    pub fn worker_id(mut self, worker_id: u64) -> Self {
+        self.worker_id = Some(worker_id);
+        self
+    }
+
+    /// Sets the custom epoch in unix-millis, defaults to [`Constants::EPOCH`].
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    /// Sets the bit layout, defaults to the `5/5/12` layout of [`Constants`].
+    pub fn layout(mut self, layout: BitLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Sets the time source, defaults to [`SystemClock`].
+    #[cfg(feature = "std")]
+    pub fn clock(mut self, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets the observability hooks, see [`Metrics`], none by default.
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics + Send + Sync>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the max clock-backwards delta in millis the generator waits through before returning
+    /// [`SnowflakeError::ClockMovedBackwards`], defaults to [`Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS`].
+    ///
+    /// `0` fails fast whenever the clock moved backwards.
+    #[cfg(feature = "std")]
+    pub fn max_backward_tolerance_millis(mut self, tolerance: u64) -> Self {
+        self.max_backward_tolerance_millis = Some(tolerance);
+        self
+    }
+
+    /// Sets how to wait for the clock moved backwards to catch up, defaults to [`WaitStrategy::Sleep`].
+    #[cfg(feature = "std")]
+    pub fn wait_strategy(mut self, strategy: WaitStrategy) -> Self {
+        self.wait_strategy = Some(strategy);
+        self
+    }
+
+    /// Sets how to poll the clock for the next millisecond when the sequence exhausted,
+    /// defaults to [`SpinStrategy::Yield`].
+    #[cfg(feature = "std")]
+    pub fn spin_strategy(mut self, strategy: SpinStrategy) -> Self {
+        self.spin_strategy = Some(strategy);
+        self
+    }
+
+    /// Sets what to do when the sequence exhausted within the current millisecond,
+    /// defaults to [`OverflowPolicy::WaitNextMillis`].
+    ///
+    /// [`SnowflakeGenerator::next_ids_timeout`] always waits within its time budget.
+    #[cfg(feature = "std")]
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = Some(policy);
+        self
+    }
+
+    /// Enables the timestamp cache, defaults to `false`.
+    ///
+    /// While the sequence of the last timestamp has room, the next ID reuses that timestamp
+    /// instead of reading the [`Clock`], the clock is re-read when the sequence rolls over
+    /// or a millisecond elapsed since the last read, measured by the monotonic [`Instant`].
+    /// Only a timestamp read from the clock is reused, never a supplied one of
+    /// [`SnowflakeGenerator::next_id_with_timestamp`] or [`SnowflakeGenerator::next_id_at`].
+    /// The IDs stay unique and strictly increasing, their timestamps lag behind the clock
+    /// by at most a millisecond.
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn timestamp_cache(mut self, enabled: bool) -> Self {
+        self.timestamp_cache = Some(enabled);
+        self
+    }
+
+    /// Caps the IDs generated per second of the timestamps at `max_per_second`,
+    /// defaults to unlimited.
+    ///
+    /// Beyond the cap the generation returns [`SnowflakeError::RateLimited`] without waiting,
+    /// the next second resets the budget, an earlier second takes from the budget of the current one.
+    /// The budget is shared between the clones.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{now_millis, Generator, SnowflakeError, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builder().max_per_second(1).build().unwrap();
+    /// let now = now_millis().unwrap();
+    /// assert!(gen.next_id_with_timestamp(now).is_ok());
+    ///
+    /// let rvt = gen.next_id_with_timestamp(now);
+    /// assert!(matches!(rvt, Err(SnowflakeError::RateLimited)));
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn max_per_second(mut self, max_per_second: u64) -> Self {
+        self.max_per_second = Some(max_per_second);
+        self
+    }
+
+    /// Sets the hook deciding what to do when the clock moved backwards by the given delta
+    /// in millis, e.g. after querying an external time authority, see [`ClockBackwardsDecision`].
+    ///
+    /// When set, the hook is consulted instead of the [`SnowflakeGeneratorBuilder::max_backward_tolerance_millis`]
+    /// and the [`WaitStrategy`], none by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::{ClockBackwardsDecision, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builder()
+    ///     .on_clock_backwards(|delta| match delta {
+    ///         0..=10 => ClockBackwardsDecision::WaitThenRetry,
+    ///         _ => ClockBackwardsDecision::Fail,
+    ///     })
+    ///     .build();
+    /// assert!(gen.is_ok());
+    /// ```
+    ///
+    /// @since 0.4.0
+    #[cfg(feature = "std")]
+    pub fn on_clock_backwards<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(u64) -> ClockBackwardsDecision + Send + Sync + 'static,
+    {
+        self.on_clock_backwards = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the unit of the timestamp bits, defaults to [`TimePrecision::Millis`].
+    pub fn precision(mut self, precision: TimePrecision) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Sets the memory ordering of the atomic operations, defaults to [`AtomicOrdering::SeqCst`],
+    /// see [`AtomicOrdering`] for the trade-off.
+    pub fn ordering(mut self, ordering: AtomicOrdering) -> Self {
+        self.ordering = Some(ordering);
+        self
+    }
+
+    /// Sets the sequence of the first generated ID, masked to the sequence bits of the layout,
+    /// defaults to `0`.
+    ///
+    /// A restart within the same millisecond as the last ID before it re-emits the same
+    /// sequences from `0`, seeding it, e.g. randomly, makes such a collision unlikely.
+    /// It trades away the sequences below the seed in the first millisecond, the sequence
+    /// of the first millisecond exhausts earlier, the IDs are still strictly increasing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builder()
+    ///     .initial_sequence(100)
+    ///     .build()
+    ///     .unwrap();
+    /// let id = gen.next_id().unwrap();
+    /// assert_eq!(100, SnowflakeGenerator::decompose(id).sequence);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn initial_sequence(mut self, sequence: u64) -> Self {
+        self.initial_sequence = Some(sequence);
+        self
+    }
+
+    /// Starts the sequence of each millisecond at a pseudo-random offset instead of `0`,
+    /// defaults to `false`, e.g. for the storage engines hashing on the low bits.
+    ///
+    /// The offset is derived from the tick and the node, within the lower half of the sequence
+    /// space, so at least half of the sequences remain in each millisecond. The IDs within
+    /// a millisecond are still strictly increasing from the offset, only the low bits across
+    /// the milliseconds are spread.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use snowflaker::generator::{Generator, SnowflakeGenerator};
+    ///
+    /// let gen = SnowflakeGenerator::builder()
+    ///     .randomize_sequence(true)
+    ///     .build()
+    ///     .unwrap();
+    /// let first = gen.next_id().unwrap();
+    /// assert!(gen.next_id().unwrap() > first);
+    /// # }
+    /// ```
+    ///
+    /// @since 0.4.0
+    pub fn randomize_sequence(mut self, enabled: bool) -> Self {
+        self.randomize_sequence = Some(enabled);
+        self
+    }
+
+    /// Validates the configured fields and builds the [`SnowflakeGenerator`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::CenterIdInvalid`]: The `data-center` ID out of range.
+    /// - [`SnowflakeError::WorkerIdInvalid`]: The `worker` ID out of range.
+    /// - [`SnowflakeError::EpochInvalid`]: The custom epoch is in the future,
+    ///   which is not validated without the `std` feature.
+    /// - [`SnowflakeError::LayoutInvalid`]: The bit layout overflows.
+    pub fn build(self) -> Result<SnowflakeGenerator, SnowflakeError> {
+        let center_id = self.center_id.unwrap_or(Constants::DEFAULT_DATA_CENTER_ID);
+        let worker_id = self.worker_id.unwrap_or(Constants::DEFAULT_WORKER_ID);
+        let layout = self.layout.unwrap_or_default();
+
+        layout.validate()?;
+
+        if center_id > layout.max_data_center_id() {
+            return Err(SnowflakeError::CenterIdInvalid);
         }
 
-        Ok(next)
+        if worker_id > layout.max_worker_id() {
+            return Err(SnowflakeError::WorkerIdInvalid);
+        }
+
+        #[cfg(feature = "std")]
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+
+        // Without the `std` feature there is no clock to validate the epoch against.
+        let epoch = match self.epoch {
+            #[cfg(feature = "std")]
+            Some(epoch) if epoch > clock.now_millis()? => {
+                return Err(SnowflakeError::EpochInvalid);
+            }
+            Some(epoch) => epoch,
+            None => Constants::EPOCH,
+        };
+
+        Ok(SnowflakeGenerator {
+            center_id,
+            worker_id,
+            epoch,
+            layout,
+            state: Arc::new(AtomicU64::new(SnowflakeGenerator::pack_state(
+                0,
+                self.initial_sequence.unwrap_or(0) & layout.sequence_mask(),
+            ))),
+            #[cfg(feature = "std")]
+            clock,
+            #[cfg(feature = "std")]
+            metrics: self.metrics,
+            #[cfg(feature = "std")]
+            max_backward_tolerance_millis: self
+                .max_backward_tolerance_millis
+                .unwrap_or(Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS),
+            #[cfg(feature = "std")]
+            wait_strategy: self.wait_strategy.unwrap_or_default(),
+            #[cfg(feature = "std")]
+            spin_strategy: self.spin_strategy.unwrap_or_default(),
+            #[cfg(feature = "std")]
+            overflow_policy: self.overflow_policy.unwrap_or_default(),
+            #[cfg(feature = "std")]
+            timestamp_cache: self
+                .timestamp_cache
+                .unwrap_or_default()
+                .then(|| Arc::new(TimestampCache::new())),
+            #[cfg(feature = "std")]
+            rate_limiter: self
+                .max_per_second
+                .map(|max_per_second| Arc::new(RateLimiter::new(max_per_second))),
+            #[cfg(feature = "std")]
+            on_clock_backwards: self.on_clock_backwards,
+            #[cfg(feature = "std")]
+            restored_timestamp: Arc::new(AtomicU64::new(0)),
+            peak_sequence: Arc::new(AtomicU64::new(0)),
+            precision: self.precision.unwrap_or_default(),
+            ordering: self.ordering.unwrap_or_default(),
+            randomize_sequence: self.randomize_sequence.unwrap_or_default(),
+        })
     }
 }
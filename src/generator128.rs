@@ -0,0 +1,536 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! A 128-bit snowflake generator with a wider timestamp, full `16`-bit
+//! `data-center`/`worker` fields, a larger sequence and random bits.
+//!
+//! `| timestamp: 48 bits | data-center: 16 bits | worker: 16 bits | sequence: 16 bits | random: 32 bits |`
+
+// ----------------------------------------------------------------
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+#[cfg(feature = "std")]
+use core::{hint, time::Duration};
+#[cfg(feature = "std")]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use portable_atomic::{AtomicU64, Ordering};
+#[cfg(not(target_has_atomic = "ptr"))]
+use portable_atomic_util::Arc;
+
+#[cfg(feature = "std")]
+use crate::clock::{Clock, SystemClock};
+use crate::generator::{mask, max_id, Constants, SnowflakeError};
+
+// ----------------------------------------------------------------
+
+/// `GOLDEN_GAMMA` the increment of the `SplitMix64` random sequence
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// `DEFAULT_SEED` the seed without a time source, mixed with the node
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+// ----------------------------------------------------------------
+
+/// [`Constants128`] The bit layout of a 128-bit ID.
+///
+/// @since 0.4.0
+pub struct Constants128;
+
+impl Constants128 {
+    /// `TIMESTAMP_BITS` timestamp bits: 48
+    pub const TIMESTAMP_BITS: u64 = 48;
+    /// `DATA_CENTER_ID_BITS` data-center bits: 16
+    pub const DATA_CENTER_ID_BITS: u64 = 16;
+    /// `WORKER_ID_BITS` worker bits: 16
+    pub const WORKER_ID_BITS: u64 = 16;
+    /// `SEQUENCE_BITS` sequence bits: 16
+    pub const SEQUENCE_BITS: u64 = 16;
+    /// `RANDOM_BITS` random bits: 32
+    pub const RANDOM_BITS: u64 = 32;
+
+    /// `MAX_TIMESTAMP` max timestamp: 2^48 - 1
+    pub const MAX_TIMESTAMP: u64 = max_id(Constants128::TIMESTAMP_BITS);
+    /// `MAX_DATA_CENTER_ID` max data-center ID: 65535
+    pub const MAX_DATA_CENTER_ID: u64 = max_id(Constants128::DATA_CENTER_ID_BITS);
+    /// `MAX_WORKER_ID` max worker ID: 65535
+    pub const MAX_WORKER_ID: u64 = max_id(Constants128::WORKER_ID_BITS);
+    /// `SEQUENCE_MASK` sequence mask: 65535
+    pub const SEQUENCE_MASK: u64 = mask(Constants128::SEQUENCE_BITS);
+    /// `RANDOM_MASK` random mask: 2^32 - 1
+    pub const RANDOM_MASK: u64 = mask(Constants128::RANDOM_BITS);
+
+    /// `SEQUENCE_SHIFT` sequence shift: 32
+    pub const SEQUENCE_SHIFT: u64 = Constants128::RANDOM_BITS;
+    /// `WORKER_ID_SHIFT` worker ID shift: 48
+    pub const WORKER_ID_SHIFT: u64 = Constants128::SEQUENCE_SHIFT + Constants128::SEQUENCE_BITS;
+    /// `CENTER_ID_SHIFT` center ID shift: 64
+    pub const CENTER_ID_SHIFT: u64 = Constants128::WORKER_ID_SHIFT + Constants128::WORKER_ID_BITS;
+    /// `TIMESTAMP_SHIFT` timestamp left shift: 80
+    pub const TIMESTAMP_SHIFT: u64 =
+        Constants128::CENTER_ID_SHIFT + Constants128::DATA_CENTER_ID_BITS;
+}
+
+// ----------------------------------------------------------------
+
+/// [`IdParts128`] The component fields packed into a 128-bit ID.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdParts128 {
+    /// The raw timestamp bits, relative to the epoch of the generator.
+    pub timestamp: u64,
+    /// The `data-center` ID.
+    pub center_id: u64,
+    /// The `worker` ID.
+    pub worker_id: u64,
+    /// The sequence within the millisecond.
+    pub sequence: u64,
+    /// The random bits, zero if disabled.
+    pub random: u64,
+}
+
+impl IdParts128 {
+    /// Returns the absolute unix-millis, adding [`Constants::EPOCH`] back to the raw timestamp,
+    /// assuming the generator uses the default epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::Constants;
+    /// use snowflaker::generator128::{Constants128, SnowflakeGenerator128};
+    ///
+    /// let parts = SnowflakeGenerator128::decompose(1 << Constants128::TIMESTAMP_SHIFT);
+    /// assert_eq!(Constants::EPOCH + 1, parts.unix_millis());
+    /// ```
+    pub const fn unix_millis(&self) -> u64 {
+        Constants::EPOCH + self.timestamp
+    }
+
+    /// Packs the parts into a 128-bit ID, the fields are masked to their bits.
+    pub const fn compose(&self) -> u128 {
+        ((self.timestamp & Constants128::MAX_TIMESTAMP) as u128) << Constants128::TIMESTAMP_SHIFT
+            | ((self.center_id & Constants128::MAX_DATA_CENTER_ID) as u128)
+                << Constants128::CENTER_ID_SHIFT
+            | ((self.worker_id & Constants128::MAX_WORKER_ID) as u128)
+                << Constants128::WORKER_ID_SHIFT
+            | ((self.sequence & Constants128::SEQUENCE_MASK) as u128)
+                << Constants128::SEQUENCE_SHIFT
+            | (self.random & Constants128::RANDOM_MASK) as u128
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`SnowflakeGenerator128`] A 128-bit snowflake ID generator, see [`Constants128`].
+///
+/// The IDs of one generator (and its clones) are strictly increasing, the random bits
+/// are the lowest bits below the sequence to avoid collisions between misconfigured nodes.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use snowflaker::generator128::SnowflakeGenerator128;
+///
+/// let gen = SnowflakeGenerator128::new(1024, 4096).unwrap();
+/// let id = gen.next_id().unwrap();
+///
+/// let parts = SnowflakeGenerator128::decompose(id);
+/// assert_eq!(1024, parts.center_id);
+/// assert_eq!(4096, parts.worker_id);
+/// # }
+/// ```
+///
+/// @since 0.4.0
+#[derive(Clone)]
+pub struct SnowflakeGenerator128 {
+    center_id: u64,
+    worker_id: u64,
+    epoch: u64,
+    random: bool,
+    /// `| last_timestamp: 48 bits | sequence: 16 bits |`
+    state: Arc<AtomicU64>,
+    /// The `SplitMix64` random sequence.
+    rng: Arc<AtomicU64>,
+    #[cfg(feature = "std")]
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+
+impl Debug for SnowflakeGenerator128 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnowflakeGenerator128")
+            .field("center_id", &self.center_id)
+            .field("worker_id", &self.worker_id)
+            .field("epoch", &self.epoch)
+            .field("random", &self.random)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SnowflakeGenerator128 {
+    /// Constructs a new [`SnowflakeGenerator128`] instance with the given `data-center` ID
+    /// and `worker` ID, both within `0..=65535`, and the random bits enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator128::SnowflakeGenerator128;
+    ///
+    /// assert!(SnowflakeGenerator128::new(65535, 65535).is_ok());
+    /// assert!(SnowflakeGenerator128::new(65536, 0).is_err());
+    /// ```
+    pub fn new(center_id: u64, worker_id: u64) -> Result<Self, SnowflakeError> {
+        SnowflakeGenerator128::builder()
+            .center_id(center_id)
+            .worker_id(worker_id)
+            .build()
+    }
+
+    /// Returns a new [`SnowflakeGenerator128Builder`] with the defaults of [`SnowflakeGenerator128::new`].
+    pub fn builder() -> SnowflakeGenerator128Builder {
+        SnowflakeGenerator128Builder::default()
+    }
+
+    /// Returns the `data-center` ID of this generator.
+    pub fn center_id(&self) -> u64 {
+        self.center_id
+    }
+
+    /// Returns the `worker` ID of this generator.
+    pub fn worker_id(&self) -> u64 {
+        self.worker_id
+    }
+
+    /// Returns the epoch in unix-millis of this generator.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Decomposes the given 128-bit ID into its [`IdParts128`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator128::{IdParts128, SnowflakeGenerator128};
+    ///
+    /// let parts = IdParts128 {
+    ///     timestamp: 1,
+    ///     center_id: 2,
+    ///     worker_id: 3,
+    ///     sequence: 4,
+    ///     random: 5,
+    /// };
+    /// assert_eq!(parts, SnowflakeGenerator128::decompose(parts.compose()));
+    /// ```
+    pub const fn decompose(id: u128) -> IdParts128 {
+        IdParts128 {
+            timestamp: (id >> Constants128::TIMESTAMP_SHIFT) as u64 & Constants128::MAX_TIMESTAMP,
+            center_id: (id >> Constants128::CENTER_ID_SHIFT) as u64
+                & Constants128::MAX_DATA_CENTER_ID,
+            worker_id: (id >> Constants128::WORKER_ID_SHIFT) as u64 & Constants128::MAX_WORKER_ID,
+            sequence: (id >> Constants128::SEQUENCE_SHIFT) as u64 & Constants128::SEQUENCE_MASK,
+            random: id as u64 & Constants128::RANDOM_MASK,
+        }
+    }
+
+    /// Generates and returns a unique 128-bit ID by the clock of this generator,
+    /// waiting for the next millisecond if the sequence is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::ClockMovedBackwards`]: the clock moved backwards beyond
+    ///   [`Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS`].
+    /// - [`SnowflakeError::TimestampOverflow`]: the timestamp exceeds the `48` timestamp bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator128::SnowflakeGenerator128;
+    ///
+    /// let gen = SnowflakeGenerator128::new(1, 1).unwrap();
+    /// let first = gen.next_id().unwrap();
+    /// let second = gen.next_id().unwrap();
+    /// assert!(second > first);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn next_id(&self) -> Result<u128, SnowflakeError> {
+        let mut deadline = None;
+        loop {
+            let timestamp = self.clock.now_millis()?;
+
+            match self.next_id_with_timestamp(timestamp) {
+                Err(SnowflakeError::ClockMovedBackwards) => {
+                    let last_timestamp = self.last_timestamp();
+                    if last_timestamp.saturating_sub(timestamp)
+                        > Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS
+                    {
+                        return Err(SnowflakeError::ClockMovedBackwards);
+                    }
+
+                    let deadline = *deadline.get_or_insert_with(|| {
+                        Instant::now()
+                            + Duration::from_millis(
+                                Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS << 1,
+                            )
+                    });
+                    if Instant::now() >= deadline {
+                        return Err(SnowflakeError::ClockMovedBackwards);
+                    }
+                    hint::spin_loop();
+                }
+                Err(SnowflakeError::SequenceExhausted) => {
+                    while self.clock.now_millis()? <= timestamp {
+                        hint::spin_loop();
+                    }
+                }
+                rvt => return rvt,
+            }
+        }
+    }
+
+    /// Generates and returns a unique 128-bit ID at the given unix-millis, never waits.
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::ClockBeforeEpoch`]: the timestamp is before the epoch.
+    /// - [`SnowflakeError::ClockMovedBackwards`]: the timestamp is before the last one.
+    /// - [`SnowflakeError::SequenceExhausted`]: the sequence of the millisecond is exhausted.
+    /// - [`SnowflakeError::TimestampOverflow`]: the timestamp exceeds the `48` timestamp bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use snowflaker::generator::Constants;
+    /// use snowflaker::generator128::SnowflakeGenerator128;
+    ///
+    /// let gen = SnowflakeGenerator128::new(1, 1).unwrap();
+    /// let id = gen.next_id_with_timestamp(Constants::EPOCH + 1).unwrap();
+    /// assert_eq!(1, SnowflakeGenerator128::decompose(id).timestamp);
+    /// ```
+    pub fn next_id_with_timestamp(&self, now_millis: u64) -> Result<u128, SnowflakeError> {
+        if now_millis < self.epoch {
+            return Err(SnowflakeError::ClockBeforeEpoch);
+        }
+
+        let ticks = now_millis - self.epoch;
+        if ticks > Constants128::MAX_TIMESTAMP {
+            return Err(SnowflakeError::TimestampOverflow);
+        }
+
+        loop {
+            let state = self.state.load(Ordering::SeqCst);
+            let (last_timestamp, last_sequence) = unpack_state(state);
+
+            if now_millis < last_timestamp {
+                return Err(SnowflakeError::ClockMovedBackwards);
+            }
+
+            let mut sequence = 0;
+            if now_millis == last_timestamp {
+                sequence = (last_sequence + 1) & Constants128::SEQUENCE_MASK;
+                if sequence == 0 {
+                    return Err(SnowflakeError::SequenceExhausted);
+                }
+            }
+
+            if self
+                .state
+                .compare_exchange_weak(
+                    state,
+                    pack_state(now_millis, sequence),
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            let parts = IdParts128 {
+                timestamp: ticks,
+                center_id: self.center_id,
+                worker_id: self.worker_id,
+                sequence,
+                random: if self.random { self.next_random() } else { 0 },
+            };
+
+            return Ok(parts.compose());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn last_timestamp(&self) -> u64 {
+        unpack_state(self.state.load(Ordering::SeqCst)).0
+    }
+
+    fn next_random(&self) -> u64 {
+        splitmix64(self.rng.fetch_add(GOLDEN_GAMMA, Ordering::Relaxed)) & Constants128::RANDOM_MASK
+    }
+}
+
+// ----------------------------------------------------------------
+
+fn pack_state(last_timestamp: u64, sequence: u64) -> u64 {
+    (last_timestamp << Constants128::SEQUENCE_BITS) | (sequence & Constants128::SEQUENCE_MASK)
+}
+
+fn unpack_state(state: u64) -> (u64, u64) {
+    (
+        state >> Constants128::SEQUENCE_BITS,
+        state & Constants128::SEQUENCE_MASK,
+    )
+}
+
+/// The output function of the `SplitMix64` random sequence for the given state.
+pub(crate) fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn default_seed(center_id: u64, worker_id: u64) -> u64 {
+    let node = (center_id << Constants128::WORKER_ID_BITS) | worker_id;
+
+    #[cfg(feature = "std")]
+    {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_nanos() as u64)
+            .unwrap_or(DEFAULT_SEED);
+        splitmix64(nanos ^ node)
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        splitmix64(DEFAULT_SEED ^ node)
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// [`SnowflakeGenerator128Builder`] The builder of [`SnowflakeGenerator128`].
+///
+/// @since 0.4.0
+#[derive(Clone, Default)]
+pub struct SnowflakeGenerator128Builder {
+    center_id: Option<u64>,
+    worker_id: Option<u64>,
+    epoch: Option<u64>,
+    random: Option<bool>,
+    seed: Option<u64>,
+    #[cfg(feature = "std")]
+    clock: Option<Arc<dyn Clock + Send + Sync>>,
+}
+
+impl Debug for SnowflakeGenerator128Builder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnowflakeGenerator128Builder")
+            .field("center_id", &self.center_id)
+            .field("worker_id", &self.worker_id)
+            .field("epoch", &self.epoch)
+            .field("random", &self.random)
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SnowflakeGenerator128Builder {
+    /// Sets the `data-center` ID, defaults to [`Constants::DEFAULT_DATA_CENTER_ID`].
+    pub fn center_id(mut self, center_id: u64) -> Self {
+        self.center_id = Some(center_id);
+        self
+    }
+
+    /// Sets the `worker` ID, defaults to [`Constants::DEFAULT_WORKER_ID`].
+    pub fn worker_id(mut self, worker_id: u64) -> Self {
+        self.worker_id = Some(worker_id);
+        self
+    }
+
+    /// Sets the custom epoch in unix-millis, defaults to [`Constants::EPOCH`].
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    /// Enables or disables the random bits, enabled by default.
+    pub fn random(mut self, random: bool) -> Self {
+        self.random = Some(random);
+        self
+    }
+
+    /// Sets the seed of the random bits, defaults to the current time mixed with the node.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the time source, defaults to [`SystemClock`].
+    #[cfg(feature = "std")]
+    pub fn clock(mut self, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Builds the [`SnowflakeGenerator128`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SnowflakeError::CenterIdInvalid`]: the `data-center` ID is greater than [`Constants128::MAX_DATA_CENTER_ID`].
+    /// - [`SnowflakeError::WorkerIdInvalid`]: the `worker` ID is greater than [`Constants128::MAX_WORKER_ID`].
+    /// - [`SnowflakeError::EpochInvalid`]: the epoch is in the future of the clock.
+    pub fn build(self) -> Result<SnowflakeGenerator128, SnowflakeError> {
+        let center_id = self.center_id.unwrap_or(Constants::DEFAULT_DATA_CENTER_ID);
+        if center_id > Constants128::MAX_DATA_CENTER_ID {
+            return Err(SnowflakeError::CenterIdInvalid);
+        }
+
+        let worker_id = self.worker_id.unwrap_or(Constants::DEFAULT_WORKER_ID);
+        if worker_id > Constants128::MAX_WORKER_ID {
+            return Err(SnowflakeError::WorkerIdInvalid);
+        }
+
+        let epoch = self.epoch.unwrap_or(Constants::EPOCH);
+        #[cfg(feature = "std")]
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+        #[cfg(feature = "std")]
+        if epoch > clock.now_millis()? {
+            return Err(SnowflakeError::EpochInvalid);
+        }
+
+        let seed = self
+            .seed
+            .unwrap_or_else(|| default_seed(center_id, worker_id));
+
+        Ok(SnowflakeGenerator128 {
+            center_id,
+            worker_id,
+            epoch,
+            random: self.random.unwrap_or(true),
+            state: Arc::new(AtomicU64::new(0)),
+            rng: Arc::new(AtomicU64::new(seed)),
+            #[cfg(feature = "std")]
+            clock,
+        })
+    }
+}
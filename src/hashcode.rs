@@ -16,6 +16,10 @@
 
 // ----------------------------------------------------------------
 
+use alloc::string::String;
+
+// ----------------------------------------------------------------
+
 /// `HASH_BASE` 31
 pub const HASH_BASE: u64 = (1 << 5) - 1;
 
@@ -26,6 +30,9 @@ pub const HASH_BASE: u64 = (1 << 5) - 1;
 /// Implementors of this trait can produce a consistent 64-bit hash-value that
 /// is unique for instances with the same content (ideally).
 ///
+/// The builtin impls wrap around on the `u64` overflow, e.g. for a long hostname,
+/// so the hash-value is well-defined and identical in the debug and release builds.
+///
 /// @since 0.2.0
 pub trait HashCode {
     /// Generates and returns a hash-value based on the contents of `self`.
@@ -34,15 +41,125 @@ pub trait HashCode {
     ///
     /// - `u64`: A 64-bit unsigned integer representing the hash code.
     fn hashcode(&self) -> u64;
+
+    /// Generates and returns a hash-value based on the contents of `self`,
+    /// multiplied by the given `base` instead of [`HASH_BASE`].
+    ///
+    /// Defaults to [`HashCode::hashcode`] for types whose hash-value does not
+    /// depend on a base.
+    ///
+    /// @since 0.4.0
+    fn hashcode_with_base(&self, base: u64) -> u64 {
+        let _ = base;
+        self.hashcode()
+    }
+}
+
+/// Computes the Java-style `31 * h + c` hash-code of the given chars.
+///
+/// The `str` and [`String`] [`HashCode`] impls share this logic, so equal contents
+/// produce the same value no matter whether they are borrowed or owned.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::hashcode::{self, HashCode};
+///
+/// let hash = hashcode::hashcode_of("ab".chars().chain("c".chars()));
+/// assert_eq!("abc".hashcode(), hash);
+/// ```
+///
+/// @since 0.4.0
+pub fn hashcode_of<I: IntoIterator<Item = char>>(chars: I) -> u64 {
+    hashcode_of_with_base(chars, HASH_BASE)
+}
+
+/// Computes the `base * h + c` hash-code of the given chars.
+///
+/// The multiplication and the addition wrap around on the `u64` overflow
+/// (`wrapping_mul`/`wrapping_add`), they never panic in the debug builds.
+///
+/// @since 0.4.0
+pub fn hashcode_of_with_base<I: IntoIterator<Item = char>>(chars: I, base: u64) -> u64 {
+    chars.into_iter().fold(0, |hash, ch| {
+        base.wrapping_mul(hash).wrapping_add(ch as u64)
+    })
+}
+
+/// Computes the `base * h + c` hash-code of the given [`str`], e.g. to tune
+/// the distribution of the derived worker IDs across hostnames.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::hashcode::{self, HashCode, HASH_BASE};
+///
+/// assert_eq!("abc".hashcode(), hashcode::hashcode_with("abc", HASH_BASE));
+/// assert_ne!("abc".hashcode(), hashcode::hashcode_with("abc", 131));
+/// ```
+///
+/// @since 0.4.0
+pub fn hashcode_with(s: &str, base: u64) -> u64 {
+    hashcode_of_with_base(s.chars(), base)
+}
+
+/// Implement the [`HashCode`] trait for borrowed types, e.g. `&str` and `&[u8]`.
+///
+/// @since 0.4.0
+impl<T: HashCode + ?Sized> HashCode for &T {
+    fn hashcode(&self) -> u64 {
+        (**self).hashcode()
+    }
+
+    fn hashcode_with_base(&self, base: u64) -> u64 {
+        (**self).hashcode_with_base(base)
+    }
+}
+
+/// Implement the [`HashCode`] trait for the [`str`] type.
+///
+/// @since 0.4.0
+impl HashCode for str {
+    fn hashcode(&self) -> u64 {
+        hashcode_of(self.chars())
+    }
+
+    fn hashcode_with_base(&self, base: u64) -> u64 {
+        hashcode_with(self, base)
+    }
 }
 
 /// Implement the [`HashCode`] trait for the [`String`] type.
 impl HashCode for String {
     fn hashcode(&self) -> u64 {
-        let mut hash: u64 = 0;
-        for ch in self.chars() {
-            hash = HASH_BASE * hash + ch as u64;
-        }
-        hash
+        self.as_str().hashcode()
+    }
+
+    fn hashcode_with_base(&self, base: u64) -> u64 {
+        self.as_str().hashcode_with_base(base)
+    }
+}
+
+/// Implement the [`HashCode`] trait for the `[u8]` type.
+///
+/// @since 0.4.0
+impl HashCode for [u8] {
+    fn hashcode(&self) -> u64 {
+        self.hashcode_with_base(HASH_BASE)
+    }
+
+    fn hashcode_with_base(&self, base: u64) -> u64 {
+        self.iter().fold(0, |hash, byte| {
+            base.wrapping_mul(hash).wrapping_add(*byte as u64)
+        })
+    }
+}
+
+/// Implement the [`HashCode`] trait for the [`u64`] type, the value itself.
+///
+/// @since 0.4.0
+impl HashCode for u64 {
+    fn hashcode(&self) -> u64 {
+        *self
     }
 }
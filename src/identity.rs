@@ -0,0 +1,120 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+use crate::generator::SnowflakeError;
+#[cfg(feature = "dynamic")]
+use crate::infras;
+
+// ----------------------------------------------------------------
+
+/// The identity source of a [`crate::generator::SnowflakeGenerator`],
+/// see [`crate::generator::SnowflakeGenerator::with_identity`].
+///
+/// Implementors derive the `data-center` ID and `worker` ID, e.g. from the MAC address,
+/// a Kubernetes pod ordinal or a coordination service.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{SnowflakeError, SnowflakeGenerator};
+/// use snowflaker::identity::IdentityProvider;
+///
+/// struct PodOrdinal(u64);
+///
+/// impl IdentityProvider for PodOrdinal {
+///     fn datacenter_id(&self) -> Result<u64, SnowflakeError> {
+///         Ok(1)
+///     }
+///
+///     fn worker_id(&self, _datacenter_id: u64) -> Result<u64, SnowflakeError> {
+///         Ok(self.0)
+///     }
+/// }
+///
+/// let gen = SnowflakeGenerator::with_identity(&PodOrdinal(7)).unwrap();
+/// assert_eq!(7, gen.worker_id());
+/// ```
+///
+/// @since 0.4.0
+pub trait IdentityProvider {
+    /// Returns the `data-center` ID.
+    fn datacenter_id(&self) -> Result<u64, SnowflakeError>;
+    /// Returns the `worker` ID within the given `data-center` ID.
+    fn worker_id(&self, datacenter_id: u64) -> Result<u64, SnowflakeError>;
+}
+
+// ----------------------------------------------------------------
+
+/// The [`IdentityProvider`] derives the `data-center` ID from the MAC address of
+/// the network interface(`non-loopback`) and the `worker` ID from the process ID,
+/// see [`infras::try_get_data_center_id`] and [`infras::try_get_worker_id`].
+///
+/// The derived pair is memoized by [`infras::try_get_identity`]. (@since 0.4.0)
+///
+/// @since 0.4.0
+#[cfg(feature = "dynamic")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MacIdentityProvider;
+
+#[cfg(feature = "dynamic")]
+impl IdentityProvider for MacIdentityProvider {
+    fn datacenter_id(&self) -> Result<u64, SnowflakeError> {
+        Ok(infras::try_get_identity().0)
+    }
+
+    fn worker_id(&self, datacenter_id: u64) -> Result<u64, SnowflakeError> {
+        match infras::try_get_identity() {
+            (center_id, worker_id) if center_id == datacenter_id => Ok(worker_id),
+            // e.g. the `data-center` ID was overridden by the env var.
+            _ => Ok(infras::try_get_worker_id(datacenter_id)),
+        }
+    }
+}
+
+/// The [`IdentityProvider`] derives the `data-center` ID from the MAC address like
+/// [`MacIdentityProvider`], and the `worker` ID from the hashcode of the hostname,
+/// restart-stable on bare metal, see [`infras::try_get_hostname_worker_id`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{Constants, SnowflakeGenerator};
+/// use snowflaker::identity::HostnameIdentityProvider;
+///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var("SNOWFLAKE_CACHE_DIR", &dir);
+/// let gen = SnowflakeGenerator::with_identity(&HostnameIdentityProvider).unwrap();
+/// assert!(gen.worker_id() <= Constants::MAX_WORKER_ID);
+/// # let _ = std::fs::remove_dir_all(dir);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "dynamic")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HostnameIdentityProvider;
+
+#[cfg(feature = "dynamic")]
+impl IdentityProvider for HostnameIdentityProvider {
+    fn datacenter_id(&self) -> Result<u64, SnowflakeError> {
+        Ok(infras::try_get_identity().0)
+    }
+
+    fn worker_id(&self, datacenter_id: u64) -> Result<u64, SnowflakeError> {
+        Ok(infras::try_get_hostname_worker_id(datacenter_id))
+    }
+}
@@ -20,18 +20,83 @@
 
 // ----------------------------------------------------------------
 
+#[cfg(test)]
+use std::cell::Cell;
+use std::env::VarError;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fmt;
+use std::fs;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use ifcfg::IfCfg;
 
-use crate::generator::Constants;
+use crate::generator::{Constants, SnowflakeError};
+use crate::hashcode;
 use crate::hashcode::HashCode;
 
 // ----------------------------------------------------------------
 
+/// The env key of the `data-center` ID, overrides the MAC-based derivation.
+///
+/// @since 0.4.0
+pub const SNOWFLAKE_DATACENTER_ID: &str = "SNOWFLAKE_DATACENTER_ID";
+/// The env key of the `worker` ID, overrides the pid-based derivation.
+///
+/// @since 0.4.0
+pub const SNOWFLAKE_WORKER_ID: &str = "SNOWFLAKE_WORKER_ID";
+/// The env key of the cache dir the derived `worker` ID is persisted under,
+/// see [`worker_id_cache_file`].
+///
+/// @since 0.4.0
+pub const SNOWFLAKE_CACHE_DIR: &str = "SNOWFLAKE_CACHE_DIR";
+
+// ----------------------------------------------------------------
+
 const LOOPBACK: &str = "Loopback";
 
+/// The kernel hostname file on Linux.
+///
+/// @since 0.4.0
+const HOSTNAME_FILE: &str = "/proc/sys/kernel/hostname";
+
+/// The env keys of the hostname, `HOSTNAME` on Unix shells and `COMPUTERNAME` on Windows.
+///
+/// @since 0.4.0
+const HOSTNAME_ENV_KEYS: &[&str] = &["HOSTNAME", "COMPUTERNAME"];
+
+/// @since 0.4.0
+const VIRTUAL_INTERFACE_PREFIXES: &[&str] = &[
+    "lo", "docker", "veth", "br-", "virbr", "vmnet", "vboxnet", "tun", "tap", "utun", "wg", "zt",
+    "cni", "flannel", "kube", "cali", "awdl", "llw", "bridge", "ppp",
+];
+
+/// @since 0.4.0
+const VIRTUAL_INTERFACE_KEYWORDS: &[&str] = &["virtual", "vpn", "hyper-v", "vethernet"];
+
+/// The memoized `(data-center ID, worker ID)` pair of [`try_get_identity`].
+///
+/// @since 0.4.0
+static IDENTITY_CACHE: Mutex<Option<(u64, u64)>> = Mutex::new(None);
+
+/// The `worker` ID slot lock files of [`try_get_worker_id`] held by this process, with their paths,
+/// kept open for the lifetime of the process: the OS releases the locks once it exits.
+///
+/// @since 0.4.0
+static WORKER_ID_LOCKS: Mutex<Vec<(PathBuf, File)>> = Mutex::new(Vec::new());
+
+// ----------------------------------------------------------------
+
+#[cfg(test)]
+thread_local! {
+    /// Counts the MAC lookups of the current thread.
+    pub(crate) static MAC_LOOKUPS: Cell<usize> = const { Cell::new(0) };
+}
+
 // ----------------------------------------------------------------
 
 /// Interface errors
@@ -56,28 +121,359 @@ impl Error for InterfaceError {}
 
 // ----------------------------------------------------------------
 
+/// Get the ID from the env var `key` by the given `lookup`, e.g. [`std::env::var`].
+///
+/// Returns `Ok(None)` if the env var is absent, or the `invalid` error if it is present
+/// but not a valid `u64` within the `max`.
+///
+/// @since 0.4.0
+pub(crate) fn try_get_env_id<F>(
+    lookup: F,
+    key: &str,
+    max: u64,
+    invalid: SnowflakeError,
+) -> Result<Option<u64>, SnowflakeError>
+where
+    F: Fn(&str) -> Result<String, VarError>,
+{
+    match lookup(key) {
+        Ok(value) => match value.trim().parse::<u64>() {
+            Ok(id) if id <= max => Ok(Some(id)),
+            _ => Err(invalid),
+        },
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(invalid),
+    }
+}
+
+// ----------------------------------------------------------------
+
 /// Get the `worker` ID by given center ID
 ///
+/// The `worker` ID is derived by a fallback chain, and persisted so it stays stable
+/// across the restarts on the same machine:
+///
+/// 1. The env var [`SNOWFLAKE_WORKER_ID`], if present and valid.
+/// 2. The ID persisted in the cache file, see [`worker_id_cache_file`].
+/// 3. The hashcode of the `center_id` and the pid, as a last resort.
+///
+/// Whichever was chosen is written to the cache file, a failed write is ignored.
+///
+/// The derived (not the env var) ID is then claimed by an OS advisory lock on the file
+/// `<cache file>.<worker ID>.lock`, held for the lifetime of the process, so the concurrent processes
+/// sharing the cache file never share a `worker` ID: a slot locked by another process is skipped
+/// for the next one. The OS releases the lock once the process exits, even on a crash,
+/// so a restart claims the same persisted ID again.
+/// If all the slots are taken, or the lock files can not be opened, the derived ID is returned as is.
+///
 /// # Examples
 ///
 /// ```rust
 /// use snowflaker::generator::Constants;
 /// use snowflaker::infras;
 ///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var(infras::SNOWFLAKE_CACHE_DIR, &dir);
 /// let center_id = infras::try_get_data_center_id();
 /// let worker_id = infras::try_get_worker_id(center_id);
 /// assert!(worker_id <= Constants::MAX_WORKER_ID);
+/// # let _ = std::fs::remove_dir_all(dir);
 /// ```
 /// @since 0.2.0
 pub fn try_get_worker_id(center_id: u64) -> u64 {
-    let mut buf = center_id.to_string();
+    worker_id_with(
+        |key| std::env::var(key),
+        Some(&worker_id_cache_file(center_id)),
+        center_id,
+    )
+}
+
+/// Returns the cache file the `worker` ID of the `center_id` is persisted in:
+/// `<dir>/snowflaker/worker-id-<center_id>`, where the `<dir>` is the first present of
+///
+/// - The env var [`SNOWFLAKE_CACHE_DIR`].
+/// - The env var `XDG_CACHE_HOME`.
+/// - `$HOME/.cache`.
+/// - The temp dir, see [`std::env::temp_dir`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::infras;
+///
+/// let file = infras::worker_id_cache_file(3);
+/// assert!(file.ends_with("snowflaker/worker-id-3"));
+/// ```
+///
+/// @since 0.4.0
+pub fn worker_id_cache_file(center_id: u64) -> PathBuf {
+    worker_id_cache_file_with(cache_dir_var, center_id)
+}
+
+/// Resolves the cache file of [`worker_id_cache_file`], looks up the env vars by the given `lookup`.
+///
+/// @since 0.4.0
+pub(crate) fn worker_id_cache_file_with<F>(lookup: F, center_id: u64) -> PathBuf
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    let dir = lookup(SNOWFLAKE_CACHE_DIR)
+        .or_else(|| lookup("XDG_CACHE_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| lookup("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+
+    dir.join("snowflaker")
+        .join(format!("worker-id-{}", center_id))
+}
+
+#[cfg(not(test))]
+fn cache_dir_var(key: &str) -> Option<OsString> {
+    std::env::var_os(key)
+}
+
+/// The tests never write into the real cache dir, the runs share one temp dir:
+/// their locks are released on exit, so it does not grow per run.
+#[cfg(test)]
+fn cache_dir_var(key: &str) -> Option<OsString> {
+    match key {
+        SNOWFLAKE_CACHE_DIR => Some(std::env::temp_dir().join("snowflaker-test-cache").into()),
+        _ => std::env::var_os(key),
+    }
+}
+
+/// Derives the `worker` ID by the fallback chain of [`try_get_worker_id`],
+/// looks up the env vars by the given `lookup` and persists into the given `cache_file`.
+///
+/// @since 0.4.0
+pub(crate) fn worker_id_with<F>(lookup: F, cache_file: Option<&Path>, center_id: u64) -> u64
+where
+    F: Fn(&str) -> Result<String, VarError>,
+{
+    let env_id = try_get_env_id(
+        lookup,
+        SNOWFLAKE_WORKER_ID,
+        Constants::MAX_WORKER_ID,
+        SnowflakeError::WorkerIdInvalid,
+    )
+    .ok()
+    .flatten();
+    let cached_id = || cache_file.and_then(read_worker_id);
+
+    let worker_id = env_id
+        .or_else(cached_id)
+        .unwrap_or_else(|| pid_worker_id(center_id));
+
+    if let Some(cache_file) = cache_file {
+        if read_worker_id(cache_file) != Some(worker_id) {
+            let _ = write_worker_id(cache_file, worker_id);
+        }
+
+        if env_id.is_none() {
+            return claim_worker_id_with(cache_file, worker_id, &WORKER_ID_LOCKS);
+        }
+    }
+
+    worker_id
+}
+
+/// Claims the first free `worker` ID slot from the `preferred` on by locking a file next to
+/// the given `cache_file`, wrapping around at [`Constants::MAX_WORKER_ID`].
+///
+/// A slot is free if its lock file is not locked, or already held in the given `locks`,
+/// the newly locked file is kept in the `locks` to hold the lock, see [`try_get_worker_id`].
+///
+/// @since 0.4.0
+pub(crate) fn claim_worker_id_with(
+    cache_file: &Path,
+    preferred: u64,
+    locks: &Mutex<Vec<(PathBuf, File)>>,
+) -> u64 {
+    let mut locks = locks
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    for offset in 0..=Constants::MAX_WORKER_ID {
+        let slot = (preferred + offset) & Constants::MAX_WORKER_ID;
+        let lock = slot_lock_file(cache_file, slot);
+        if locks.iter().any(|(held, _)| *held == lock) {
+            return slot;
+        }
+
+        match try_lock(&lock) {
+            Ok(Some(file)) => {
+                locks.push((lock, file));
+                return slot;
+            }
+            Ok(None) => continue,
+            // The lock files can not be opened, do not probe further.
+            Err(_) => break,
+        }
+    }
+
+    preferred
+}
+
+/// Returns the lock file of the `worker` ID `slot`: `<cache file>.<slot>.lock`.
+fn slot_lock_file(cache_file: &Path, slot: u64) -> PathBuf {
+    let mut file = cache_file.as_os_str().to_owned();
+    file.push(format!(".{}.lock", slot));
+    PathBuf::from(file)
+}
+
+/// Tries to lock the given `lock` file exclusively without blocking,
+/// returns the open file holding the lock, `Ok(None)` if another one holds it.
+fn try_lock(lock: &Path) -> io::Result<Option<File>> {
+    if let Some(parent) = lock.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(lock)?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(err)) => Err(err),
+    }
+}
+
+/// Get the `worker` ID by the hashcode of the hostname of the local host, see [`try_get_hostname`],
+/// stable across the restarts on the same machine unlike the pid.
+///
+/// Falls back to [`try_get_worker_id`] if the hostname is unavailable.
+///
+/// Note: the hostnames may collide once masked to [`Constants::MAX_WORKER_ID`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::Constants;
+/// use snowflaker::infras;
+///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var(infras::SNOWFLAKE_CACHE_DIR, &dir);
+/// let worker_id = infras::try_get_hostname_worker_id(3);
+/// assert!(worker_id <= Constants::MAX_WORKER_ID);
+/// assert_eq!(worker_id, infras::try_get_hostname_worker_id(3));
+/// # let _ = std::fs::remove_dir_all(dir);
+/// ```
+///
+/// @since 0.4.0
+pub fn try_get_hostname_worker_id(center_id: u64) -> u64 {
+    match try_get_hostname() {
+        Some(hostname) => hostname_worker_id(&hostname),
+        None => try_get_worker_id(center_id),
+    }
+}
+
+/// Get the hostname of the local host: the kernel hostname file on Linux,
+/// otherwise the env var `HOSTNAME` or `COMPUTERNAME`, `None` if all are absent or blank.
+///
+/// @since 0.4.0
+pub fn try_get_hostname() -> Option<String> {
+    hostname_with(|key| std::env::var(key), Some(Path::new(HOSTNAME_FILE)))
+}
+
+/// Reads the hostname from the given `hostname_file` first,
+/// then looks up the env vars by the given `lookup`.
+///
+/// @since 0.4.0
+pub(crate) fn hostname_with<F>(lookup: F, hostname_file: Option<&Path>) -> Option<String>
+where
+    F: Fn(&str) -> Result<String, VarError>,
+{
+    hostname_file
+        .and_then(|file| fs::read_to_string(file).ok())
+        .into_iter()
+        .chain(HOSTNAME_ENV_KEYS.iter().filter_map(|key| lookup(key).ok()))
+        .map(|hostname| hostname.trim().to_string())
+        .find(|hostname| !hostname.is_empty())
+}
+
+/// Derives the `worker` ID from the hashcode of the hostname.
+///
+/// @since 0.4.0
+pub(crate) fn hostname_worker_id(hostname: &str) -> u64 {
+    (hostname.hashcode() & 0xFFFF) & Constants::MAX_WORKER_ID
+}
+
+/// Derives the `worker` ID from the hashcode of the `center_id` and the pid.
+fn pid_worker_id(center_id: u64) -> u64 {
+    let center_id = center_id.to_string();
     let pid = std::process::id().to_string();
-    buf.push_str(&pid);
 
-    let hashcode = buf.hashcode();
+    let hashcode = hashcode::hashcode_of(center_id.chars().chain(pid.chars()));
     (hashcode & 0xFFFF) & Constants::MAX_WORKER_ID
 }
 
+/// Reads the persisted `worker` ID, `None` if the file is missing or malformed.
+fn read_worker_id(cache_file: &Path) -> Option<u64> {
+    fs::read_to_string(cache_file)
+        .ok()
+        .and_then(|content| content.trim().parse::<u64>().ok())
+        .filter(|&worker_id| worker_id <= Constants::MAX_WORKER_ID)
+}
+
+/// Persists the `worker` ID, creates the parent dirs if absent.
+fn write_worker_id(cache_file: &Path, worker_id: u64) -> std::io::Result<()> {
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(cache_file, worker_id.to_string())
+}
+
+// ----------------------------------------------------------------
+
+/// Get the `(data-center ID, worker ID)` pair derived by [`try_get_data_center_id`]
+/// and [`try_get_worker_id`], memoized on the first call for the lifetime of the process,
+/// so the later calls skip the MAC lookup and the hashing.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::infras;
+///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var(infras::SNOWFLAKE_CACHE_DIR, &dir);
+/// let (center_id, worker_id) = infras::try_get_identity();
+/// assert_eq!((center_id, worker_id), infras::try_get_identity());
+/// # let _ = std::fs::remove_dir_all(dir);
+/// ```
+///
+/// @since 0.4.0
+pub fn try_get_identity() -> (u64, u64) {
+    identity_of(&IDENTITY_CACHE)
+}
+
+/// Clears the memoized pair of [`try_get_identity`], the next call derives it again,
+/// e.g. in the tests.
+///
+/// @since 0.4.0
+pub fn clear_identity_cache() {
+    *IDENTITY_CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Derives the `(data-center ID, worker ID)` pair on the first call, memoized in the given `cache`.
+///
+/// @since 0.4.0
+pub(crate) fn identity_of(cache: &Mutex<Option<(u64, u64)>>) -> (u64, u64) {
+    let mut cache = cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    *cache.get_or_insert_with(|| {
+        let center_id = try_get_data_center_id();
+        (center_id, try_get_worker_id(center_id))
+    })
+}
+
 // ----------------------------------------------------------------
 
 /// Get the `data-center` ID by network interface(`non-loopback`) on the local host
@@ -92,18 +488,66 @@ pub fn try_get_worker_id(center_id: u64) -> u64 {
 /// assert!(center_id <= Constants::MAX_DATA_CENTER_ID);
 /// ```
 /// @since 0.2.0
-#[rustfmt::skip]
 pub fn try_get_data_center_id() -> u64 {
-    let mut id = Constants::DEFAULT_DATA_CENTER_ID;
+    match try_get_local_first_non_loopback_interface() {
+        Ok(mac) => data_center_id_of(&mac),
+        Err(_) => Constants::DEFAULT_DATA_CENTER_ID & Constants::MAX_DATA_CENTER_ID,
+    }
+}
 
-    if let Ok(mac) = try_get_local_first_non_loopback_interface() {
-        let tail = mac.len() - 1;
-        let lower_bits = (0x000000FF & (mac[tail - 1] as u64)) | (0x0000FF00 & ((mac[tail] as u64) << 8));
+/// Get the `data-center` ID by the last octet of the first non-loopback IPv4 address on the local host,
+/// an alternative to the MAC-based [`try_get_data_center_id`].
+///
+/// Falls back to [`try_get_data_center_id`] if no IPv4 address is found.
+///
+/// Note: the hosts whose last octets are congruent modulo `MAX_DATA_CENTER_ID + 1` share the ID.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::Constants;
+/// use snowflaker::infras;
+///
+/// let center_id = infras::try_get_ipv4_data_center_id();
+/// assert!(center_id <= Constants::MAX_DATA_CENTER_ID);
+/// ```
+///
+/// @since 0.4.0
+pub fn try_get_ipv4_data_center_id() -> u64 {
+    let ipv4 = IfCfg::get()
+        .ok()
+        .and_then(|interfaces| try_select_interface_ipv4(&interfaces));
 
-        id = lower_bits >> 8;
-        if id == 0 {
-            id = lower_bits >> 6;
-        }
+    match ipv4 {
+        Some(ipv4) => data_center_id_of_ipv4(ipv4),
+        None => try_get_data_center_id(),
+    }
+}
+
+/// Derives the `data-center` ID from the last octet of the IPv4 address
+/// modulo `MAX_DATA_CENTER_ID + 1`.
+///
+/// @since 0.4.0
+pub(crate) fn data_center_id_of_ipv4(ipv4: Ipv4Addr) -> u64 {
+    ipv4.octets()[3] as u64 % (Constants::MAX_DATA_CENTER_ID + 1)
+}
+
+/// Derives the `data-center` ID from the last two bytes of the MAC address,
+/// falls back to [`Constants::DEFAULT_DATA_CENTER_ID`] if it is shorter than two bytes.
+///
+/// @since 0.4.0
+#[rustfmt::skip]
+pub(crate) fn data_center_id_of(mac: &[u8]) -> u64 {
+    if mac.len() < 2 {
+        return Constants::DEFAULT_DATA_CENTER_ID & Constants::MAX_DATA_CENTER_ID;
+    }
+
+    let tail = mac.len() - 1;
+    let lower_bits = (0x000000FF & (mac[tail - 1] as u64)) | (0x0000FF00 & ((mac[tail] as u64) << 8));
+
+    let mut id = lower_bits >> 8;
+    if id == 0 {
+        id = lower_bits >> 6;
     }
 
     id & Constants::MAX_DATA_CENTER_ID
@@ -117,23 +561,150 @@ pub fn try_get_data_center_id() -> u64 {
 ///
 /// @since 0.2.0
 fn try_get_local_first_non_loopback_interface() -> Result<Vec<u8>, Box<dyn Error>> {
+    #[cfg(test)]
+    MAC_LOOKUPS.with(|lookups| lookups.set(lookups.get() + 1));
+
     let interfaces = match IfCfg::get() {
         Ok(interfaces) => interfaces,
         Err(_) => return Err(Box::new(InterfaceError::IfCfgError)),
     };
 
-    // Notes: does not consider whether the interface is up?
-    let mac_bytes = interfaces
+    try_select_interface_mac(&interfaces)
+}
+
+/// Selects the MAC address of the most suitable non-loopback interface.
+///
+/// Interfaces that are up and have a non-zero MAC address qualify, physical interfaces are
+/// preferred over virtual ones (`docker0`, `veth*`, VPN tunnels, ...) by name heuristics.
+/// If none qualify, falls back to the first non-loopback interface.
+///
+/// @since 0.4.0
+pub(crate) fn try_select_interface_mac<I: NetworkInterface>(
+    interfaces: &[I],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let candidates: Vec<&I> = interfaces
         .iter()
-        .find(|conf| !conf.name.contains(LOOPBACK))
-        .map(|conf| {
-            conf.mac
-                .split('-')
-                .map(|hex| u8::from_str_radix(hex, 16))
-                .collect::<Result<Vec<u8>, _>>()
-                .map_err(|err| Box::new(err) as Box<dyn Error>)
+        .filter(|conf| !conf.name().contains(LOOPBACK))
+        .collect();
+
+    let qualified: Vec<(&I, Vec<u8>)> = candidates
+        .iter()
+        .filter(|conf| conf.is_up())
+        .filter_map(|conf| match parse_mac(conf.mac()) {
+            Ok(mac) if mac.iter().any(|&byte| byte != 0) => Some((*conf, mac)),
+            _ => None,
         })
-        .ok_or_else(|| Box::new(InterfaceError::NonLoopbackNotFound))??;
+        .collect();
+
+    let preferred = qualified
+        .iter()
+        .position(|(conf, _)| !is_virtual(conf.name()))
+        .unwrap_or(0);
+    if let Some((_, mac)) = qualified.into_iter().nth(preferred) {
+        return Ok(mac);
+    }
+
+    // Fallback: the first non-loopback interface.
+    candidates
+        .first()
+        .map(|conf| parse_mac(conf.mac()))
+        .ok_or_else(|| Box::new(InterfaceError::NonLoopbackNotFound))?
+}
+
+/// Selects the first non-loopback IPv4 address, the interfaces that are up are preferred.
+///
+/// @since 0.4.0
+pub(crate) fn try_select_interface_ipv4<I: NetworkInterface>(interfaces: &[I]) -> Option<Ipv4Addr> {
+    let candidates: Vec<&I> = interfaces
+        .iter()
+        .filter(|conf| !conf.name().contains(LOOPBACK))
+        .collect();
+    let first_of = |conf: &&I| {
+        conf.ipv4s()
+            .into_iter()
+            .find(|ipv4| !ipv4.is_loopback() && !ipv4.is_unspecified())
+    };
 
-    Ok(mac_bytes)
+    candidates
+        .iter()
+        .filter(|conf| conf.is_up())
+        .find_map(first_of)
+        .or_else(|| candidates.iter().find_map(first_of))
+}
+
+/// Parses the MAC address in the form of `aa-bb-cc-dd-ee-ff` or `aa:bb:cc:dd:ee:ff`,
+/// an empty MAC address yields no bytes.
+fn parse_mac(mac: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mac = mac.trim();
+    if mac.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    mac.split(['-', ':'])
+        .map(|hex| u8::from_str_radix(hex, 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|err| Box::new(err) as Box<dyn Error>)
+}
+
+/// Guesses whether the interface is virtual by its name.
+fn is_virtual(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+
+    VIRTUAL_INTERFACE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+        || VIRTUAL_INTERFACE_KEYWORDS
+            .iter()
+            .any(|keyword| name.contains(keyword))
+}
+
+// ----------------------------------------------------------------
+
+/// A network interface, abstracts over [`IfCfg`] to allow injection.
+///
+/// @since 0.4.0
+pub(crate) trait NetworkInterface {
+    /// The name of the interface.
+    fn name(&self) -> &str;
+    /// The MAC address of the interface.
+    fn mac(&self) -> &str;
+    /// Whether the interface is up.
+    fn is_up(&self) -> bool;
+    /// The IPv4 addresses of the interface.
+    ///
+    /// @since 0.4.0
+    fn ipv4s(&self) -> Vec<Ipv4Addr>;
+}
+
+impl NetworkInterface for IfCfg {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn mac(&self) -> &str {
+        &self.mac
+    }
+
+    /// Reads the `operstate` on Linux, otherwise treats the interface
+    /// with any address assigned as up.
+    fn is_up(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        if let Ok(state) =
+            std::fs::read_to_string(format!("/sys/class/net/{}/operstate", self.name))
+        {
+            return matches!(state.trim(), "up" | "unknown");
+        }
+
+        !self.addresses.is_empty()
+    }
+
+    fn ipv4s(&self) -> Vec<Ipv4Addr> {
+        self.addresses
+            .iter()
+            .filter_map(|address| match address.address {
+                Some(SocketAddr::V4(address)) => Some(*address.ip()),
+                _ => None,
+            })
+            .collect()
+    }
 }
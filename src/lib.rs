@@ -17,21 +17,48 @@
 // error[E0554]: `#![feature]` may not be used on the stable release channel
 // #![feature(doc_cfg)]
 
+// @since 0.4.0
+// features = [`"std"`] on by default, `no_std` otherwise.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+// @since 0.4.0
+// features = [`"global"`] on by default, the builtin global generators, e.g. `next_id()`.
+
 // ----------------------------------------------------------------
 
-use std::sync::{Arc, Mutex};
+extern crate alloc;
+
+#[cfg(feature = "global")]
+use std::collections::HashMap;
+#[cfg(feature = "global")]
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
+#[cfg(feature = "global")]
 use lazy_static::lazy_static;
 
-use crate::generator::{Generator, SnowflakeError, SnowflakeGenerator};
+#[cfg(feature = "global")]
+use crate::generator::{Constants, Generator, SnowflakeError, SnowflakeGenerator};
+#[cfg(feature = "global")]
+use crate::generator128::SnowflakeGenerator128;
 
 // ----------------------------------------------------------------
 
+/// @since 0.4.0
+#[cfg(feature = "std")]
+pub mod clock;
+/// @since 0.4.0
+pub mod codec;
+/// @since 0.4.0
+pub mod compare;
 /// @since 0.1.0
 pub mod generator;
+/// @since 0.4.0
+pub mod generator128;
 
 /// @since 0.2.0
 pub mod hashcode;
+/// @since 0.4.0
+pub mod identity;
 /// @since 0.2.0
 #[cfg(feature = "dynamic")]
 pub mod infras;
@@ -39,38 +66,191 @@ pub mod infras;
 /// @since 0.3.0
 #[macro_use]
 pub mod macros;
+/// @since 0.4.0
+pub mod metrics;
+/// @since 0.4.0
+#[cfg(feature = "registry")]
+pub mod registry;
+/// @since 0.4.0
+pub mod verify;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
 // ----------------------------------------------------------------
 
+/// The builtin generator is set once and never replaced, so [`next_id`] calls it through
+/// a shared reference without a lock, the atomics of the generator handle the concurrency.
+#[cfg(feature = "global")]
+static BUILT_IN_SNOWFLAKE: OnceLock<SnowflakeGenerator> = OnceLock::new();
+
+#[cfg(feature = "global")]
 lazy_static! {
-    static ref BUILT_IN_SNOWFLAKE: Arc<Mutex<Option<SnowflakeGenerator>>> =
-        Arc::new(Mutex::new(None));
+    /// The builtin `dynamic` generator is replaceable by `reset_dynamic`,
+    /// `dynamic_next_id` shares the read lock.
+    static ref BUILT_IN_SNOWFLAKE_DYNAMIC: Arc<RwLock<Option<SnowflakeGenerator>>> =
+        Arc::new(RwLock::new(None));
 }
 
+#[cfg(feature = "global")]
 lazy_static! {
-    static ref BUILT_IN_SNOWFLAKE_DYNAMIC: Arc<Mutex<Option<SnowflakeGenerator>>> =
-        Arc::new(Mutex::new(None));
+    static ref BUILT_IN_SNOWFLAKE_128: SnowflakeGenerator128 =
+        SnowflakeGenerator128::builder().build().unwrap();
+}
+
+#[cfg(feature = "global")]
+lazy_static! {
+    static ref NAMED_SNOWFLAKES: RwLock<HashMap<String, SnowflakeGenerator>> =
+        RwLock::new(HashMap::new());
+}
+
+/// The generators of `thread_local_next_id`, the exited threads give theirs back for reuse.
+#[cfg(feature = "global")]
+static THREAD_LOCAL_POOL: Mutex<ThreadLocalPool> = Mutex::new(ThreadLocalPool::new());
+
+#[cfg(feature = "global")]
+thread_local! {
+    static THREAD_LOCAL_SNOWFLAKE: ThreadLocalSnowflake = ThreadLocalSnowflake::acquire();
 }
 
 // ----------------------------------------------------------------
 
-fn generator() -> Arc<Mutex<Option<SnowflakeGenerator>>> {
-    let mut instance = BUILT_IN_SNOWFLAKE.lock().unwrap();
-    if instance.is_none() {
-        *instance = Some(SnowflakeGenerator::builtin().unwrap());
+/// The pool of the per-thread generators, one per `worker` ID of the
+/// [`Constants::DEFAULT_DATA_CENTER_ID`] except the [`Constants::DEFAULT_WORKER_ID`] of the builtin generator.
+#[cfg(feature = "global")]
+struct ThreadLocalPool {
+    /// All the generators created so far, by the allocation order.
+    created: Vec<SnowflakeGenerator>,
+    /// The count of the live threads holding each of the created generators,
+    /// the exited threads give theirs back, reused with their state.
+    holders: Vec<usize>,
+}
+
+#[cfg(feature = "global")]
+impl ThreadLocalPool {
+    const fn new() -> Self {
+        Self {
+            created: Vec::new(),
+            holders: Vec::new(),
+        }
+    }
+
+    /// Takes a free generator, or creates one with the next unused `worker` ID,
+    /// or shares a clone of the least shared one once all the `worker` IDs are taken:
+    /// the clones share the state, so the IDs stay unique at the cost of contention.
+    ///
+    /// Returns the index of the generator along with it, see [`ThreadLocalPool::release`].
+    fn acquire(&mut self) -> Result<(usize, SnowflakeGenerator), SnowflakeError> {
+        let least = self
+            .holders
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, holders)| **holders);
+
+        let index = match least {
+            Some((index, &holders))
+                if holders == 0 || self.created.len() as u64 >= Constants::MAX_WORKER_ID =>
+            {
+                index
+            }
+            _ => {
+                // The `worker` IDs except the one of the builtin generator.
+                let index = self.created.len() as u64;
+                let worker_id = if index < Constants::DEFAULT_WORKER_ID {
+                    index
+                } else {
+                    index + 1
+                };
+                let generator =
+                    SnowflakeGenerator::new(Constants::DEFAULT_DATA_CENTER_ID, worker_id)?;
+                self.created.push(generator);
+                self.holders.push(0);
+
+                self.created.len() - 1
+            }
+        };
+
+        self.holders[index] += 1;
+
+        Ok((index, self.created[index].clone()))
     }
 
-    Arc::clone(&BUILT_IN_SNOWFLAKE)
+    /// Gives back the generator of the `index` taken by [`ThreadLocalPool::acquire`].
+    fn release(&mut self, index: usize) {
+        self.holders[index] -= 1;
+    }
 }
 
-#[cfg(feature = "dynamic")]
-fn dynamic_generator() -> Arc<Mutex<Option<SnowflakeGenerator>>> {
-    let mut instance = BUILT_IN_SNOWFLAKE_DYNAMIC.lock().unwrap();
-    if instance.is_none() {
-        *instance = Some(SnowflakeGenerator::dynamic().unwrap());
+/// The generator of the current thread, given back to the [`ThreadLocalPool`] on the thread exit.
+#[cfg(feature = "global")]
+struct ThreadLocalSnowflake(Result<(usize, SnowflakeGenerator), SnowflakeError>);
+
+#[cfg(feature = "global")]
+impl ThreadLocalSnowflake {
+    fn acquire() -> Self {
+        Self(thread_local_pool().acquire())
+    }
+}
+
+#[cfg(feature = "global")]
+impl Drop for ThreadLocalSnowflake {
+    fn drop(&mut self) {
+        if let Ok((index, _)) = &self.0 {
+            thread_local_pool().release(*index);
+        }
+    }
+}
+
+#[cfg(feature = "global")]
+fn thread_local_pool() -> std::sync::MutexGuard<'static, ThreadLocalPool> {
+    // The pool is always consistent, a panic elsewhere never leaves it torn.
+    THREAD_LOCAL_POOL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(feature = "global")]
+fn install(
+    instance: &OnceLock<SnowflakeGenerator>,
+    generator: SnowflakeGenerator,
+) -> Result<(), SnowflakeError> {
+    instance
+        .set(generator)
+        .map_err(|_| SnowflakeError::AlreadyInitialized)
+}
+
+#[cfg(feature = "global")]
+fn generator() -> &'static SnowflakeGenerator {
+    BUILT_IN_SNOWFLAKE.get_or_init(|| SnowflakeGenerator::builtin().unwrap())
+}
+
+#[cfg(feature = "global")]
+fn try_generator_of(
+    instance: &OnceLock<SnowflakeGenerator>,
+) -> Result<&SnowflakeGenerator, SnowflakeError> {
+    if let Some(generator) = instance.get() {
+        return Ok(generator);
+    }
+
+    // A racing initializer may win, either way the installed one is returned.
+    let _ = instance.set(SnowflakeGenerator::builtin()?);
+
+    Ok(instance.get().unwrap())
+}
+
+#[cfg(all(feature = "dynamic", feature = "global"))]
+fn replace(instance: &RwLock<Option<SnowflakeGenerator>>, generator: SnowflakeGenerator) {
+    let mut instance = instance.write().unwrap();
+    *instance = Some(generator);
+}
+
+#[cfg(all(feature = "dynamic", feature = "global"))]
+fn dynamic_generator() -> Arc<RwLock<Option<SnowflakeGenerator>>> {
+    if BUILT_IN_SNOWFLAKE_DYNAMIC.read().unwrap().is_none() {
+        let mut instance = BUILT_IN_SNOWFLAKE_DYNAMIC.write().unwrap();
+        if instance.is_none() {
+            *instance = Some(SnowflakeGenerator::dynamic().unwrap());
+        }
     }
 
     Arc::clone(&BUILT_IN_SNOWFLAKE_DYNAMIC)
@@ -78,6 +258,42 @@ fn dynamic_generator() -> Arc<Mutex<Option<SnowflakeGenerator>>> {
 
 // ----------------------------------------------------------------
 
+/// Installs a [`SnowflakeGenerator`] with the given `data-center` ID and `worker` ID
+/// as the builtin generator used by [`next_id`] and friends.
+///
+/// It must be called before the first use of the builtin generator,
+/// [`next_id`] lazily falls back to [`SnowflakeGenerator::builtin`] only if `init` was never called.
+///
+/// ## Return
+///
+/// Returns a `Result<(), SnowflakeError>` where:
+///
+/// - `Ok(())`: The generator was installed.
+/// - `Err(SnowflakeError)`: [`SnowflakeError::AlreadyInitialized`] if the builtin generator
+///   was already initialized, or the `data-center` ID or `worker` ID invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// let rvt = snowflaker::init(3, 7);
+/// assert!(rvt.is_ok());
+///
+/// let rvt = snowflaker::next_id();
+/// assert!(rvt.is_ok());
+///
+/// let rvt = snowflaker::init(3, 7);
+/// assert!(rvt.is_err());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn init(center_id: u64, worker_id: u64) -> Result<(), SnowflakeError> {
+    install(
+        &BUILT_IN_SNOWFLAKE,
+        SnowflakeGenerator::new(center_id, worker_id)?,
+    )
+}
+
 /// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
 /// generates and returns a unique ID based on the [`Generator::next_id`] function.
 ///
@@ -96,8 +312,35 @@ fn dynamic_generator() -> Arc<Mutex<Option<SnowflakeGenerator>>> {
 /// let rvt = next_id();
 /// assert!(rvt.is_ok());
 /// ```
+#[cfg(feature = "global")]
 pub fn next_id() -> Result<u64, SnowflakeError> {
-    generator().lock().unwrap().as_ref().unwrap().next_id()
+    generator().next_id()
+}
+
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates and returns a unique ID like [`next_id`], but never panics.
+///
+/// ## Return
+///
+/// Returns a `Result<u64, SnowflakeError>` where:
+///
+/// - `Ok(u64)`: Represents a successfully generated unique ID.
+/// - `Err(SnowflakeError)`: The errors of the lazy [`SnowflakeGenerator::builtin`],
+///   or an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::try_next_id;
+///
+/// let rvt = try_next_id();
+/// assert!(rvt.is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn try_next_id() -> Result<u64, SnowflakeError> {
+    try_generator_of(&BUILT_IN_SNOWFLAKE)?.next_id()
 }
 
 /// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
@@ -118,12 +361,367 @@ pub fn next_id() -> Result<u64, SnowflakeError> {
 /// let rvt = next_id_string();
 /// assert!(rvt.is_ok());
 /// ```
+#[cfg(feature = "global")]
 pub fn next_id_string() -> Result<String, SnowflakeError> {
     next_id().map(|v| v.to_string())
 }
 
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates and returns a unique fixed-length String ID in the given `radix`, see [`codec::to_radix`].
+///
+/// The ID is never truncated: a `width` smaller than the encoded length returns the full string.
+///
+/// ## Return
+///
+/// Returns a `Result<String, SnowflakeError>` where:
+///
+/// - `Ok(String)`: Represents a successfully generated unique ID, left-padded with `pad` to `width`.
+/// - `Err(SnowflakeError)`: [`SnowflakeError::RadixInvalid`] if the `radix` out of range `2..=36`,
+///   or an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::next_id_radix;
+///
+/// let rvt = next_id_radix(36, 13, '0');
+/// assert_eq!(13, rvt.unwrap().len());
+///
+/// let rvt = next_id_radix(1, 13, '0');
+/// assert!(rvt.is_err());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn next_id_radix(radix: u32, width: usize, pad: char) -> Result<String, SnowflakeError> {
+    if !(2..=36).contains(&radix) {
+        return Err(SnowflakeError::RadixInvalid);
+    }
+
+    next_id().and_then(|id| codec::to_radix(id, radix, width, pad))
+}
+
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates and returns a unique `i64` ID, see [`SnowflakeGenerator::as_i64`].
+///
+/// ## Return
+///
+/// Returns a `Result<i64, SnowflakeError>` where:
+///
+/// - `Ok(i64)`: Represents a successfully generated unique ID.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::next_id_i64;
+///
+/// let rvt = next_id_i64();
+/// assert!(rvt.unwrap() > 0);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn next_id_i64() -> Result<i64, SnowflakeError> {
+    generator().next_id_i64()
+}
+
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates and returns a unique base62 String ID, see [`codec::encode_base62`].
+///
+/// ## Return
+///
+/// Returns a `Result<String, SnowflakeError>` where:
+///
+/// - `Ok(String)`: Represents a successfully generated unique base62 ID.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::{codec, next_id_base62};
+///
+/// let rvt = next_id_base62();
+/// assert!(rvt.is_ok());
+/// assert!(codec::decode_base62(&rvt.unwrap()).is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn next_id_base62() -> Result<String, SnowflakeError> {
+    next_id().map(codec::encode_base62)
+}
+
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates and returns a unique fixed-width(`16`) lowercase hex String ID, see [`codec::to_hex`].
+///
+/// ## Return
+///
+/// Returns a `Result<String, SnowflakeError>` where:
+///
+/// - `Ok(String)`: Represents a successfully generated unique hex ID.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::{codec, next_id_hex};
+///
+/// let rvt = next_id_hex();
+/// assert!(rvt.is_ok());
+/// assert!(codec::from_hex(&rvt.unwrap()).is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn next_id_hex() -> Result<String, SnowflakeError> {
+    next_id().map(codec::to_hex)
+}
+
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates and returns a unique ID as a fixed 8-byte big-endian array, see [`codec::id_to_be_bytes`].
+///
+/// ## Return
+///
+/// Returns a `Result<[u8; 8], SnowflakeError>` where:
+///
+/// - `Ok([u8; 8])`: Represents a successfully generated unique ID in big-endian bytes.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::{codec, next_id_bytes};
+///
+/// let rvt = next_id_bytes();
+/// assert!(codec::id_from_be_bytes(rvt.unwrap()) > 0);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn next_id_bytes() -> Result<[u8; 8], SnowflakeError> {
+    next_id().map(codec::id_to_be_bytes)
+}
+
+/// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::builtin`]
+/// generates two consecutive unique IDs and combines them into a canonical `8-4-4-4-12`
+/// UUID-shaped String ID, see [`codec::encode_uuid_like`].
+///
+/// The version (`8`) and the variant (`0b10`) bits are set, so it passes the standard UUID parsers,
+/// and [`codec::decode_uuid_like`] round-trips the embedded IDs.
+///
+/// ## Return
+///
+/// Returns a `Result<String, SnowflakeError>` where:
+///
+/// - `Ok(String)`: Represents a successfully generated unique UUID-shaped ID.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::{codec, next_uuid_like};
+///
+/// let rvt = next_uuid_like();
+/// assert_eq!(36, rvt.as_ref().unwrap().len());
+/// assert!(codec::decode_uuid_like(&rvt.unwrap()).is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(all(feature = "uuid", feature = "global"))]
+pub fn next_uuid_like() -> Result<String, SnowflakeError> {
+    let generator = generator();
+
+    codec::encode_uuid_like(generator.next_id()?, generator.next_id()?)
+}
+
+/// Use builtin default [`SnowflakeGenerator128`] instance
+/// generates and returns a unique 128-bit ID, see [`SnowflakeGenerator128::next_id`].
+///
+/// ## Return
+///
+/// Returns a `Result<u128, SnowflakeError>` where:
+///
+/// - `Ok(u128)`: Represents a successfully generated unique ID.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::next_id_u128;
+///
+/// let rvt = next_id_u128();
+/// assert!(rvt.is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn next_id_u128() -> Result<u128, SnowflakeError> {
+    BUILT_IN_SNOWFLAKE_128.next_id()
+}
+
+/// Use a lazily created per-thread [`SnowflakeGenerator`]
+/// generates and returns a unique ID without contention between threads.
+///
+/// Each thread owns its own generator with the [`Constants::DEFAULT_DATA_CENTER_ID`] and
+/// a distinct `worker` ID, skipping the [`Constants::DEFAULT_WORKER_ID`] of the builtin generator
+/// behind [`next_id`]. An exited thread gives its generator back, with its state,
+/// to the next new thread, so a thread pool churning its threads does not run out of `worker` IDs.
+///
+/// Note: the `worker` ID space only holds `31` IDs for the threads, beyond the `31`th live thread
+/// the threads share the least shared generators: the IDs stay unique, but the threads
+/// sharing a generator contend on it. The IDs may still collide with another generator
+/// of the same `data-center` ID and `worker` ID, e.g. `init(1, 5)`.
+///
+/// ## Return
+///
+/// Returns a `Result<u64, SnowflakeError>` where:
+///
+/// - `Ok(u64)`: Represents a successfully generated unique ID.
+/// - `Err(SnowflakeError)`: Indicates an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::thread_local_next_id;
+///
+/// let rvt = thread_local_next_id();
+/// assert!(rvt.is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn thread_local_next_id() -> Result<u64, SnowflakeError> {
+    THREAD_LOCAL_SNOWFLAKE.with(|generator| match &generator.0 {
+        Ok((_, generator)) => generator.next_id(),
+        Err(err) => Err(err.clone()),
+    })
+}
+
+// ----------------------------------------------------------------
+
+/// Registers the given [`SnowflakeGenerator`] as the named global generator `name`,
+/// one per logical ID space, e.g. `users`, `orders` and `events`, see [`named_next_id`].
+///
+/// ## Return
+///
+/// Returns a `Result<(), SnowflakeError>` where:
+///
+/// - `Ok(())`: The generator was registered.
+/// - `Err(SnowflakeError)`: [`SnowflakeError::AlreadyInitialized`] if the `name` was already registered,
+///   the registered generator is kept, or [`SnowflakeError::LockPoisoned`].
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::SnowflakeGenerator;
+///
+/// let rvt = snowflaker::register_named("orders", SnowflakeGenerator::new(3, 7).unwrap());
+/// assert!(rvt.is_ok());
+///
+/// let rvt = snowflaker::register_named("orders", SnowflakeGenerator::new(3, 8).unwrap());
+/// assert!(rvt.is_err());
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn register_named(name: &str, generator: SnowflakeGenerator) -> Result<(), SnowflakeError> {
+    let mut generators = NAMED_SNOWFLAKES
+        .write()
+        .map_err(|_| SnowflakeError::LockPoisoned)?;
+    if generators.contains_key(name) {
+        return Err(SnowflakeError::AlreadyInitialized);
+    }
+
+    generators.insert(name.to_string(), generator);
+
+    Ok(())
+}
+
+/// Use the named global generator registered by [`register_named`]
+/// generates and returns a unique ID based on the [`Generator::next_id`] function.
+///
+/// The lookup shares a read lock, the generator itself is thread-safe.
+///
+/// ## Return
+///
+/// Returns a `Result<u64, SnowflakeError>` where:
+///
+/// - `Ok(u64)`: Represents a successfully generated unique ID.
+/// - `Err(SnowflakeError)`: [`SnowflakeError::GeneratorNotFound`] if the `name` was never registered,
+///   or an error occurred, such as the system clock moved backwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::{SnowflakeError, SnowflakeGenerator};
+///
+/// snowflaker::register_named("users", SnowflakeGenerator::new(3, 7).unwrap()).unwrap();
+///
+/// let id = snowflaker::named_next_id("users").unwrap();
+/// assert_eq!(7, SnowflakeGenerator::decompose(id).worker_id);
+///
+/// let rvt = snowflaker::named_next_id("unknown");
+/// assert!(matches!(rvt, Err(SnowflakeError::GeneratorNotFound)));
+/// ```
+///
+/// @since 0.4.0
+#[cfg(feature = "global")]
+pub fn named_next_id(name: &str) -> Result<u64, SnowflakeError> {
+    let generators = NAMED_SNOWFLAKES
+        .read()
+        .map_err(|_| SnowflakeError::LockPoisoned)?;
+
+    generators
+        .get(name)
+        .ok_or(SnowflakeError::GeneratorNotFound)?
+        .next_id()
+}
+
 // ----------------------------------------------------------------
 
+/// Replaces the builtin `dynamic` generator with a [`SnowflakeGenerator`] of the given
+/// `data-center` ID and `worker` ID, [`dynamic_next_id`] and friends use it afterward.
+///
+/// The swap happens under the generator's write lock, in-flight calls either finish with
+/// the old generator or start with the new one, never observe a torn state.
+///
+/// Note: the new generator starts with a fresh sequence state, IDs generated by
+/// the same `data-center` ID and `worker` ID within the current millisecond may be duplicated.
+///
+/// ## Return
+///
+/// Returns a `Result<(), SnowflakeError>` where:
+///
+/// - `Ok(())`: The generator was replaced.
+/// - `Err(SnowflakeError)`: The `data-center` ID or `worker` ID invalid, the old generator is kept.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::SnowflakeGenerator;
+///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var("SNOWFLAKE_CACHE_DIR", &dir);
+/// let rvt = snowflaker::reset_dynamic(3, 7);
+/// assert!(rvt.is_ok());
+///
+/// let id = snowflaker::dynamic_next_id().unwrap();
+/// assert_eq!(7, SnowflakeGenerator::decompose(id).worker_id);
+/// # let _ = std::fs::remove_dir_all(dir);
+/// ```
+///
+/// @since 0.4.0
+#[cfg(all(feature = "dynamic", feature = "global"))]
+pub fn reset_dynamic(center_id: u64, worker_id: u64) -> Result<(), SnowflakeError> {
+    replace(
+        &BUILT_IN_SNOWFLAKE_DYNAMIC,
+        SnowflakeGenerator::new(center_id, worker_id)?,
+    );
+
+    Ok(())
+}
+
 /// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::dynamic`]
 /// generates and returns a unique ID based on the [`Generator::next_id`] function.
 ///
@@ -139,12 +737,15 @@ pub fn next_id_string() -> Result<String, SnowflakeError> {
 /// ```rust
 /// use snowflaker::dynamic_next_id;
 ///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var("SNOWFLAKE_CACHE_DIR", &dir);
 /// let rvt = dynamic_next_id();
 /// assert!(rvt.is_ok());
+/// # let _ = std::fs::remove_dir_all(dir);
 /// ```
-#[cfg(feature = "dynamic")]
+#[cfg(all(feature = "dynamic", feature = "global"))]
 pub fn dynamic_next_id() -> Result<u64, SnowflakeError> {
-    dynamic_generator().lock().unwrap().as_ref().unwrap().next_id()
+    dynamic_generator().read().unwrap().as_ref().unwrap().next_id()
 }
 
 /// Use builtin default [`Generator`] `impl` instance [`SnowflakeGenerator::dynamic`]
@@ -162,10 +763,13 @@ pub fn dynamic_next_id() -> Result<u64, SnowflakeError> {
 /// ```rust
 /// use snowflaker::dynamic_next_id_string;
 ///
+/// # let dir = std::env::temp_dir().join(format!("snowflaker-doctest-{}", std::process::id()));
+/// # std::env::set_var("SNOWFLAKE_CACHE_DIR", &dir);
 /// let rvt = dynamic_next_id_string();
 /// assert!(rvt.is_ok());
+/// # let _ = std::fs::remove_dir_all(dir);
 /// ```
-#[cfg(feature = "dynamic")]
+#[cfg(all(feature = "dynamic", feature = "global"))]
 pub fn dynamic_next_id_string() -> Result<String, SnowflakeError> {
     dynamic_next_id().map(|v| v.to_string())
 }
\ No newline at end of file
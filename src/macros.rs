@@ -19,6 +19,7 @@
 /// Use the builtin default generator[`crate::generator::SnowflakeGenerator::builtin`].
 ///
 #[macro_export]
+#[cfg(feature = "global")]
 macro_rules! snowflake_builtin {
     () => {
         $crate::next_id()
@@ -28,6 +29,7 @@ macro_rules! snowflake_builtin {
 /// Use the builtin default generator[`super::generator::SnowflakeGenerator::builtin`].
 ///
 #[macro_export]
+#[cfg(feature = "global")]
 macro_rules! snowflake_builtin_string {
     () => {
         $crate::next_id_string()
@@ -37,7 +39,7 @@ macro_rules! snowflake_builtin_string {
 /// Use the builtin dynamic generator[`super::generator::SnowflakeGenerator::dynamic`] by features = [`"dynamic"`].
 ///
 #[macro_export]
-#[cfg(feature = "dynamic")]
+#[cfg(all(feature = "dynamic", feature = "global"))]
 macro_rules! snowflake_dynamic {
     () => {
         $crate::dynamic_next_id()
@@ -46,9 +48,42 @@ macro_rules! snowflake_dynamic {
 
 /// Use the builtin dynamic generator[`crate::generator::SnowflakeGenerator::dynamic`] by features = [`"dynamic"`].
 #[macro_export]
-#[cfg(feature = "dynamic")]
+#[cfg(all(feature = "dynamic", feature = "global"))]
 macro_rules! snowflake_dynamic_string {
     () => {
         $crate::dynamic_next_id_string()
     };
-}
\ No newline at end of file
+}
+/// Builds a custom generator[`crate::generator::SnowflakeGenerator::new`] inline,
+/// expands to `SnowflakeGenerator::new(center, worker)`.
+///
+/// ```rust
+/// let gen = snowflaker::snowflake_with!(center = 3, worker = 7);
+/// assert!(gen.is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[macro_export]
+macro_rules! snowflake_with {
+    (center = $center_id:expr, worker = $worker_id:expr $(,)?) => {
+        $crate::generator::SnowflakeGenerator::new($center_id, $worker_id)
+    };
+}
+
+/// Builds a custom generator[`crate::generator::SnowflakeGenerator::new`] inline and
+/// generates a unique ID in one expression, returns `Result<u64, SnowflakeError>`.
+///
+/// ```rust
+/// let rvt = snowflaker::snowflake_next!(center = 3, worker = 7);
+/// assert!(rvt.is_ok());
+/// ```
+///
+/// @since 0.4.0
+#[macro_export]
+#[cfg(feature = "std")]
+macro_rules! snowflake_next {
+    (center = $center_id:expr, worker = $worker_id:expr $(,)?) => {
+        $crate::snowflake_with!(center = $center_id, worker = $worker_id)
+            .and_then(|generator| $crate::generator::Generator::next_id(&generator))
+    };
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+/// The observability hooks of a [`crate::generator::SnowflakeGenerator`],
+/// see [`crate::generator::SnowflakeGenerator::with_metrics`].
+///
+/// All callbacks default to no-op, implementors override the ones they count,
+/// e.g. to export them to Prometheus. They are invoked on the generating thread,
+/// so they should be cheap, e.g. an atomic increment.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+///
+/// use snowflaker::generator::{Generator, SnowflakeGenerator};
+/// use snowflaker::metrics::Metrics;
+///
+/// #[derive(Default)]
+/// struct Counter(AtomicU64);
+///
+/// impl Metrics for Counter {
+///     fn on_id_generated(&self, _id: u64) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let counter = Arc::new(Counter::default());
+/// let gen = SnowflakeGenerator::with_metrics(1, 1, counter.clone()).unwrap();
+/// assert!(gen.next_id().is_ok());
+/// assert_eq!(1, counter.0.load(Ordering::Relaxed));
+/// # }
+/// ```
+///
+/// @since 0.4.0
+pub trait Metrics {
+    /// Invoked once per generated ID.
+    fn on_id_generated(&self, id: u64) {
+        let _ = id;
+    }
+
+    /// Invoked when the sequence of the `timestamp` is exhausted
+    /// and the generator waits for the next one.
+    fn on_sequence_rollover(&self, timestamp: u64) {
+        let _ = timestamp;
+    }
+
+    /// Invoked when the clock moved backwards by `delta_millis`
+    /// behind the last timestamp.
+    fn on_clock_backwards(&self, delta_millis: u64) {
+        let _ = delta_millis;
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! A process-global registry of the `(data-center ID, worker ID)` nodes in use,
+//! rejecting a second generator with an already-registered node.
+//!
+//! The check is opt-in per call: [`SnowflakeGenerator::new`], [`SnowflakeGenerator::dynamic`]
+//! and [`SnowflakeGenerator::builder`] never consult the registry, only the generators of
+//! [`SnowflakeGenerator::new_registered`] and [`crate::registry::register`] are checked against each other.
+
+// ----------------------------------------------------------------
+
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::generator::{SnowflakeError, SnowflakeGenerator};
+
+// ----------------------------------------------------------------
+
+lazy_static! {
+    static ref REGISTERED_NODES: Mutex<HashSet<(u64, u64)>> = Mutex::new(HashSet::new());
+}
+
+// ----------------------------------------------------------------
+
+/// [`RegisteredGenerator`] A [`SnowflakeGenerator`] holding its registered node,
+/// which is unregistered on drop.
+///
+/// It is not [`Clone`]: a clone would share the node of the original one.
+///
+/// @since 0.4.0
+#[derive(Debug)]
+pub struct RegisteredGenerator {
+    generator: SnowflakeGenerator,
+}
+
+impl RegisteredGenerator {
+    /// Returns the registered generator.
+    pub fn generator(&self) -> &SnowflakeGenerator {
+        &self.generator
+    }
+}
+
+impl Deref for RegisteredGenerator {
+    type Target = SnowflakeGenerator;
+
+    fn deref(&self) -> &Self::Target {
+        &self.generator
+    }
+}
+
+impl Drop for RegisteredGenerator {
+    fn drop(&mut self) {
+        unregister(self.generator.center_id(), self.generator.worker_id());
+    }
+}
+
+// ----------------------------------------------------------------
+
+/// Registers the node of the given generator, e.g. built by
+/// [`SnowflakeGenerator::dynamic`] or [`SnowflakeGenerator::builder`].
+///
+/// # Errors
+///
+/// - [`SnowflakeError::DuplicateNode`]: the node was already registered.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::generator::SnowflakeGenerator;
+/// use snowflaker::registry;
+///
+/// let gen = registry::register(SnowflakeGenerator::new(5, 17).unwrap());
+/// assert!(gen.is_ok());
+///
+/// let duplicate = registry::register(SnowflakeGenerator::new(5, 17).unwrap());
+/// assert!(duplicate.is_err());
+///
+/// drop(gen);
+/// assert!(!registry::is_registered(5, 17));
+/// ```
+///
+/// @since 0.4.0
+pub fn register(generator: SnowflakeGenerator) -> Result<RegisteredGenerator, SnowflakeError> {
+    let center_id = generator.center_id();
+    let worker_id = generator.worker_id();
+
+    let mut nodes = REGISTERED_NODES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if !nodes.insert((center_id, worker_id)) {
+        return Err(SnowflakeError::DuplicateNode(center_id, worker_id));
+    }
+
+    Ok(RegisteredGenerator { generator })
+}
+
+/// Returns `true` if the node is registered.
+///
+/// @since 0.4.0
+pub fn is_registered(center_id: u64, worker_id: u64) -> bool {
+    REGISTERED_NODES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .contains(&(center_id, worker_id))
+}
+
+fn unregister(center_id: u64, worker_id: u64) {
+    REGISTERED_NODES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .remove(&(center_id, worker_id));
+}
@@ -16,72 +16,3411 @@
 
 // ----------------------------------------------------------------
 
-use crate::generator::{Constants, Generator, SnowflakeGenerator};
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "global")]
+use std::sync::OnceLock;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::clock::{Clock, MonotonicClock, SystemClock};
+use crate::generator::{
+    mask, max_id, shift_for, AtomicOrdering, BitLayout, ClockBackwardsDecision, Constants,
+    DatacenterId, Generator, GeneratorState, OverflowPolicy, PackingOrder, SnowflakeConfig,
+    SnowflakeError, SnowflakeGenerator, SnowflakeGeneratorLocal, SpinStrategy, TimePrecision,
+    Timing, WaitStrategy, WorkerId,
+};
+use crate::generator128::{Constants128, IdParts128, SnowflakeGenerator128};
+use crate::identity::IdentityProvider;
+use crate::metrics::Metrics;
 
 use super::*;
 
 // ----------------------------------------------------------------
 
 #[test]
-fn test_bits() {
-    assert_eq!(31, Constants::MAX_DATA_CENTER_ID);
-    assert_eq!(31, Constants::MAX_WORKER_ID);
-    assert_eq!(4095, Constants::SEQUENCE_MASK);
+fn test_bits() {
+    assert_eq!(31, Constants::MAX_DATA_CENTER_ID);
+    assert_eq!(31, Constants::MAX_WORKER_ID);
+    assert_eq!(4095, Constants::SEQUENCE_MASK);
+
+    assert_eq!(12, Constants::WORKER_ID_SHIFT);
+    assert_eq!(17, Constants::CENTER_ID_SHIFT);
+    assert_eq!(22, Constants::TIMESTAMP_SHIFT);
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_next_id() {
+    // 122235238222008321
+    let rvt = next_id();
+    assert!(rvt.is_ok());
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_init_then_next_id() {
+    let instance = OnceLock::new();
+
+    let rvt = install(&instance, SnowflakeGenerator::new(3, 7).unwrap());
+    assert!(rvt.is_ok());
+
+    let id = instance.get().unwrap().next_id().unwrap();
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(3, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_init_twice() {
+    let instance = OnceLock::new();
+
+    let rvt = install(&instance, SnowflakeGenerator::new(3, 7).unwrap());
+    assert!(rvt.is_ok());
+
+    let rvt = install(&instance, SnowflakeGenerator::new(3, 8).unwrap());
+    assert!(matches!(rvt, Err(SnowflakeError::AlreadyInitialized)));
+    assert_eq!(7, instance.get().unwrap().worker_id());
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_init_after_next_id() {
+    assert!(next_id().is_ok());
+
+    let rvt = init(3, 7);
+    assert!(matches!(rvt, Err(SnowflakeError::AlreadyInitialized)));
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_next_id_string() {
+    // 122256588529602560
+    let rvt = next_id_string();
+    assert!(rvt.is_ok());
+}
+
+#[test]
+fn test_generator_new_failed() {
+    let gen = SnowflakeGenerator::new(32, 32);
+    assert!(gen.is_err());
+}
+
+#[test]
+fn test_generator_new_ok() {
+    let gen = SnowflakeGenerator::new(31, 31);
+    assert!(gen.is_ok());
+}
+
+#[test]
+fn test_generator_new_worker_id_invalid() {
+    let gen = SnowflakeGenerator::new(1, 32);
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+
+    let gen = SnowflakeGenerator::new(1, 40);
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+}
+
+#[test]
+fn test_generator_new_center_id_invalid() {
+    let gen = SnowflakeGenerator::new(32, 1);
+    assert!(matches!(gen, Err(SnowflakeError::CenterIdInvalid)));
+}
+
+#[test]
+fn test_generator_getters() {
+    let gen = SnowflakeGenerator::new(12, 7).unwrap();
+    assert_eq!(12, gen.center_id());
+    assert_eq!(7, gen.worker_id());
+    assert_eq!(Constants::EPOCH, gen.epoch());
+}
+
+#[test]
+fn test_generator_builtin_ok() {
+    let gen = SnowflakeGenerator::builtin();
+    assert!(gen.is_ok());
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_try_next_id() {
+    assert!(try_next_id().is_ok());
+
+    // lazily falls back to the builtin generator
+    let instance = OnceLock::new();
+    assert!(try_generator_of(&instance).unwrap().next_id().is_ok());
+    assert!(instance.get().is_some());
+
+    // the installed one is kept
+    let instance = OnceLock::new();
+    install(&instance, SnowflakeGenerator::new(3, 7).unwrap()).unwrap();
+    assert_eq!(7, try_generator_of(&instance).unwrap().worker_id());
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_try_next_id_after_panic() {
+    static INSTANCE: OnceLock<SnowflakeGenerator> = OnceLock::new();
+    install(&INSTANCE, SnowflakeGenerator::new(3, 7).unwrap()).unwrap();
+
+    // no lock to poison
+    let rvt = thread::spawn(|| {
+        let _id = INSTANCE.get().unwrap().next_id().unwrap();
+        panic!("panics while using the generator");
+    })
+    .join();
+    assert!(rvt.is_err());
+
+    assert!(try_generator_of(&INSTANCE).unwrap().next_id().is_ok());
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_next_id_contended() {
+    let started = Instant::now();
+    let handles: Vec<_> = (0..8)
+        .map(|_| thread::spawn(|| (0..10_000).map(|_| next_id().unwrap()).collect::<Vec<_>>()))
+        .collect();
+
+    let mut ids = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            assert!(ids.insert(id));
+        }
+    }
+    assert_eq!(80_000, ids.len());
+
+    // capped by the 4096 IDs per millisecond: ~20ms, not serialized beyond it
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_generator_next_id() {
+    // 122235451737247745
+    // 122_235_451_737_247_745 -> 18
+    let rvt = generator().next_id();
+    assert!(rvt.is_ok());
+}
+
+#[test]
+fn test_custom_new_next_id() {
+    let center_id = 16;
+    let worker_id = 16;
+
+    let gen = SnowflakeGenerator::new(center_id, worker_id);
+    assert!(gen.is_ok());
+    let rvt = gen.unwrap().next_id();
+    assert!(rvt.is_ok());
+}
+
+#[test]
+fn test_generator_new_clamped() {
+    let gen = SnowflakeGenerator::new_clamped(40, 33);
+    assert_eq!(40 & 31, gen.center_id());
+    assert_eq!(33 & 31, gen.worker_id());
+    assert!(gen.next_id().is_ok());
+
+    let mut warnings = Vec::new();
+    let gen =
+        SnowflakeGenerator::new_clamped_with(3, 7, |warning| warnings.push(warning.to_string()));
+    assert_eq!(3, gen.center_id());
+    assert_eq!(7, gen.worker_id());
+    assert!(warnings.is_empty());
+
+    let _ =
+        SnowflakeGenerator::new_clamped_with(3, 33, |warning| warnings.push(warning.to_string()));
+    assert_eq!(1, warnings.len());
+    assert!(warnings[0].contains("33"));
+}
+
+#[test]
+fn test_generator_default() {
+    let gen: SnowflakeGenerator = Default::default();
+    assert_eq!(Constants::DEFAULT_DATA_CENTER_ID, gen.center_id());
+    assert_eq!(Constants::DEFAULT_WORKER_ID, gen.worker_id());
+
+    let rvt = gen.next_id();
+    assert!(rvt.is_ok());
+}
+
+#[test]
+// the `Hash` impl ignores the interior mutable runtime state
+#[allow(clippy::mutable_key_type)]
+fn test_generator_eq_hash_by_config() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    let other = SnowflakeGenerator::new(3, 7).unwrap();
+    assert!(other.next_id().is_ok());
+
+    // the runtime state is ignored
+    assert_eq!(gen, other);
+    assert_eq!(gen, gen.clone());
+    assert_ne!(gen, SnowflakeGenerator::new(3, 8).unwrap());
+
+    let mut set = HashSet::new();
+    set.insert(gen);
+    set.insert(other);
+    assert_eq!(1, set.len());
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)]
+fn test_generator_eq_hash_by_layout_and_precision() {
+    // the same IDs and epoch, incompatible formats
+    let compact = SnowflakeGenerator::compact(7).unwrap();
+    let default = SnowflakeGenerator::builder()
+        .center_id(0)
+        .worker_id(7)
+        .epoch(Constants::COMPACT_EPOCH)
+        .build()
+        .unwrap();
+    assert_eq!(
+        (compact.center_id(), compact.worker_id(), compact.epoch()),
+        (default.center_id(), default.worker_id(), default.epoch())
+    );
+    assert_ne!(compact, default);
+
+    let seconds = SnowflakeGenerator::builder()
+        .center_id(0)
+        .worker_id(7)
+        .epoch(Constants::COMPACT_EPOCH)
+        .precision(TimePrecision::Seconds)
+        .build()
+        .unwrap();
+    assert_ne!(default, seconds);
+
+    let mut set = HashSet::new();
+    set.insert(compact);
+    set.insert(default);
+    set.insert(seconds);
+    assert_eq!(3, set.len());
+}
+
+// ---------------------------------------------------------------- identity
+
+struct FixedIdentityProvider {
+    center_id: u64,
+    worker_id: u64,
+}
+
+impl IdentityProvider for FixedIdentityProvider {
+    fn datacenter_id(&self) -> Result<u64, SnowflakeError> {
+        Ok(self.center_id)
+    }
+
+    fn worker_id(&self, _datacenter_id: u64) -> Result<u64, SnowflakeError> {
+        Ok(self.worker_id)
+    }
+}
+
+#[test]
+fn test_generator_with_identity() {
+    let provider = FixedIdentityProvider {
+        center_id: 3,
+        worker_id: 7,
+    };
+
+    let gen = SnowflakeGenerator::with_identity(&provider).unwrap();
+    assert_eq!(3, gen.center_id());
+    assert_eq!(7, gen.worker_id());
+
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(7, parts.worker_id);
+}
+
+#[test]
+fn test_generator_with_identity_invalid() {
+    let provider = FixedIdentityProvider {
+        center_id: 3,
+        worker_id: 32,
+    };
+
+    let gen = SnowflakeGenerator::with_identity(&provider);
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+}
+
+// ---------------------------------------------------------------- epoch
+
+#[test]
+fn test_generator_with_epoch() {
+    let epoch = SnowflakeGenerator::time_gen().unwrap() - 1000;
+
+    let gen = SnowflakeGenerator::with_epoch(1, 1, epoch).unwrap();
+    let id = gen.next_id().unwrap();
+
+    let parts = SnowflakeGenerator::decompose(id);
+    assert!(parts.timestamp >= 1000);
+    assert!(parts.timestamp < 1000 * 60);
+}
+
+#[test]
+fn test_generator_with_epoch_in_future() {
+    let epoch = SnowflakeGenerator::time_gen().unwrap() + 60 * 1000;
+
+    let gen = SnowflakeGenerator::with_epoch(1, 1, epoch);
+    assert!(matches!(gen, Err(SnowflakeError::EpochInvalid)));
+}
+
+// ---------------------------------------------------------------- error
+
+#[test]
+fn test_error_from_system_time_error() {
+    let err = UNIX_EPOCH
+        .duration_since(UNIX_EPOCH + Duration::from_millis(5))
+        .unwrap_err();
+
+    let err = SnowflakeError::from(err);
+    assert!(matches!(
+        err,
+        SnowflakeError::SystemTimeError(duration) if duration == Duration::from_millis(5)
+    ));
+    assert_eq!(
+        "SystemTime before UNIX EPOCH! (5ms behind)",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_error_boxed() {
+    fn boxed() -> Result<u64, Box<dyn std::error::Error>> {
+        Ok(SnowflakeGenerator::new(1, 32)?.next_id()?)
+    }
+
+    let err = boxed().unwrap_err();
+    assert_eq!("Worker ID out of range", err.to_string());
+}
+
+// ---------------------------------------------------------------- builder
+
+#[test]
+fn test_builder_build_ok() {
+    let gen = SnowflakeGenerator::builder()
+        .center_id(3)
+        .worker_id(7)
+        .epoch(Constants::EPOCH)
+        .build();
+    assert!(gen.is_ok());
+
+    let parts = SnowflakeGenerator::decompose(gen.unwrap().next_id().unwrap());
+    assert_eq!(3, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+}
+
+#[test]
+fn test_builder_defaults() {
+    let gen = SnowflakeGenerator::builder().build().unwrap();
+
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(Constants::DEFAULT_DATA_CENTER_ID, parts.center_id);
+    assert_eq!(Constants::DEFAULT_WORKER_ID, parts.worker_id);
+}
+
+#[test]
+fn test_builder_worker_id_invalid() {
+    let gen = SnowflakeGenerator::builder()
+        .center_id(1)
+        .worker_id(32)
+        .build();
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+}
+
+// ---------------------------------------------------------------- multi-thread
+
+#[test]
+fn test_multi_thread_clones_unique() {
+    let generator = SnowflakeGenerator::builtin().unwrap();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let generator = generator.clone();
+            thread::spawn(move || {
+                (0..100_000)
+                    .map(|_| generator.next_id().unwrap())
+                    .collect::<Vec<u64>>()
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            assert!(ids.insert(id), "duplicate id: {}", id);
+        }
+    }
+
+    assert_eq!(8 * 100_000, ids.len());
+}
+
+#[test]
+fn test_multi_thread_acquire_release_unique() {
+    let generator = SnowflakeGenerator::builder()
+        .ordering(AtomicOrdering::AcquireRelease)
+        .build()
+        .unwrap();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let generator = generator.clone();
+            thread::spawn(move || {
+                (0..100_000)
+                    .map(|_| generator.next_id().unwrap())
+                    .collect::<Vec<u64>>()
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    for handle in handles {
+        let thread_ids = handle.join().unwrap();
+        assert!(thread_ids.windows(2).all(|pair| pair[0] < pair[1]));
+        for id in thread_ids {
+            assert!(ids.insert(id), "duplicate id: {}", id);
+        }
+    }
+
+    assert_eq!(8 * 100_000, ids.len());
+}
+
+#[test]
+fn test_multi_thread_clones_monotonic() {
+    let generator = SnowflakeGenerator::builtin().unwrap();
+
+    // The `debug_assertions` check inside `next_id` panics if the shared ordering regresses.
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let generator = generator.clone();
+            thread::spawn(move || {
+                (0..100_000)
+                    .map(|_| generator.next_id().unwrap())
+                    .collect::<Vec<u64>>()
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let ids = handle.join().unwrap();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "snowflake id regressed")]
+fn test_monotonic_assertion_trips() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let now = Constants::EPOCH + 1000;
+
+    let id = gen.next_id_with_timestamp(now).unwrap();
+    // A regressed id since the last emitted one has a later sequence.
+    gen.debug_assert_monotonic(now, 8, id);
+}
+
+/// Serializes the tests of the thread-local generators, they share the pool of the `worker` IDs.
+#[cfg(feature = "global")]
+static THREAD_LOCAL_TESTS: Mutex<()> = Mutex::new(());
+
+#[cfg(feature = "global")]
+#[test]
+fn test_thread_local_next_id_unique() {
+    let _serial = THREAD_LOCAL_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+    let barrier = Arc::new(std::sync::Barrier::new(8));
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                let ids = (0..100_000)
+                    .map(|_| thread_local_next_id().unwrap())
+                    .collect::<Vec<u64>>();
+                // all live at once, none gives its worker ID back to another
+                barrier.wait();
+                ids
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    let mut worker_ids = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            worker_ids.insert(SnowflakeGenerator::decompose(id).worker_id);
+            assert!(ids.insert(id), "duplicate id: {}", id);
+        }
+    }
+
+    assert_eq!(8 * 100_000, ids.len());
+    assert_eq!(8, worker_ids.len());
+    // never the one of the builtin generator
+    assert!(!worker_ids.contains(&Constants::DEFAULT_WORKER_ID));
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_thread_local_next_id_churn() {
+    let _serial = THREAD_LOCAL_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ids = HashSet::new();
+    let mut worker_ids = HashSet::new();
+
+    // the exited threads give their worker IDs back
+    for _ in 0..100 {
+        let batch = thread::spawn(|| {
+            (0..1_000)
+                .map(|_| thread_local_next_id().unwrap())
+                .collect::<Vec<u64>>()
+        })
+        .join()
+        .unwrap();
+
+        for id in batch {
+            worker_ids.insert(SnowflakeGenerator::decompose(id).worker_id);
+            assert!(ids.insert(id), "duplicate id: {}", id);
+        }
+    }
+
+    // one thread at a time, reusing the same worker ID
+    assert_eq!(1, worker_ids.len());
+    assert!(!worker_ids.contains(&Constants::DEFAULT_WORKER_ID));
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_thread_local_next_id_beyond_capacity() {
+    let _serial = THREAD_LOCAL_TESTS.lock().unwrap_or_else(|e| e.into_inner());
+    let threads = 2 * Constants::MAX_WORKER_ID as usize;
+    let barrier = Arc::new(std::sync::Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                // all live at once
+                barrier.wait();
+                let ids = (0..10_000)
+                    .map(|_| thread_local_next_id().unwrap())
+                    .collect::<Vec<u64>>();
+                barrier.wait();
+                ids
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            // the threads beyond the capacity share the generators
+            assert!(ids.insert(id), "duplicate id: {}", id);
+        }
+    }
+
+    assert_eq!(threads * 10_000, ids.len());
+}
+
+// ---------------------------------------------------------------- timestamp
+
+#[test]
+fn test_next_id_with_timestamp() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let now = Constants::EPOCH + 1000;
+
+    let first = gen.next_id_with_timestamp(now).unwrap();
+    let second = gen.next_id_with_timestamp(now).unwrap();
+    assert!(second > first);
+    assert_eq!(1000, SnowflakeGenerator::decompose(second).timestamp);
+    assert_eq!(1, SnowflakeGenerator::decompose(second).sequence);
+
+    assert!(matches!(
+        gen.next_id_with_timestamp(now - 1),
+        Err(SnowflakeError::ClockMovedBackwards)
+    ));
+}
+
+#[test]
+fn test_next_id_with_timestamp_sequence_exhausted() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let now = Constants::EPOCH + 1000;
+
+    for _ in 0..=Constants::SEQUENCE_MASK {
+        assert!(gen.next_id_with_timestamp(now).is_ok());
+    }
+
+    assert!(matches!(
+        gen.next_id_with_timestamp(now),
+        Err(SnowflakeError::SequenceExhausted)
+    ));
+    assert!(gen.next_id_with_timestamp(now + 1).is_ok());
+}
+
+#[test]
+fn test_next_id_timestamp_overflow() {
+    let layout = BitLayout::default();
+    assert_eq!(41, layout.timestamp_bits());
+
+    let max = Constants::EPOCH + layout.max_timestamp();
+    let clock = MockClock::new(&[max, max + 1]);
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+
+    let id = gen.next_id().unwrap();
+    assert_eq!(max, SnowflakeGenerator::timestamp_of(id));
+    assert!(id < i64::MAX as u64);
+
+    let rvt = gen.next_id();
+    assert!(matches!(rvt, Err(SnowflakeError::TimestampOverflow)));
+}
+
+#[test]
+fn test_next_id_timestamp_overflow_state() {
+    // 51 timestamp bits, wider than the 42 bits of the packed state
+    let gen = SnowflakeGenerator::builder()
+        .center_id(0)
+        .worker_id(0)
+        .epoch(0)
+        .layout(BitLayout::new(0, 0, 12).unwrap())
+        .build()
+        .unwrap();
+
+    let max = (1 << 42) - 1;
+    assert!(gen.next_id_with_timestamp(max).is_ok());
+    assert!(gen.next_id_with_timestamp(max).is_ok());
+
+    let rvt = gen.next_id_with_timestamp((1 << 42) + 5);
+    assert!(matches!(rvt, Err(SnowflakeError::TimestampOverflow)));
+    let rvt = gen.next_id_with_timestamp((1 << 42) + 5);
+    assert!(matches!(rvt, Err(SnowflakeError::TimestampOverflow)));
+
+    let valid_until = gen.layout_info().valid_until;
+    assert_eq!(UNIX_EPOCH + Duration::from_millis(max), valid_until);
+}
+
+#[test]
+fn test_next_id_with_parts() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    let generated = gen.next_id_with_parts().unwrap();
+    assert_eq!(gen.center_id(), generated.center_id);
+    assert_eq!(gen.worker_id(), generated.worker_id);
+
+    let parts = SnowflakeGenerator::decompose(generated.id);
+    assert_eq!(generated.center_id, parts.center_id);
+    assert_eq!(generated.worker_id, parts.worker_id);
+    assert_eq!(generated.sequence, parts.sequence);
+    assert_eq!(generated.timestamp_millis, parts.unix_millis());
+
+    let reconstructed = (parts.timestamp << Constants::TIMESTAMP_SHIFT)
+        | (parts.center_id << Constants::CENTER_ID_SHIFT)
+        | (parts.worker_id << Constants::WORKER_ID_SHIFT)
+        | parts.sequence;
+    assert_eq!(generated.id, reconstructed);
+
+    let next = gen.next_id_with_parts().unwrap();
+    assert!(next.id > generated.id);
+}
+
+// ---------------------------------------------------------------- validate
+
+#[test]
+fn test_generator_validate() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    let id = gen.next_id().unwrap();
+    assert!(gen.is_valid(id));
+    assert!(gen.validate(id).is_ok());
+
+    let other = SnowflakeGenerator::new(3, 8).unwrap().next_id().unwrap();
+    assert!(!gen.is_valid(other));
+    assert!(matches!(
+        gen.validate(other),
+        Err(SnowflakeError::WorkerIdInvalid)
+    ));
+
+    let other = SnowflakeGenerator::new(4, 7).unwrap().next_id().unwrap();
+    assert!(matches!(
+        gen.validate(other),
+        Err(SnowflakeError::CenterIdInvalid)
+    ));
+}
+
+#[test]
+fn test_generator_validate_future_timestamp() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    let future = SnowflakeGenerator::time_gen().unwrap() + 60 * 1000 - Constants::EPOCH;
+    let id = (future << Constants::TIMESTAMP_SHIFT)
+        | (3 << Constants::CENTER_ID_SHIFT)
+        | (7 << Constants::WORKER_ID_SHIFT);
+    assert!(!gen.is_valid(id));
+    assert!(matches!(
+        gen.validate(id),
+        Err(SnowflakeError::TimestampInvalid)
+    ));
+
+    let id = (1 << 63) | (3 << Constants::CENTER_ID_SHIFT) | (7 << Constants::WORKER_ID_SHIFT);
+    assert!(matches!(
+        gen.validate(id),
+        Err(SnowflakeError::TimestampInvalid)
+    ));
+}
+
+#[test]
+fn test_generator_validate_timestamp_overflow() {
+    // the `58` timestamp bits in seconds overflow the millis
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::new(0, 0, 5).unwrap())
+        .precision(TimePrecision::Seconds)
+        .center_id(0)
+        .worker_id(0)
+        .build()
+        .unwrap();
+
+    assert!(gen.is_valid(gen.next_id().unwrap()));
+    assert!(matches!(
+        gen.validate(u64::MAX >> 1),
+        Err(SnowflakeError::TimestampInvalid)
+    ));
+}
+
+// ---------------------------------------------------------------- spin
+
+#[test]
+fn test_sequence_exhausted_spin_strategies() {
+    for strategy in [SpinStrategy::Yield, SpinStrategy::Hint, SpinStrategy::Busy] {
+        let gen = SnowflakeGenerator::builder()
+            .spin_strategy(strategy)
+            .build()
+            .unwrap();
+
+        let first = gen.next_id().unwrap();
+        gen.set_sequence(Constants::SEQUENCE_MASK);
+
+        let second = gen.next_id().unwrap();
+        assert!(second > first);
+
+        let (first, second) = (
+            SnowflakeGenerator::decompose(first),
+            SnowflakeGenerator::decompose(second),
+        );
+        assert!(second.timestamp > first.timestamp);
+        assert_eq!(0, second.sequence);
+    }
+}
+
+#[test]
+fn test_til_next_millis() {
+    let now = SnowflakeGenerator::time_gen().unwrap();
+    let next = SnowflakeGenerator::til_next_millis(now).unwrap();
+    assert!(next > now);
+}
+
+#[test]
+fn test_wait_next_millis() {
+    let now = generator::now_millis().unwrap();
+    assert!(now > Constants::EPOCH);
+
+    for _ in 0..10 {
+        let now = generator::now_millis().unwrap();
+        let next = generator::wait_next_millis(now).unwrap();
+        assert!(next > now);
+        assert!(generator::now_millis().unwrap() >= next);
+    }
+
+    // already passed
+    assert!(generator::wait_next_millis(0).unwrap() > 0);
+}
+
+/// A [`Generator`] with a distinct type, to be boxed beside a [`SnowflakeGenerator`].
+struct CountingGenerator(AtomicU64);
+
+impl Generator for CountingGenerator {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        Ok(self.0.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+#[test]
+fn test_boxed_generators() {
+    let generators: Vec<Box<dyn Generator>> = vec![
+        Box::new(SnowflakeGenerator::new(3, 7).unwrap()),
+        Box::new(CountingGenerator(AtomicU64::new(42))),
+    ];
+
+    let ids: Vec<u64> = generators
+        .iter()
+        .map(|gen| gen.next_id().unwrap())
+        .collect();
+    assert_eq!(7, SnowflakeGenerator::decompose(ids[0]).worker_id);
+    assert_eq!(42, ids[1]);
+}
+
+// ---------------------------------------------------------------- precision
+
+#[test]
+fn test_time_precision() {
+    assert_eq!(TimePrecision::Millis, TimePrecision::default());
+    assert_eq!(1000, TimePrecision::Seconds.unit_millis());
+    assert_eq!(12_000, TimePrecision::Seconds.truncate(12_345));
+    assert_eq!(12_345, TimePrecision::Millis.truncate(12_345));
+
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    assert_eq!(TimePrecision::Millis, gen.precision());
+}
+
+#[test]
+fn test_next_id_with_timestamp_seconds() {
+    let gen = SnowflakeGenerator::builder()
+        .precision(TimePrecision::Seconds)
+        .build()
+        .unwrap();
+    let now = Constants::EPOCH + 5_000;
+
+    let first = gen.next_id_with_timestamp(now).unwrap();
+    let second = gen.next_id_with_timestamp(now + 999).unwrap();
+    assert_eq!(5, SnowflakeGenerator::decompose(first).timestamp);
+    assert_eq!(5, SnowflakeGenerator::decompose(second).timestamp);
+    assert_eq!(1, SnowflakeGenerator::decompose(second).sequence);
+
+    let third = gen.next_id_with_timestamp(now + 1_000).unwrap();
+    assert_eq!(6, SnowflakeGenerator::decompose(third).timestamp);
+    assert_eq!(0, SnowflakeGenerator::decompose(third).sequence);
+}
+
+#[test]
+fn test_seconds_sequence_exhausted_waits_next_second() {
+    let gen = SnowflakeGenerator::builder()
+        .precision(TimePrecision::Seconds)
+        .build()
+        .unwrap();
+
+    let first = gen.next_id().unwrap();
+    gen.set_sequence(Constants::SEQUENCE_MASK);
+
+    let start = Instant::now();
+    let second = gen.next_id().unwrap();
+    let elapsed = start.elapsed();
+
+    assert!(second > first);
+    assert_eq!(
+        SnowflakeGenerator::decompose(first).timestamp + 1,
+        SnowflakeGenerator::decompose(second).timestamp
+    );
+    assert_eq!(0, SnowflakeGenerator::decompose(second).sequence);
+    assert!(elapsed <= Duration::from_millis(1_100));
+
+    let ids: Vec<u64> = (0..1_000).map(|_| gen.next_id().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(ids[0] > second);
+}
+
+// ---------------------------------------------------------------- layout
+
+#[test]
+fn test_bit_layout_default() {
+    let layout = BitLayout::default();
+    assert!(layout.validate().is_ok());
+
+    assert_eq!(Constants::MAX_DATA_CENTER_ID, layout.max_data_center_id());
+    assert_eq!(Constants::MAX_WORKER_ID, layout.max_worker_id());
+    assert_eq!(Constants::SEQUENCE_MASK, layout.sequence_mask());
+    assert_eq!(Constants::WORKER_ID_SHIFT, layout.worker_id_shift());
+    assert_eq!(Constants::CENTER_ID_SHIFT, layout.center_id_shift());
+    assert_eq!(Constants::TIMESTAMP_SHIFT, layout.timestamp_shift());
+}
+
+#[test]
+fn test_bit_layout_const() {
+    const LAYOUT: BitLayout = BitLayout::new_const(0, 10, 12);
+    const MAX_WORKER_ID: u64 = LAYOUT.max_worker_id();
+    const TIMESTAMP_SHIFT: u64 = LAYOUT.timestamp_shift();
+
+    assert_eq!(1023, MAX_WORKER_ID);
+    assert_eq!(22, TIMESTAMP_SHIFT);
+    assert_eq!(BitLayout::new(0, 10, 12).unwrap(), LAYOUT);
+}
+
+#[test]
+fn test_bit_layout_new() {
+    let layout = BitLayout::new(5, 5, 12).unwrap();
+    assert_eq!(BitLayout::default(), layout);
+
+    // no data-center field
+    let layout = BitLayout::new(0, 10, 12).unwrap();
+    assert_eq!(0, layout.max_data_center_id());
+    assert_eq!(1023, layout.max_worker_id());
+}
+
+#[test]
+fn test_layout_info() {
+    let info = SnowflakeGenerator::new(1, 1).unwrap().layout_info();
+    assert_eq!(4096, info.ids_per_millisecond);
+    assert_eq!(32, info.max_workers);
+    assert_eq!(32, info.max_datacenters);
+
+    // 41 timestamp bits for about 69 years since the epoch in 2023
+    let secs = info
+        .valid_until
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let year = 1970 + secs / (365 * 24 * 60 * 60 + 6 * 60 * 60);
+    assert_eq!(2092, year);
+
+    let gen = SnowflakeGenerator::builder()
+        .center_id(0)
+        .worker_id(1)
+        .layout(BitLayout::new(0, 10, 12).unwrap())
+        .build()
+        .unwrap();
+    let info = gen.layout_info();
+    assert_eq!(1024, info.max_workers);
+    assert_eq!(1, info.max_datacenters);
+}
+
+#[test]
+fn test_bit_layout_invalid() {
+    // sums to 24
+    let rvt = BitLayout::new(6, 6, 12);
+    assert!(matches!(rvt, Err(SnowflakeError::LayoutInvalid(_))));
+    assert_eq!(
+        "Bit layout out of range: data-center(6) + worker(6) + sequence(12) = 24 bits, exceeds 22 by 2",
+        rvt.unwrap_err().to_string()
+    );
+
+    // never wraps
+    let rvt = BitLayout::new(u64::MAX, 1, 1);
+    assert!(matches!(rvt, Err(SnowflakeError::LayoutInvalid(_))));
+
+    let rvt = std::panic::catch_unwind(|| BitLayout::new_const(6, 6, 12));
+    assert!(rvt.is_err());
+
+    let rvt = BitLayout::default().with_tag_bits(13).validate();
+    assert_eq!(
+        "Bit layout out of range: tag(13) exceeds sequence(12) by 1",
+        rvt.unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_custom_layout_round_trip() {
+    let layout = BitLayout::new(0, 10, 12).unwrap();
+    let gen = SnowflakeGenerator::builder()
+        .center_id(0)
+        .worker_id(1000)
+        .layout(layout)
+        .build()
+        .unwrap();
+
+    for rvt in gen.iter().take(100) {
+        let id = rvt.unwrap();
+        let parts = layout.decompose(id);
+        assert_eq!(0, parts.center_id);
+        assert_eq!(1000, parts.worker_id);
+        assert_eq!(
+            id,
+            layout.compose(
+                parts.timestamp,
+                parts.center_id,
+                parts.worker_id,
+                parts.sequence
+            )
+        );
+    }
+}
+
+#[test]
+fn test_generator_effective_maximums() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    assert_eq!(Constants::MAX_DATA_CENTER_ID, gen.max_datacenter_id());
+    assert_eq!(Constants::MAX_WORKER_ID, gen.max_worker_id());
+    assert_eq!(Constants::SEQUENCE_MASK, gen.max_sequence());
+
+    let gen = SnowflakeGenerator::builder()
+        .center_id(0)
+        .layout(BitLayout::new(2, 8, 10).unwrap())
+        .build()
+        .unwrap();
+    assert_eq!((1 << 2) - 1, gen.max_datacenter_id());
+    assert_eq!((1 << 8) - 1, gen.max_worker_id());
+    assert_eq!((1 << 10) - 1, gen.max_sequence());
+}
+
+#[test]
+fn test_custom_layout_id_invalid() {
+    let layout = BitLayout::new(0, 10, 12).unwrap();
+
+    let gen = SnowflakeGenerator::builder()
+        .center_id(1)
+        .layout(layout)
+        .build();
+    assert!(matches!(gen, Err(SnowflakeError::CenterIdInvalid)));
+
+    let gen = SnowflakeGenerator::builder()
+        .center_id(0)
+        .worker_id(1024)
+        .layout(layout)
+        .build();
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+}
+
+// ---------------------------------------------------------------- iter
+
+#[test]
+fn test_generator_iter() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+
+    let ids: Vec<Result<u64, SnowflakeError>> = gen.iter().take(5).collect();
+    assert_eq!(5, ids.len());
+    assert!(ids.iter().all(|rvt| rvt.is_ok()));
+
+    let ids: Vec<u64> = ids.into_iter().map(|rvt| rvt.unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+// ---------------------------------------------------------------- clock
+
+/// A [`Clock`] returns the scripted timestamps in order, then repeats the last one.
+struct MockClock {
+    timestamps: Mutex<VecDeque<u64>>,
+}
+
+impl MockClock {
+    fn new(timestamps: &[u64]) -> Self {
+        Self {
+            timestamps: Mutex::new(timestamps.iter().copied().collect()),
+        }
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        let mut timestamps = self.timestamps.lock().unwrap();
+        if timestamps.len() > 1 {
+            return Ok(timestamps.pop_front().unwrap());
+        }
+
+        Ok(*timestamps.front().unwrap())
+    }
+}
+
+/// A [`Clock`] always fails as if the `SystemTime` was before the UNIX EPOCH.
+struct FailingClock;
+
+impl Clock for FailingClock {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        Err(SnowflakeError::SystemTimeError(Duration::from_millis(1)))
+    }
+}
+
+/// A [`Clock`] fails the first `failures` reads like [`FailingClock`], then reads the [`SystemClock`].
+struct FlakyClock {
+    failures: AtomicU64,
+}
+
+impl Clock for FlakyClock {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        if self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return FailingClock.now_millis();
+        }
+
+        SystemClock.now_millis()
+    }
+}
+
+#[test]
+fn test_generator_clock_failed() {
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(FailingClock)).unwrap();
+
+    let rvt = gen.next_id();
+    assert!(matches!(rvt, Err(SnowflakeError::SystemTimeError(_))));
+}
+
+/// A [`Clock`] returns the given timestamp once and then fails.
+struct FailingAfterOnceClock {
+    timestamp: Mutex<Option<u64>>,
+}
+
+impl Clock for FailingAfterOnceClock {
+    fn now_millis(&self) -> Result<u64, SnowflakeError> {
+        self.timestamp
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or(SnowflakeError::SystemTimeError(Duration::from_millis(1)))
+    }
+}
+
+#[test]
+fn test_generator_clock_failed_while_waiting() {
+    let now = Constants::EPOCH + 1000;
+    let clock = FailingAfterOnceClock {
+        timestamp: Mutex::new(Some(now)),
+    };
+
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    let _ = gen.next_id_with_timestamp(now).unwrap();
+    gen.set_sequence(Constants::SEQUENCE_MASK);
+
+    // the sequence exhausted, then the clock fails while waiting for the next millis
+    let rvt = gen.next_id();
+    assert!(matches!(rvt, Err(SnowflakeError::SystemTimeError(_))));
+}
+
+#[test]
+fn test_next_id_retry() {
+    let flaky = |failures| {
+        let clock = FlakyClock {
+            failures: AtomicU64::new(failures),
+        };
+        SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap()
+    };
+
+    // fails twice, then succeeds
+    let gen = flaky(2);
+    assert!(gen.next_id_retry(3, Duration::from_millis(1)).is_ok());
+
+    let gen = flaky(3);
+    let rvt = gen.next_id_retry(3, Duration::from_millis(1));
+    assert!(matches!(rvt, Err(SnowflakeError::SystemTimeError(_))));
+    assert!(gen.next_id().is_ok());
+
+    // at least once
+    assert!(flaky(0).next_id_retry(0, Duration::ZERO).is_ok());
+
+    // the permanent errors are not retried
+    let rvt = SnowflakeGenerator::new_with_clock(
+        1,
+        1,
+        Arc::new(MockClock::new(&[Constants::EPOCH - 1000])),
+    )
+    .unwrap()
+    .next_id_retry(3, Duration::from_secs(10));
+    assert_eq!(SnowflakeError::ClockBeforeEpoch, rvt.unwrap_err());
+}
+
+#[test]
+fn test_self_test() {
+    assert!(SnowflakeGenerator::new(3, 7).unwrap().self_test().is_ok());
+
+    let layout = BitLayout::default()
+        .with_tag_bits(4)
+        .with_packing_order(PackingOrder::SequenceHigh);
+    let gen = SnowflakeGenerator::builder()
+        .layout(layout)
+        .build()
+        .unwrap();
+    assert!(gen.self_test().is_ok());
+
+    let clock = MockClock::new(&[Constants::EPOCH - 1000]);
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    assert_eq!(
+        SnowflakeError::ClockBeforeEpoch,
+        gen.self_test().unwrap_err()
+    );
+}
+
+#[test]
+fn test_next_id_at() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    // 2024-01-01 00:00:00 UTC
+    let created_at = 1704067200000;
+    let ids: Vec<u64> = (0..10)
+        .map(|_| gen.next_id_at(created_at).unwrap())
+        .collect();
+    assert!(verify::verify_unique(&ids).is_ok());
+    for id in &ids {
+        assert_eq!(created_at, SnowflakeGenerator::timestamp_of(*id));
+    }
+    assert!(gen.next_id_at(created_at + 1).is_ok());
+
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+    let rvt = gen.next_id_at(now + 60_000);
+    assert_eq!(SnowflakeError::TimestampInvalid, rvt.unwrap_err());
+
+    let rvt = SnowflakeGenerator::new(3, 7)
+        .unwrap()
+        .next_id_at(Constants::EPOCH - 1);
+    assert_eq!(SnowflakeError::ClockBeforeEpoch, rvt.unwrap_err());
+}
+
+#[test]
+fn test_next_id_at_seconds() {
+    // halfway into a second
+    let now = Constants::EPOCH + 1_002_500;
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now])))
+        .precision(TimePrecision::Seconds)
+        .build()
+        .unwrap();
+
+    // within the current second of the clock
+    let id = gen.next_id_at(now).unwrap();
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(1002, parts.timestamp);
+    assert!(gen.next_id_at(now + 499).is_ok());
+
+    let rvt = gen.next_id_at(now + 500);
+    assert_eq!(SnowflakeError::TimestampInvalid, rvt.unwrap_err());
+}
+
+#[test]
+fn test_generator_clock_before_epoch() {
+    // a misconfigured machine with the clock set before the epoch
+    let clock = MockClock::new(&[Constants::EPOCH - 1000]);
+
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+
+    let rvt = gen.next_id();
+    assert!(matches!(rvt, Err(SnowflakeError::ClockBeforeEpoch)));
+
+    let rvt = gen.next_id_with_timestamp(Constants::EPOCH - 1);
+    assert!(matches!(rvt, Err(SnowflakeError::ClockBeforeEpoch)));
+}
+
+#[test]
+fn test_remaining_in_window() {
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(MockClock::new(&[now]))).unwrap();
+    assert_eq!(Constants::SEQUENCE_MASK + 1, gen.remaining_in_window());
+
+    for emitted in 1..=3 {
+        gen.next_id().unwrap();
+        assert_eq!(
+            Constants::SEQUENCE_MASK + 1 - emitted,
+            gen.remaining_in_window()
+        );
+    }
+
+    gen.set_sequence(Constants::SEQUENCE_MASK);
+    assert_eq!(0, gen.remaining_in_window());
+
+    // the clock moved on to the next millisecond
+    let clock = MockClock::new(&[now, now + 1]);
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    gen.next_id().unwrap();
+    assert_eq!(Constants::SEQUENCE_MASK + 1, gen.remaining_in_window());
+}
+
+#[test]
+fn test_advance_window() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let id = gen.next_id().unwrap();
+
+    gen.advance_window().unwrap();
+    let next = gen.next_id().unwrap();
+    assert!(SnowflakeGenerator::timestamp_of(next) > SnowflakeGenerator::timestamp_of(id));
+    assert_eq!(0, SnowflakeGenerator::decompose(next).sequence);
+
+    // the rollover edge: the exhausted sequence starts over in the next millisecond
+    let now = Constants::EPOCH + 1000;
+    let clock = MockClock::new(&[now, now, now + 1]);
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    gen.next_id().unwrap();
+    gen.set_sequence(Constants::SEQUENCE_MASK);
+
+    gen.advance_window().unwrap();
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(1001, parts.timestamp);
+    assert_eq!(0, parts.sequence);
+}
+
+#[test]
+fn test_max_per_second() {
+    let now = Constants::EPOCH + 1000;
+    let clock = MockClock::new(&[now, now + 1, now + 2, now + 999, now + 1000]);
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(clock))
+        .max_per_second(3)
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        gen.next_id().unwrap();
+    }
+    let rvt = gen.next_id();
+    assert!(matches!(rvt, Err(SnowflakeError::RateLimited)));
+
+    // the next second resets the budget
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(2000, parts.timestamp);
+
+    // an earlier second never refills the budget
+    let gen = SnowflakeGenerator::builder()
+        .max_per_second(1)
+        .build()
+        .unwrap();
+    gen.next_id_with_timestamp(now + 1000).unwrap();
+    assert!(gen.next_id_with_timestamp(now).is_err());
+    let rvt = gen.next_id_with_timestamp(now + 1000);
+    assert!(matches!(rvt, Err(SnowflakeError::RateLimited)));
+
+    // unlimited by default
+    let gen = SnowflakeGenerator::builder().build().unwrap();
+    let now = SnowflakeGenerator::time_gen().unwrap();
+    for _ in 0..100 {
+        gen.next_id_with_timestamp(now).unwrap();
+    }
+}
+
+#[test]
+fn test_generator_new_with_clock() {
+    let now = Constants::EPOCH + 1000;
+    let clock = MockClock::new(&[now]);
+
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(1000, parts.timestamp);
+    assert_eq!(0, parts.sequence);
+}
+
+#[test]
+fn test_generator_clock_moved_backwards_recovered() {
+    let now = Constants::EPOCH + 1000;
+    // backwards by 2ms, then recovered
+    let clock = MockClock::new(&[now, now - 2, now - 2, now + 1]);
+
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    let first = gen.next_id().unwrap();
+    let second = gen.next_id().unwrap();
+    assert!(second > first);
+
+    let parts = SnowflakeGenerator::decompose(second);
+    assert_eq!(1001, parts.timestamp);
+}
+
+#[test]
+fn test_generator_clock_moved_backwards() {
+    let now = Constants::EPOCH + 1000;
+    let clock = MockClock::new(&[now, now - 2, now - 1]);
+
+    let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+    assert!(gen.next_id().is_ok());
+    assert!(matches!(
+        gen.next_id(),
+        Err(SnowflakeError::ClockMovedBackwards)
+    ));
+}
+
+#[test]
+fn test_monotonic_clock_wall_stepped_backwards() {
+    let now = Constants::EPOCH + 1000;
+    // the base, then stepped backwards by 1s
+    let wall = MockClock::new(&[now, now + 1, now - 1000]);
+    let clock = MonotonicClock::with_wall_clock(wall).unwrap();
+
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(clock))
+        .max_backward_tolerance_millis(0)
+        .build()
+        .unwrap();
+
+    let ids: Vec<u64> = (0..10_000).map(|_| gen.next_id().unwrap()).collect();
+    assert!(verify::verify_monotonic(&ids).is_ok());
+    assert!(SnowflakeGenerator::decompose(ids[9_999]).unix_millis() > now);
+}
+
+#[test]
+fn test_monotonic_clock_wall_stepped_forwards() {
+    let now = Constants::EPOCH + 1000;
+    let wall = MockClock::new(&[now, now + 10_000, now]);
+    let clock = MonotonicClock::with_wall_clock(wall).unwrap();
+
+    // followed, then never backwards
+    assert!(clock.now_millis().unwrap() >= now + 10_000);
+    assert!(clock.now_millis().unwrap() >= now + 10_000);
+}
+
+#[test]
+fn test_generator_backward_tolerance_exceeded() {
+    let now = Constants::EPOCH + 1000;
+    let clock = MockClock::new(&[now, now - 2, now - 2, now + 1]);
+
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(clock))
+        .max_backward_tolerance_millis(1)
+        .build()
+        .unwrap();
+    assert!(gen.next_id().is_ok());
+    assert!(matches!(
+        gen.next_id(),
+        Err(SnowflakeError::ClockMovedBackwards)
+    ));
+}
+
+#[test]
+fn test_generator_backward_tolerance_spin() {
+    let now = Constants::EPOCH + 1000;
+    let clock = MockClock::new(&[now, now - 4, now - 3, now - 1, now]);
+
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(clock))
+        .max_backward_tolerance_millis(5)
+        .wait_strategy(WaitStrategy::Spin)
+        .build()
+        .unwrap();
+    let first = gen.next_id().unwrap();
+    let second = gen.next_id().unwrap();
+    assert!(second > first);
+}
+
+#[test]
+fn test_generate_waited() {
+    let now = Constants::EPOCH + 1000;
+    // sleeps `2 * 5ms` through the clock moved backwards
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now, now - 5, now - 5, now + 1])))
+        .build()
+        .unwrap();
+    gen.next_id().unwrap();
+
+    let mut waited = Duration::ZERO;
+    let generated = gen.generate_for_waited(None, &mut waited).unwrap();
+    assert_eq!(1001, SnowflakeGenerator::decompose(generated.id).timestamp);
+    assert!(waited >= Duration::from_millis(10));
+
+    // no wait
+    let mut waited = Duration::ZERO;
+    gen.generate_for_waited(None, &mut waited).unwrap();
+    assert_eq!(Duration::ZERO, waited);
+}
+
+#[test]
+fn test_on_clock_backwards() {
+    let now = Constants::EPOCH + 1000;
+    let hooked = |timestamps: &[u64], decision| {
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let recorded = deltas.clone();
+        let gen = SnowflakeGenerator::builder()
+            .clock(Arc::new(MockClock::new(timestamps)))
+            .max_backward_tolerance_millis(1000)
+            .on_clock_backwards(move |delta| {
+                recorded.lock().unwrap().push(delta);
+                decision
+            })
+            .build()
+            .unwrap();
+        (gen, deltas)
+    };
+
+    // fails immediately despite the tolerance
+    let (gen, deltas) = hooked(&[now, now - 5], ClockBackwardsDecision::Fail);
+    gen.next_id().unwrap();
+    let start = Instant::now();
+    assert_eq!(
+        SnowflakeError::ClockMovedBackwards,
+        gen.next_id().unwrap_err()
+    );
+    assert!(start.elapsed() < Duration::from_millis(5));
+    assert_eq!(vec![5], *deltas.lock().unwrap());
+
+    let (gen, _) = hooked(
+        &[now, now - 5, now - 5, now + 1],
+        ClockBackwardsDecision::WaitThenRetry,
+    );
+    gen.next_id().unwrap();
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(1001, parts.timestamp);
+
+    let (gen, _) = hooked(
+        &[now, now - 5],
+        ClockBackwardsDecision::ProceedWithLastTimestamp,
+    );
+    let first = gen.next_id().unwrap();
+    let second = gen.next_id().unwrap();
+    assert!(second > first);
+    let parts = SnowflakeGenerator::decompose(second);
+    assert_eq!((1000, 1), (parts.timestamp, parts.sequence));
+}
+
+#[test]
+fn test_generator_local_monotonic() {
+    let gen = SnowflakeGeneratorLocal::new(3, 7).unwrap();
+    let ids: Vec<u64> = (0..10_000).map(|_| gen.next_id().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    let parts = SnowflakeGenerator::decompose(ids[0]);
+    assert_eq!((3, 7), (parts.center_id, parts.worker_id));
+
+    // the same IDs as the atomic generator on the rollover
+    let now = Constants::EPOCH + 1000;
+    let mut timestamps = vec![now; Constants::SEQUENCE_MASK as usize + 1];
+    timestamps.push(now + 1);
+    let local = SnowflakeGeneratorLocal::with_clock(3, 7, MockClock::new(&timestamps)).unwrap();
+    let atomic = SnowflakeGenerator::builder()
+        .center_id(3)
+        .worker_id(7)
+        .clock(Arc::new(MockClock::new(&timestamps)))
+        .build()
+        .unwrap();
+    for _ in 0..=Constants::SEQUENCE_MASK + 1 {
+        assert_eq!(atomic.next_id().unwrap(), local.next_id().unwrap());
+    }
+    let parts = SnowflakeGenerator::decompose(local.next_id().unwrap());
+    assert_eq!((1001, 1), (parts.timestamp, parts.sequence));
+
+    // sleeps through the clock moved backwards within the default tolerance like the atomic one
+    let gen = SnowflakeGeneratorLocal::with_clock(3, 7, MockClock::new(&[now, now - 5, now + 1]))
+        .unwrap();
+    gen.next_id().unwrap();
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!((1001, 0), (parts.timestamp, parts.sequence));
+
+    let backwards = now - Constants::DEFAULT_MAX_BACKWARD_TOLERANCE_MILLIS - 1;
+    let gen = SnowflakeGeneratorLocal::with_clock(3, 7, MockClock::new(&[now, backwards])).unwrap();
+    gen.next_id().unwrap();
+    assert_eq!(
+        SnowflakeError::ClockMovedBackwards,
+        gen.next_id().unwrap_err()
+    );
+    assert!(SnowflakeGeneratorLocal::new(3, 32).is_err());
+}
+
+#[test]
+fn test_generator_local_layout() {
+    let now = Constants::EPOCH + 1000;
+    let layout = BitLayout::new(0, 10, 12).unwrap();
+    let gen = SnowflakeGeneratorLocal::with_layout(
+        0,
+        1023,
+        layout,
+        Constants::TWITTER_EPOCH,
+        MockClock::new(&[now]),
+    )
+    .unwrap();
+    let parts = layout.decompose(gen.next_id().unwrap());
+    assert_eq!((0, 1023), (parts.center_id, parts.worker_id));
+    assert_eq!(now - Constants::TWITTER_EPOCH, parts.timestamp);
+
+    let rvt = SnowflakeGeneratorLocal::with_layout(1, 0, layout, Constants::EPOCH, SystemClock);
+    assert_eq!(SnowflakeError::CenterIdInvalid, rvt.unwrap_err());
+
+    // 1 timestamp bit
+    let layout = BitLayout::default().with_id_bits(23);
+    let gen = SnowflakeGeneratorLocal::with_layout(
+        3,
+        7,
+        layout,
+        Constants::EPOCH,
+        MockClock::new(&[now]),
+    )
+    .unwrap();
+    assert_eq!(
+        SnowflakeError::TimestampOverflow,
+        gen.next_id().unwrap_err()
+    );
+
+    // the epoch ahead of the clock
+    let gen = SnowflakeGeneratorLocal::with_layout(3, 7, layout, now + 1, MockClock::new(&[now]))
+        .unwrap();
+    assert_eq!(SnowflakeError::ClockBeforeEpoch, gen.next_id().unwrap_err());
+}
+
+// ---------------------------------------------------------------- config
+
+#[test]
+fn test_generator_config_round_trip() {
+    let config = SnowflakeConfig {
+        center_id: 3,
+        worker_id: 7,
+        epoch: Constants::EPOCH,
+        ..SnowflakeConfig::default()
+    };
+
+    let gen = SnowflakeGenerator::from_config(config).unwrap();
+    assert_eq!(config, gen.to_config());
+
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(3, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+}
+
+#[test]
+fn test_generator_config_round_trip_layout() {
+    let gen = SnowflakeGenerator::compact(7).unwrap();
+    let restored = SnowflakeGenerator::from_config(gen.to_config()).unwrap();
+    assert_eq!(gen, restored);
+    assert_eq!(gen.layout(), restored.layout());
+    assert_eq!(TimePrecision::Seconds, restored.precision());
+
+    let layout = gen.layout();
+    let parts = layout.decompose(restored.next_id().unwrap());
+    assert_eq!(7, parts.worker_id);
+    assert!(restored.next_id().unwrap() < 1 << layout.id_bits());
+}
+
+#[test]
+fn test_generator_config_invalid() {
+    let config = SnowflakeConfig {
+        worker_id: 32,
+        ..SnowflakeConfig::default()
+    };
+
+    let gen = SnowflakeGenerator::from_config(config);
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+}
+
+#[test]
+fn test_config_from_str() {
+    let config: SnowflakeConfig = "dc=3,worker=9,epoch=1700000000000".parse().unwrap();
+    assert_eq!(
+        SnowflakeConfig {
+            center_id: 3,
+            worker_id: 9,
+            epoch: 1700000000000,
+            ..SnowflakeConfig::default()
+        },
+        config
+    );
+    assert_eq!(config, config.to_string().parse().unwrap());
+
+    // whitespace, aliases and defaults
+    let config: SnowflakeConfig = " center = 3 , worker = 9 ".parse().unwrap();
+    assert_eq!(Constants::EPOCH, config.epoch);
+    assert_eq!(3, config.center_id);
+
+    let gen: SnowflakeGenerator = "dc=3,worker=9".parse().unwrap();
+    assert_eq!(
+        9,
+        SnowflakeGenerator::decompose(gen.next_id().unwrap()).worker_id
+    );
+}
+
+#[test]
+fn test_config_from_str_layout() {
+    let config = SnowflakeGenerator::compact(7).unwrap().to_config();
+    assert_eq!(
+        "dc=0,worker=7,epoch=1767225600000,layout=0/7/12,id_bits=48,precision=seconds",
+        config.to_string()
+    );
+    assert_eq!(config, config.to_string().parse().unwrap());
+
+    let layout = BitLayout::default()
+        .with_tag_bits(4)
+        .with_packing_order(PackingOrder::SequenceHigh);
+    let config = SnowflakeConfig {
+        layout,
+        ..SnowflakeConfig::default()
+    };
+    assert_eq!(
+        "dc=1,worker=1,epoch=1680646028000,tag_bits=4,order=sequence-high",
+        config.to_string()
+    );
+    assert_eq!(config, config.to_string().parse().unwrap());
+
+    // the IDs are validated against the layout
+    let config: SnowflakeConfig = "dc=0,worker=1000,layout=0/10/12".parse().unwrap();
+    assert_eq!(1000, config.worker_id);
+    assert!(matches!(
+        "dc=1,worker=0,layout=0/10/12".parse::<SnowflakeConfig>(),
+        Err(SnowflakeError::CenterIdInvalid)
+    ));
+    assert!(matches!(
+        "layout=6/6/12".parse::<SnowflakeConfig>(),
+        Err(SnowflakeError::LayoutInvalid(_))
+    ));
+}
+
+#[test]
+fn test_config_from_str_out_of_range() {
+    assert!(matches!(
+        "dc=3,worker=32".parse::<SnowflakeConfig>(),
+        Err(SnowflakeError::WorkerIdInvalid)
+    ));
+    assert!(matches!(
+        "dc=32".parse::<SnowflakeConfig>(),
+        Err(SnowflakeError::CenterIdInvalid)
+    ));
+    // validated by the generator
+    let epoch = SnowflakeGenerator::time_gen().unwrap() + 60 * 1000;
+    assert!(matches!(
+        format!("epoch={}", epoch).parse::<SnowflakeGenerator>(),
+        Err(SnowflakeError::EpochInvalid)
+    ));
+}
+
+#[test]
+fn test_config_from_str_malformed() {
+    let reason = |s: &str| match s.parse::<SnowflakeConfig>() {
+        Err(SnowflakeError::ConfigInvalid(reason)) => reason,
+        rvt => panic!("{}: {:?}", s, rvt),
+    };
+
+    assert!(reason("dc=3,shard=1").contains("unknown key `shard`"));
+    assert!(reason("dc=3,dc=4").contains("duplicate key `dc`"));
+    assert!(reason("dc=3,worker").contains("expected `key=value`"));
+    assert!(reason("dc=3,").contains("expected `key=value`"));
+    assert!(reason("worker=-1").contains("unsigned integer"));
+    assert!(reason("").contains("expected `key=value`"));
+    assert!(reason("layout=5/5").contains("`D/W/S` bits"));
+    assert!(reason("layout=5/x/12").contains("unsigned integer"));
+    assert!(reason("precision=micros").contains("`millis` or `seconds`"));
+    assert!(reason("order=random").contains("`sequence-high`"));
+}
+
+// ---------------------------------------------------------------- decompose
+
+#[test]
+fn test_decompose_round_trip() {
+    let gen = SnowflakeGenerator::new(12, 7).unwrap();
+    let id = gen.next_id().unwrap();
+
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(12, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+
+    let packed = (parts.timestamp << Constants::TIMESTAMP_SHIFT)
+        | (parts.center_id << Constants::CENTER_ID_SHIFT)
+        | (parts.worker_id << Constants::WORKER_ID_SHIFT)
+        | parts.sequence;
+    assert_eq!(id, packed);
+}
+
+#[test]
+fn test_decompose_unix_millis() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let before = SnowflakeGenerator::time_gen().unwrap();
+    let id = gen.next_id().unwrap();
+    let after = SnowflakeGenerator::time_gen().unwrap();
+
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(parts.timestamp + Constants::EPOCH, parts.unix_millis());
+    assert!(parts.unix_millis() >= before);
+    assert!(parts.unix_millis() <= after);
+}
+
+#[test]
+fn test_timestamp_of() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+
+    let first = gen.next_id().unwrap();
+    thread::sleep(Duration::from_millis(3));
+    let second = gen.next_id().unwrap();
+
+    let first_millis = SnowflakeGenerator::timestamp_of(first);
+    let second_millis = SnowflakeGenerator::timestamp_of(second);
+    assert!(second_millis >= first_millis);
+    assert!(
+        SnowflakeGenerator::system_time_of(second) >= SnowflakeGenerator::system_time_of(first)
+    );
+}
+
+#[test]
+fn test_timestamp_of_near_epoch() {
+    assert_eq!(Constants::EPOCH, SnowflakeGenerator::timestamp_of(0));
+    assert_eq!(
+        Constants::EPOCH,
+        SnowflakeGenerator::timestamp_of(Constants::SEQUENCE_MASK)
+    );
+}
+
+// ---------------------------------------------------------------- compare
+
+#[test]
+fn test_compare_time_diff_millis() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+
+    let a = gen.next_id().unwrap();
+    thread::sleep(Duration::from_millis(50));
+    let b = gen.next_id().unwrap();
+
+    let diff = compare::time_diff_millis(b, a);
+    assert!((50..200).contains(&diff), "diff: {}", diff);
+    assert_eq!(-diff, compare::time_diff_millis(a, b));
+
+    assert!(compare::is_before(a, b));
+    assert!(!compare::is_before(b, a));
+}
+
+#[test]
+fn test_compare_same_millis() {
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builtin().unwrap();
+
+    let a = gen.next_id_with_timestamp(now).unwrap();
+    let b = gen.next_id_with_timestamp(now).unwrap();
+    assert!(b > a);
+
+    assert_eq!(0, compare::time_diff_millis(a, b));
+    assert!(!compare::is_before(a, b));
+    assert!(!compare::is_before(b, a));
+}
+
+// ---------------------------------------------------------------- node file
+
+fn write_node_file(name: &str, content: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("snowflaker-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_from_node_file() {
+    let path = write_node_file("node-id", "3:9\n");
+    let gen = SnowflakeGenerator::from_node_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(3, gen.center_id());
+    assert_eq!(9, gen.worker_id());
+    assert_eq!(
+        9,
+        SnowflakeGenerator::decompose(gen.next_id().unwrap()).worker_id
+    );
+}
+
+#[test]
+fn test_from_node_file_malformed() {
+    for (name, content) in [
+        ("node-id-empty", ""),
+        ("node-id-no-colon", "3 9"),
+        ("node-id-not-number", "3:nine"),
+        ("node-id-negative", "-3:9"),
+    ] {
+        let path = write_node_file(name, content);
+        let rvt = SnowflakeGenerator::from_node_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        match rvt {
+            Err(SnowflakeError::NodeFileInvalid(reason)) => {
+                assert!(
+                    reason.contains("expected `datacenter:worker`"),
+                    "{}",
+                    reason
+                )
+            }
+            rvt => panic!("{}: {:?}", content, rvt),
+        }
+    }
+
+    // out of range
+    let path = write_node_file("node-id-out-of-range", "32:9");
+    let rvt = SnowflakeGenerator::from_node_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(rvt, Err(SnowflakeError::CenterIdInvalid)));
+}
+
+#[test]
+fn test_from_node_file_missing() {
+    let path = std::env::temp_dir().join("snowflaker-missing-node-id");
+    let rvt = SnowflakeGenerator::from_node_file(&path);
+    assert!(matches!(rvt, Err(SnowflakeError::NodeFileInvalid(_))));
+    assert!(rvt
+        .unwrap_err()
+        .to_string()
+        .contains("snowflaker-missing-node-id"));
+}
+
+// ---------------------------------------------------------------- generator128
+
+#[test]
+fn test_generator128_monotonic() {
+    let gen = SnowflakeGenerator128::new(1024, 4096).unwrap();
+    let ids: Vec<u128> = (0..10_000).map(|_| gen.next_id().unwrap()).collect();
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_generator128_decompose_round_trip() {
+    let gen = SnowflakeGenerator128::new(1024, 4096).unwrap();
+    let before = SystemClock.now_millis().unwrap();
+    let id = gen.next_id().unwrap();
+    let after = SystemClock.now_millis().unwrap();
+
+    let parts = SnowflakeGenerator128::decompose(id);
+    assert_eq!(1024, parts.center_id);
+    assert_eq!(4096, parts.worker_id);
+    assert!(parts.unix_millis() >= before && parts.unix_millis() <= after);
+    assert_eq!(id, parts.compose());
+}
+
+#[test]
+fn test_generator128_with_timestamp() {
+    let gen = SnowflakeGenerator128::builder()
+        .center_id(65535)
+        .worker_id(65535)
+        .random(false)
+        .build()
+        .unwrap();
+    let now = Constants::EPOCH + 1000;
+
+    let first = gen.next_id_with_timestamp(now).unwrap();
+    let expected = IdParts128 {
+        timestamp: 1000,
+        center_id: 65535,
+        worker_id: 65535,
+        sequence: 0,
+        random: 0,
+    };
+    assert_eq!(expected.compose(), first);
+
+    let second = gen.next_id_with_timestamp(now).unwrap();
+    assert_eq!(1, SnowflakeGenerator128::decompose(second).sequence);
+
+    // 65536 IDs per millisecond
+    for _ in 2..=Constants128::SEQUENCE_MASK {
+        assert!(gen.next_id_with_timestamp(now).is_ok());
+    }
+    assert!(matches!(
+        gen.next_id_with_timestamp(now),
+        Err(SnowflakeError::SequenceExhausted)
+    ));
+    assert!(matches!(
+        gen.next_id_with_timestamp(now - 1),
+        Err(SnowflakeError::ClockMovedBackwards)
+    ));
+    assert!(matches!(
+        gen.next_id_with_timestamp(Constants::EPOCH - 1),
+        Err(SnowflakeError::ClockBeforeEpoch)
+    ));
+    assert!(matches!(
+        gen.next_id_with_timestamp(Constants::EPOCH + Constants128::MAX_TIMESTAMP + 1),
+        Err(SnowflakeError::TimestampOverflow)
+    ));
+}
+
+#[test]
+fn test_generator128_random_bits() {
+    let now = Constants::EPOCH + 1000;
+    let seeded = |seed: u64| -> Vec<u64> {
+        let gen = SnowflakeGenerator128::builder().seed(seed).build().unwrap();
+        (0..100)
+            .map(|_| {
+                SnowflakeGenerator128::decompose(gen.next_id_with_timestamp(now).unwrap()).random
+            })
+            .collect()
+    };
+
+    // deterministic by the seed
+    assert_eq!(seeded(42), seeded(42));
+    assert_ne!(seeded(42), seeded(43));
+
+    let randoms: HashSet<u64> = seeded(42).into_iter().collect();
+    assert!(randoms.len() > 1);
+}
+
+#[test]
+fn test_generator128_invalid() {
+    assert!(matches!(
+        SnowflakeGenerator128::new(Constants128::MAX_DATA_CENTER_ID + 1, 0),
+        Err(SnowflakeError::CenterIdInvalid)
+    ));
+    assert!(matches!(
+        SnowflakeGenerator128::new(0, Constants128::MAX_WORKER_ID + 1),
+        Err(SnowflakeError::WorkerIdInvalid)
+    ));
+    assert!(matches!(
+        SnowflakeGenerator128::builder().epoch(u64::MAX).build(),
+        Err(SnowflakeError::EpochInvalid)
+    ));
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_generator128_codec_round_trip() {
+    let mut ids = vec![0, 1, u64::MAX as u128, u128::MAX];
+    ids.push(next_id_u128().unwrap());
+
+    for id in ids {
+        assert_eq!(
+            id,
+            codec::decode_base62_u128(&codec::encode_base62_u128(id)).unwrap()
+        );
+        let hex = codec::to_hex_u128(id);
+        assert_eq!(32, hex.len());
+        assert_eq!(id, codec::from_hex_u128(&hex).unwrap());
+    }
+
+    // overflows u64
+    assert!(codec::decode_base62(&codec::encode_base62_u128(u64::MAX as u128 + 1)).is_err());
+}
+
+// ---------------------------------------------------------------- batch
+
+#[test]
+fn test_next_ids_timeout() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    let ids = gen
+        .next_ids_timeout(10_000, Duration::from_secs(5))
+        .unwrap();
+
+    assert_eq!(10_000, ids.len());
+    assert!(verify::verify_monotonic(&ids).is_ok());
+}
+
+#[test]
+fn test_next_ids_joined() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    let joined = gen.next_ids_joined(3, ",").unwrap();
+    assert_eq!(2, joined.matches(',').count());
+
+    let ids: Vec<u64> = joined.split(',').map(|id| id.parse().unwrap()).collect();
+    assert_eq!(3, ids.len());
+    assert!(verify::verify_monotonic(&ids).is_ok());
+
+    assert_eq!(1, gen.next_ids_joined(1, ", ").unwrap().split(", ").count());
+    assert_eq!("", gen.next_ids_joined(0, ",").unwrap());
+}
+
+#[test]
+fn test_next_ids_timeout_stalled_clock() {
+    let now = Constants::EPOCH + 1000;
+    // 4 IDs per millisecond, the clock never advances
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::new(5, 5, 2).unwrap())
+        .clock(Arc::new(MockClock::new(&[now])))
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    let ids = gen.next_ids_timeout(10, Duration::from_millis(20)).unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+
+    assert_eq!(4, ids.len());
+    assert!(verify::verify_unique(&ids).is_ok());
+    assert!(verify::verify_monotonic(&ids).is_ok());
+}
+
+#[test]
+fn test_next_ids_timeout_recovered() {
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::new(5, 5, 2).unwrap())
+        .clock(Arc::new(MockClock::new(&[
+            now,
+            now,
+            now,
+            now,
+            now,
+            now + 1,
+        ])))
+        .build()
+        .unwrap();
+
+    let ids = gen.next_ids_timeout(6, Duration::from_secs(1)).unwrap();
+    assert_eq!(6, ids.len());
+    assert!(verify::verify_monotonic(&ids).is_ok());
+}
+
+#[test]
+fn test_next_ids_timeout_on_clock_backwards() {
+    let now = Constants::EPOCH + 1000;
+    let hooked = |timestamps: &[u64], decision| {
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let recorded = deltas.clone();
+        let gen = SnowflakeGenerator::builder()
+            .clock(Arc::new(MockClock::new(timestamps)))
+            .max_backward_tolerance_millis(0)
+            .on_clock_backwards(move |delta| {
+                recorded.lock().unwrap().push(delta);
+                decision
+            })
+            .build()
+            .unwrap();
+        (gen, deltas)
+    };
+
+    let (gen, deltas) = hooked(&[now, now - 5], ClockBackwardsDecision::Fail);
+    assert_eq!(
+        SnowflakeError::ClockMovedBackwards,
+        gen.next_ids_timeout(2, Duration::from_secs(1)).unwrap_err()
+    );
+    assert_eq!(vec![5], *deltas.lock().unwrap());
+
+    // waits despite the tolerance
+    let (gen, _) = hooked(
+        &[now, now - 5, now - 5, now + 1],
+        ClockBackwardsDecision::WaitThenRetry,
+    );
+    let ids = gen.next_ids_timeout(2, Duration::from_secs(1)).unwrap();
+    assert_eq!(2, ids.len());
+    assert_eq!(1001, SnowflakeGenerator::decompose(ids[1]).timestamp);
+
+    let (gen, deltas) = hooked(
+        &[now, now - 5],
+        ClockBackwardsDecision::ProceedWithLastTimestamp,
+    );
+    let ids = gen.next_ids_timeout(3, Duration::from_secs(1)).unwrap();
+    assert!(verify::verify_monotonic(&ids).is_ok());
+    let parts = SnowflakeGenerator::decompose(ids[2]);
+    assert_eq!((1000, 2), (parts.timestamp, parts.sequence));
+    assert_eq!(vec![5, 5], *deltas.lock().unwrap());
+}
+
+// ---------------------------------------------------------------- metrics
+
+#[derive(Default)]
+struct CountingMetrics {
+    generated: AtomicU64,
+    rollovers: AtomicU64,
+    backwards: AtomicU64,
+}
+
+impl Metrics for CountingMetrics {
+    fn on_id_generated(&self, _id: u64) {
+        self.generated.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_sequence_rollover(&self, _timestamp: u64) {
+        self.rollovers.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_clock_backwards(&self, delta_millis: u64) {
+        self.backwards.fetch_add(delta_millis, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_metrics_on_id_generated() {
+    let metrics = Arc::new(CountingMetrics::default());
+    let gen = SnowflakeGenerator::with_metrics(3, 7, metrics.clone()).unwrap();
+
+    for n in 1..=100 {
+        assert!(gen.next_id().is_ok());
+        assert_eq!(n, metrics.generated.load(Ordering::SeqCst));
+    }
+}
+
+#[test]
+fn test_metrics_on_sequence_rollover() {
+    let now = Constants::EPOCH + 1000;
+    let metrics = Arc::new(CountingMetrics::default());
+    // 2 IDs per millisecond
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::new(5, 5, 1).unwrap())
+        .clock(Arc::new(MockClock::new(&[now, now, now, now + 1])))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    for _ in 0..3 {
+        assert!(gen.next_id().is_ok());
+    }
+    assert_eq!(3, metrics.generated.load(Ordering::SeqCst));
+    assert_eq!(1, metrics.rollovers.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_metrics_on_clock_backwards() {
+    let now = Constants::EPOCH + 1000;
+    let metrics = Arc::new(CountingMetrics::default());
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now, now - 2, now - 2, now + 1])))
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    assert!(gen.next_id().is_ok());
+    assert!(gen.next_id().is_ok());
+    assert_eq!(2, metrics.backwards.load(Ordering::SeqCst));
+    assert_eq!(2, metrics.generated.load(Ordering::SeqCst));
+}
+
+// ---------------------------------------------------------------- sequence utilization
+
+#[test]
+fn test_sequence_utilization() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    assert_eq!(0.0, gen.current_sequence_utilization());
+    assert_eq!(0, gen.peak_sequence());
+
+    let now = 1_700_000_000_000;
+    for _ in 0..2048 {
+        gen.next_id_with_timestamp(now).unwrap();
+    }
+
+    let utilization = gen.current_sequence_utilization();
+    assert!(utilization > 0.0);
+    assert!(utilization < 1.0);
+    assert_eq!(2047, gen.peak_sequence());
+
+    // the next millisecond resets the sequence, the peak stays
+    gen.next_id_with_timestamp(now + 1).unwrap();
+    assert_eq!(0.0, gen.current_sequence_utilization());
+    assert_eq!(2047, gen.peak_sequence());
+    assert_eq!(2047, gen.clone().peak_sequence());
+}
+
+#[test]
+fn test_sequence_utilization_tight_loop() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    let mut peak = 0.0f32;
+    for _ in 0..100_000 {
+        gen.next_id().unwrap();
+        peak = peak.max(gen.current_sequence_utilization());
+    }
+
+    assert!(peak > 0.0);
+    assert!(gen.peak_sequence() > 0);
+}
+
+// ---------------------------------------------------------------- twitter
+
+#[test]
+fn test_twitter() {
+    assert_eq!(1288834974657, Constants::TWITTER_EPOCH);
+
+    let gen = SnowflakeGenerator::twitter(3, 7).unwrap();
+    assert_eq!(Constants::TWITTER_EPOCH, gen.epoch());
+    assert_eq!(BitLayout::default(), BitLayout::new(5, 5, 12).unwrap());
+    assert_eq!(41, BitLayout::default().timestamp_bits());
+
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(3, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+    let millis = parts.timestamp + Constants::TWITTER_EPOCH;
+    assert!(millis >= now && millis < now + 1000);
+
+    // 2020-01-01 00:00:00 UTC
+    let new_year = 1577836800000;
+    let id = (new_year - Constants::TWITTER_EPOCH) << Constants::TIMESTAMP_SHIFT;
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(new_year, parts.timestamp + Constants::TWITTER_EPOCH);
+}
+
+// ---------------------------------------------------------------- compact
+
+#[test]
+fn test_compact() {
+    let gen = SnowflakeGenerator::compact(127).unwrap();
+    assert_eq!(Constants::COMPACT_EPOCH, gen.epoch());
+    assert_eq!(0, gen.max_datacenter_id());
+    assert_eq!(127, gen.max_worker_id());
+    assert_eq!(29, gen.layout().timestamp_bits());
+
+    let ids: Vec<u64> = (0..1000).map(|_| gen.next_id().unwrap()).collect();
+    for pair in ids.windows(2) {
+        assert!(pair[0] < pair[1]);
+    }
+    assert!(ids.iter().all(|id| *id < 1 << 48));
+    assert_eq!(127, gen.layout().decompose(ids[0]).worker_id);
+
+    // valid for about 17 years since the epoch in 2026
+    let secs = gen
+        .layout_info()
+        .valid_until
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    assert_eq!(2043, 1970 + secs / (365 * 24 * 60 * 60 + 6 * 60 * 60));
+
+    let rvt = SnowflakeGenerator::compact(128);
+    assert_eq!(SnowflakeError::WorkerIdInvalid, rvt.unwrap_err());
+
+    // beyond the max timestamp
+    let max = Constants::COMPACT_EPOCH + (gen.layout().max_timestamp() + 1) * 1000;
+    let rvt = gen.next_id_with_timestamp(max);
+    assert_eq!(SnowflakeError::TimestampOverflow, rvt.unwrap_err());
+}
+
+#[test]
+fn test_bit_layout_with_id_bits() {
+    let layout = BitLayout::new(0, 7, 12).unwrap().with_id_bits(48);
+    assert!(layout.validate().is_ok());
+    assert_eq!(48, layout.id_bits());
+    assert_eq!(29, layout.timestamp_bits());
+    assert_eq!(63, BitLayout::default().id_bits());
+
+    let rvt = BitLayout::default().with_id_bits(64).validate();
+    assert_eq!(
+        "Bit layout out of range: id(64) must exceed data-center(5) + worker(5) + sequence(12) = 22 bits, up to 63",
+        rvt.unwrap_err().to_string()
+    );
+    assert!(BitLayout::default().with_id_bits(22).validate().is_err());
+    assert!(BitLayout::default().with_id_bits(23).validate().is_ok());
+}
+
+#[test]
+fn test_bit_width_fns() {
+    assert_eq!(4095, mask(12));
+    assert_eq!(31, max_id(5));
+    assert_eq!(0, mask(0));
+    assert_eq!(u64::MAX, mask(64));
+    assert_eq!(22, shift_for(&[12, 5, 5]));
+    assert_eq!(0, shift_for(&[]));
+
+    assert_eq!(Constants::SEQUENCE_MASK, mask(Constants::SEQUENCE_BITS));
+    assert_eq!(Constants::MAX_WORKER_ID, max_id(Constants::WORKER_ID_BITS));
+    let layout = BitLayout::new(4, 6, 12).unwrap();
+    assert_eq!(max_id(6), layout.max_worker_id());
+    assert_eq!(shift_for(&[12, 6]), layout.center_id_shift());
+}
+
+// ---------------------------------------------------------------- packing order
+
+#[test]
+fn test_packing_order_sequence_high() {
+    let layout = BitLayout::default().with_packing_order(PackingOrder::SequenceHigh);
+    assert_eq!(
+        PackingOrder::TimestampHigh,
+        BitLayout::default().packing_order()
+    );
+    assert_eq!(41, layout.timestamp_bits());
+
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builder()
+        .center_id(3)
+        .worker_id(7)
+        .layout(layout)
+        .clock(Arc::new(MockClock::new(&[now])))
+        .build()
+        .unwrap();
+
+    let ids: Vec<u64> = (0..10).map(|_| gen.next_id().unwrap()).collect();
+    for (sequence, id) in ids.iter().enumerate() {
+        let parts = layout.decompose(*id);
+        assert_eq!(1000, parts.timestamp);
+        assert_eq!(3, parts.center_id);
+        assert_eq!(7, parts.worker_id);
+        assert_eq!(sequence as u64, parts.sequence);
+        assert!(*id < 1 << Constants::ID_BITS);
+    }
+    assert_eq!(
+        ids.iter()
+            .map(|id| layout.decompose(*id))
+            .collect::<Vec<_>>(),
+        codec::decompose_many(&ids, &layout)
+    );
+
+    // the sequence in the high bits
+    assert_eq!(1 << 51, ids[1] - ids[0]);
+    assert!(verify::verify_unique(&ids).is_ok());
+
+    let rvt = gen.next_id_for_shard(1, 2);
+    assert_eq!(SnowflakeError::ShardInvalid, rvt.unwrap_err());
+}
+
+#[test]
+fn test_packing_order_sequence_high_tagged() {
+    let layout = BitLayout::default()
+        .with_tag_bits(4)
+        .with_packing_order(PackingOrder::SequenceHigh);
+    let gen = SnowflakeGenerator::builder()
+        .layout(layout)
+        .build()
+        .unwrap();
+
+    let parts = layout.decompose(gen.next_id_tagged(9).unwrap());
+    assert_eq!(9, parts.tag);
+    assert_eq!(Constants::DEFAULT_WORKER_ID, parts.worker_id);
+    assert_eq!(Constants::DEFAULT_DATA_CENTER_ID, parts.center_id);
+}
+
+// ---------------------------------------------------------------- named
+
+#[cfg(feature = "global")]
+#[test]
+fn test_named_next_id() {
+    register_named("test-users", SnowflakeGenerator::new(1, 11).unwrap()).unwrap();
+    register_named("test-orders", SnowflakeGenerator::new(2, 22).unwrap()).unwrap();
+
+    for _ in 0..100 {
+        let parts = SnowflakeGenerator::decompose(named_next_id("test-users").unwrap());
+        assert_eq!((1, 11), (parts.center_id, parts.worker_id));
+
+        let parts = SnowflakeGenerator::decompose(named_next_id("test-orders").unwrap());
+        assert_eq!((2, 22), (parts.center_id, parts.worker_id));
+    }
+
+    // the registered generator is kept
+    assert!(matches!(
+        register_named("test-users", SnowflakeGenerator::new(3, 7).unwrap()),
+        Err(SnowflakeError::AlreadyInitialized)
+    ));
+    let parts = SnowflakeGenerator::decompose(named_next_id("test-users").unwrap());
+    assert_eq!(11, parts.worker_id);
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_named_next_id_not_found() {
+    assert!(matches!(
+        named_next_id("test-unregistered"),
+        Err(SnowflakeError::GeneratorNotFound)
+    ));
+}
+
+// ---------------------------------------------------------------- producer
+
+#[test]
+fn test_spawn_producer() {
+    let metrics = Arc::new(CountingMetrics::default());
+    let gen = SnowflakeGenerator::builder()
+        .metrics(metrics.clone())
+        .build()
+        .unwrap();
+
+    let rx = gen.spawn_producer(64);
+    let ids: Vec<u64> = rx.iter().take(1000).map(|rvt| rvt.unwrap()).collect();
+    assert!(verify::verify_unique(&ids).is_ok());
+    assert!(verify::verify_monotonic(&ids).is_ok());
+    assert_eq!(2, Arc::strong_count(&metrics));
+
+    // the producer thread exits and drops the generator
+    drop(rx);
+    let started = Instant::now();
+    while Arc::strong_count(&metrics) > 1 {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+// ---------------------------------------------------------------- overflow policy
+
+#[test]
+fn test_overflow_policy_error() {
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now])))
+        .overflow_policy(OverflowPolicy::Error)
+        .build()
+        .unwrap();
+    gen.restore(GeneratorState {
+        last_timestamp: now,
+        sequence: Constants::SEQUENCE_MASK - 1,
+    });
+
+    let id = gen.next_id().unwrap();
+    assert_eq!(
+        Constants::SEQUENCE_MASK,
+        SnowflakeGenerator::decompose(id).sequence
+    );
+
+    let started = Instant::now();
+    assert!(matches!(
+        gen.next_id(),
+        Err(SnowflakeError::SequenceExhausted)
+    ));
+    assert!(started.elapsed() < Duration::from_millis(100));
+}
+
+#[test]
+fn test_overflow_policy_wait_next_millis() {
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now, now, now, now + 1])))
+        .build()
+        .unwrap();
+    gen.restore(GeneratorState {
+        last_timestamp: now,
+        sequence: Constants::SEQUENCE_MASK - 1,
+    });
+
+    assert!(gen.next_id().is_ok());
+
+    // waits for the next millisecond
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(now + 1, parts.unix_millis());
+    assert_eq!(0, parts.sequence);
+}
+
+// ---------------------------------------------------------------- timestamp cache
+
+#[test]
+fn test_timestamp_cache() {
+    let now = Constants::EPOCH + 1000;
+    let layout = BitLayout::new(5, 5, 2).unwrap();
+    // 4 IDs per millisecond
+    let gen = SnowflakeGenerator::builder()
+        .layout(layout)
+        .clock(Arc::new(MockClock::new(&[now, now + 1, now + 2, now + 3])))
+        .timestamp_cache(true)
+        .build()
+        .unwrap();
+
+    let timestamps: Vec<u64> = gen
+        .iter()
+        .take(8)
+        .map(|rvt| layout.decompose(rvt.unwrap()).unix_millis())
+        .collect();
+    // the clock is re-read only on the rollover
+    assert_eq!(
+        vec![now, now, now, now, now + 1, now + 1, now + 1, now + 1],
+        timestamps
+    );
+
+    let gen = SnowflakeGenerator::builder()
+        .layout(layout)
+        .clock(Arc::new(MockClock::new(&[now, now + 1, now + 2, now + 3])))
+        .build()
+        .unwrap();
+    let timestamps: Vec<u64> = gen
+        .iter()
+        .take(4)
+        .map(|rvt| layout.decompose(rvt.unwrap()).unix_millis())
+        .collect();
+    assert_eq!(vec![now, now + 1, now + 2, now + 3], timestamps);
+}
+
+#[test]
+fn test_timestamp_cache_bounded() {
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now, now + 3_600_000])))
+        .timestamp_cache(true)
+        .build()
+        .unwrap();
+
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!((now, 0), (parts.unix_millis(), parts.sequence));
+
+    // idle without a rollover, the clock advanced an hour in the meantime
+    thread::sleep(Duration::from_millis(2));
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!((now + 3_600_000, 0), (parts.unix_millis(), parts.sequence));
+}
+
+#[test]
+fn test_timestamp_cache_never_reuses_supplied_timestamp() {
+    let gen = SnowflakeGenerator::builder()
+        .timestamp_cache(true)
+        .build()
+        .unwrap();
+
+    // backfilled before any clock read
+    let backfill = Constants::EPOCH + 1000;
+    gen.next_id_at(backfill).unwrap();
+
+    let before = generator::now_millis().unwrap();
+    let id = gen.next_id().unwrap();
+    assert!(SnowflakeGenerator::timestamp_of(id) >= before);
+
+    // supplied after a clock read
+    let now = Constants::EPOCH + 1000;
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(MockClock::new(&[now, now + 5])))
+        .timestamp_cache(true)
+        .build()
+        .unwrap();
+    gen.next_id().unwrap();
+    gen.next_id_with_timestamp(now + 3).unwrap();
+
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!((now + 5, 0), (parts.unix_millis(), parts.sequence));
+}
+
+#[test]
+fn test_timestamp_cache_unique() {
+    let gen = SnowflakeGenerator::builder()
+        .center_id(3)
+        .worker_id(7)
+        .timestamp_cache(true)
+        .build()
+        .unwrap();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let gen = gen.clone();
+            thread::spawn(move || {
+                let ids: Vec<u64> = (0..10_000).map(|_| gen.next_id().unwrap()).collect();
+                assert!(verify::verify_monotonic(&ids).is_ok());
+                ids
+            })
+        })
+        .collect();
+
+    let ids: Vec<u64> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(80_000, ids.len());
+    assert!(verify::verify_unique(&ids).is_ok());
+}
+
+// ---------------------------------------------------------------- retryable
+
+#[test]
+fn test_error_is_retryable() {
+    assert!(SnowflakeError::ClockMovedBackwards.is_retryable());
+    assert!(SnowflakeError::SystemTimeError(Duration::from_millis(1)).is_retryable());
+    assert!(SnowflakeError::SequenceExhausted.is_retryable());
+    assert!(SnowflakeError::RateLimited.is_retryable());
+
+    let permanent = [
+        SnowflakeError::CenterIdInvalid,
+        SnowflakeError::WorkerIdInvalid,
+        SnowflakeError::EpochInvalid,
+        SnowflakeError::EncodingInvalid,
+        SnowflakeError::AlreadyInitialized,
+        SnowflakeError::LayoutInvalid(BitLayout::default()),
+        SnowflakeError::TimestampOverflow,
+        SnowflakeError::TimestampInvalid,
+        SnowflakeError::RadixInvalid,
+        SnowflakeError::DuplicateNode(3, 7),
+        SnowflakeError::NodeFileInvalid("missing".to_string()),
+        SnowflakeError::ConfigInvalid("malformed".to_string()),
+        SnowflakeError::ShardInvalid,
+        SnowflakeError::TagInvalid,
+        SnowflakeError::LockPoisoned,
+        SnowflakeError::ClockBeforeEpoch,
+        SnowflakeError::GeneratorNotFound,
+        SnowflakeError::SelfTestFailed("regressed".to_string()),
+    ];
+    for err in permanent {
+        assert!(!err.is_retryable(), "{}", err);
+    }
+}
+
+#[test]
+fn test_error_eq() {
+    let rvt = SnowflakeGenerator::new(Constants::MAX_DATA_CENTER_ID + 1, 1);
+    assert_eq!(SnowflakeError::CenterIdInvalid, rvt.unwrap_err());
+
+    assert_eq!(
+        SnowflakeError::DuplicateNode(3, 7),
+        SnowflakeError::DuplicateNode(3, 7)
+    );
+    assert_ne!(
+        SnowflakeError::DuplicateNode(3, 7),
+        SnowflakeError::DuplicateNode(7, 3)
+    );
+    assert_ne!(
+        SnowflakeError::SystemTimeError(Duration::from_millis(1)),
+        SnowflakeError::SystemTimeError(Duration::from_millis(2))
+    );
+    assert_ne!(
+        SnowflakeError::WorkerIdInvalid,
+        SnowflakeError::CenterIdInvalid
+    );
+}
+
+// ---------------------------------------------------------------- shard
+
+#[test]
+fn test_next_id_for_shard() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    let mut ids = HashSet::new();
+    for _ in 0..100 {
+        let id = gen.next_id_for_shard(3, 8).unwrap();
+        assert_eq!(3, id % 8);
+        assert!(ids.insert(id));
+    }
+
+    // interleaved with the plain IDs
+    let mut last = 0;
+    for shard in 0..16 {
+        let id = gen.next_id_for_shard(shard % 4, 4).unwrap();
+        assert_eq!(shard % 4, id % 4);
+        assert!(id > last);
+        assert!(ids.insert(id));
+        last = gen.next_id().unwrap();
+        assert!(ids.insert(last));
+    }
+}
+
+#[test]
+fn test_next_id_for_shard_invalid() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+    assert!(matches!(
+        gen.next_id_for_shard(0, 0),
+        Err(SnowflakeError::ShardInvalid)
+    ));
+    assert!(matches!(
+        gen.next_id_for_shard(8, 8),
+        Err(SnowflakeError::ShardInvalid)
+    ));
+    assert!(matches!(
+        gen.next_id_for_shard(0, Constants::SEQUENCE_MASK + 2),
+        Err(SnowflakeError::ShardInvalid)
+    ));
+    assert!(gen
+        .next_id_for_shard(0, Constants::SEQUENCE_MASK + 1)
+        .is_ok());
+}
+
+// ---------------------------------------------------------------- tag
+
+#[test]
+fn test_next_id_tagged() {
+    let layout = BitLayout::default().with_tag_bits(4);
+    assert_eq!(4, layout.tag_bits());
+    assert_eq!(15, layout.max_tag());
+    assert_eq!(255, layout.sequence_mask());
+    assert_eq!(0, BitLayout::default().max_tag());
+
+    let gen = SnowflakeGenerator::builder()
+        .center_id(3)
+        .worker_id(7)
+        .layout(layout)
+        .build()
+        .unwrap();
+
+    let mut ids = HashSet::new();
+    let mut last = 0;
+    for i in 0..1000 {
+        let tag = i % 16;
+        let id = gen.next_id_tagged(tag).unwrap();
+        assert!(id > last);
+        assert!(ids.insert(id));
+        last = id;
+
+        let parts = layout.decompose(id);
+        assert_eq!(tag, parts.tag);
+        assert_eq!(3, parts.center_id);
+        assert_eq!(7, parts.worker_id);
+        assert_eq!(
+            id,
+            layout.compose(
+                parts.timestamp,
+                parts.center_id,
+                parts.worker_id,
+                parts.sequence
+            ) | parts.tag
+        );
+    }
+
+    // the untagged IDs carry the tag zero
+    assert_eq!(0, layout.decompose(gen.next_id().unwrap()).tag);
+}
+
+#[test]
+fn test_next_id_tagged_invalid() {
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::default().with_tag_bits(4))
+        .build()
+        .unwrap();
+    assert!(matches!(
+        gen.next_id_tagged(16),
+        Err(SnowflakeError::TagInvalid)
+    ));
+    assert!(matches!(
+        gen.next_id_for_shard(3, 8),
+        Err(SnowflakeError::ShardInvalid)
+    ));
+
+    // no tag bits reserved, only the tag zero fits
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    assert!(gen.next_id_tagged(0).is_ok());
+    assert!(matches!(
+        gen.next_id_tagged(1),
+        Err(SnowflakeError::TagInvalid)
+    ));
+
+    let rvt = SnowflakeGenerator::builder()
+        .layout(BitLayout::default().with_tag_bits(13))
+        .build();
+    assert!(matches!(rvt, Err(SnowflakeError::LayoutInvalid(_))));
+}
+
+#[test]
+fn test_next_id_for_tenant() {
+    let layout = BitLayout::default().with_tag_bits(2);
+    let gen = SnowflakeGenerator::builder()
+        .layout(layout)
+        .build()
+        .unwrap();
+
+    let mut ids = HashSet::new();
+    for _ in 0..500 {
+        let first = gen.next_id_for_tenant(1).unwrap();
+        let second = gen.next_id_for_tenant(2).unwrap();
+        assert!(ids.insert(first));
+        assert!(ids.insert(second));
+
+        assert_eq!(1, layout.decompose(first).tenant());
+        assert_eq!(2, layout.decompose(second).tenant());
+    }
+
+    assert_eq!(
+        SnowflakeError::TagInvalid,
+        gen.next_id_for_tenant(4).unwrap_err()
+    );
+}
+
+// ---------------------------------------------------------------- typed ids
+
+#[test]
+fn test_typed_ids() {
+    let center_id = DatacenterId::new(3).unwrap();
+    let worker_id = WorkerId::new(7).unwrap();
+    assert_eq!(3, center_id.get());
+    assert_eq!(7, worker_id.get());
+
+    let gen = SnowflakeGenerator::from_ids(center_id, worker_id).unwrap();
+    assert_eq!(SnowflakeGenerator::new(3, 7).unwrap(), gen);
+
+    let parts = SnowflakeGenerator::decompose(gen.next_id().unwrap());
+    assert_eq!(3, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+}
+
+#[test]
+fn test_typed_ids_invalid() {
+    assert!(DatacenterId::new(Constants::MAX_DATA_CENTER_ID).is_ok());
+    assert!(WorkerId::new(Constants::MAX_WORKER_ID).is_ok());
+
+    assert!(matches!(
+        DatacenterId::new(Constants::MAX_DATA_CENTER_ID + 1),
+        Err(SnowflakeError::CenterIdInvalid)
+    ));
+    assert!(matches!(
+        WorkerId::new(Constants::MAX_WORKER_ID + 1),
+        Err(SnowflakeError::WorkerIdInvalid)
+    ));
+}
+
+// ---------------------------------------------------------------- fork
+
+#[test]
+fn test_fork_independent_state() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    gen.set_sequence(10);
+
+    let fork = gen.fork();
+    assert_eq!(gen, fork);
+    assert_eq!(10, fork.get_sequence());
+
+    fork.set_sequence(20);
+    assert_eq!(10, gen.get_sequence());
+    assert_eq!(20, fork.get_sequence());
+
+    // unlike the shared clone
+    let cloned = gen.clone();
+    cloned.set_sequence(30);
+    assert_eq!(30, gen.get_sequence());
+    assert_eq!(20, fork.get_sequence());
+}
+
+// ---------------------------------------------------------------- initial sequence
+
+#[test]
+fn test_initial_sequence() {
+    let gen = SnowflakeGenerator::builder()
+        .initial_sequence(100)
+        .build()
+        .unwrap();
+    assert_eq!(100, gen.get_sequence());
+
+    let now = Constants::EPOCH + 1000;
+    let first = gen.next_id_with_timestamp(now).unwrap();
+    let second = gen.next_id_with_timestamp(now).unwrap();
+    assert_eq!(100, SnowflakeGenerator::decompose(first).sequence);
+    assert_eq!(101, SnowflakeGenerator::decompose(second).sequence);
+
+    // the next millisecond starts from 0
+    let third = gen.next_id_with_timestamp(now + 1).unwrap();
+    assert_eq!(0, SnowflakeGenerator::decompose(third).sequence);
+    assert!(verify::verify_monotonic(&[first, second, third]).is_ok());
+}
+
+#[test]
+fn test_randomize_sequence() {
+    let gen = SnowflakeGenerator::builder()
+        .randomize_sequence(true)
+        .build()
+        .unwrap();
+
+    let now = Constants::EPOCH + 1000;
+    let mut ids = HashSet::new();
+    let mut starts = HashSet::new();
+    let mut last = 0;
+    for millis in now..now + 10 {
+        for i in 0..100 {
+            let id = gen.next_id_with_timestamp(millis).unwrap();
+            assert!(id > last);
+            assert!(ids.insert(id));
+            last = id;
+
+            let sequence = SnowflakeGenerator::decompose(id).sequence;
+            if i == 0 {
+                assert!(sequence <= Constants::SEQUENCE_MASK >> 1);
+                starts.insert(sequence);
+            }
+        }
+    }
+    assert!(starts.len() > 1);
+
+    // disabled by default, a new millisecond starts at zero
+    let gen = SnowflakeGenerator::new(1, 1).unwrap();
+    gen.next_id_with_timestamp(now).unwrap();
+    let id = gen.next_id_with_timestamp(now + 1).unwrap();
+    assert_eq!(0, SnowflakeGenerator::decompose(id).sequence);
+}
+
+#[test]
+fn test_initial_sequence_masked() {
+    let gen = SnowflakeGenerator::builder()
+        .initial_sequence(Constants::SEQUENCE_MASK + 1 + 100)
+        .build()
+        .unwrap();
+    assert_eq!(100, gen.get_sequence());
+
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::new(5, 5, 8).unwrap())
+        .initial_sequence(0x1ff)
+        .build()
+        .unwrap();
+    assert_eq!(0xff, gen.get_sequence());
+}
+
+// ---------------------------------------------------------------- snapshot
+
+#[test]
+fn test_snapshot_restore_monotonic() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    let ids: Vec<u64> = (0..1_000).map(|_| gen.next_id().unwrap()).collect();
+    let last = *ids.last().unwrap();
+
+    let state = gen.snapshot();
+    let parts = SnowflakeGenerator::decompose(last);
+    assert_eq!(parts.unix_millis(), state.last_timestamp);
+    assert_eq!(parts.sequence, state.sequence);
+
+    // mutates the state after the snapshot, the stale snapshot never moves it backwards
+    let latest = gen.next_id().unwrap();
+    gen.restore(state);
+    assert_eq!(
+        SnowflakeGenerator::decompose(latest).sequence,
+        gen.snapshot().sequence
+    );
+    assert!(gen.next_id().unwrap() > latest);
+
+    // restarted
+    let restarted = SnowflakeGenerator::new(3, 7).unwrap();
+    restarted.restore(gen.snapshot());
+    let next = restarted.next_id().unwrap();
+    assert!(next > latest);
+    assert!(verify::verify_monotonic(&[last, latest, next]).is_ok());
+}
+
+#[test]
+fn test_restore_ahead_of_clock_waits() {
+    let restored = Constants::EPOCH + 1000;
+    // the clock behind the restored timestamp by 20ms, then caught up
+    let clock = MockClock::new(&[restored - 20, restored - 20, restored + 1]);
+
+    let gen = SnowflakeGenerator::builder()
+        .clock(Arc::new(clock))
+        .max_backward_tolerance_millis(0)
+        .build()
+        .unwrap();
+    gen.restore(GeneratorState {
+        last_timestamp: restored,
+        sequence: 5,
+    });
+
+    let started = Instant::now();
+    let id = gen.next_id().unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(1001, parts.timestamp);
+    assert_eq!(0, parts.sequence);
+}
+
+#[test]
+fn test_restore_ahead_of_clock_with_timestamp() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    let restored = Constants::EPOCH + 1000;
+    gen.restore(GeneratorState {
+        last_timestamp: restored,
+        sequence: 5,
+    });
+
+    // never waits
+    assert!(matches!(
+        gen.next_id_with_timestamp(restored - 1),
+        Err(SnowflakeError::ClockMovedBackwards)
+    ));
+    let parts = SnowflakeGenerator::decompose(gen.next_id_with_timestamp(restored).unwrap());
+    assert_eq!(6, parts.sequence);
+}
+
+// ---------------------------------------------------------------- verify
+
+#[test]
+fn test_verify_unique() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let mut ids: Vec<u64> = (0..10_000).map(|_| gen.next_id().unwrap()).collect();
+    assert!(verify::verify_unique(&ids).is_ok());
+    assert!(verify::verify_monotonic(&ids).is_ok());
+
+    // plant a duplicate
+    ids[7_000] = ids[42];
+    let err = verify::verify_unique(&ids).unwrap_err();
+    assert_eq!(ids[42], err.id);
+    assert_eq!(42, err.first);
+    assert_eq!(7_000, err.second);
+
+    let err = verify::verify_monotonic(&ids).unwrap_err();
+    assert_eq!(ids[6_999], err.previous);
+    assert_eq!(ids[42], err.next);
+    assert_eq!(7_000, err.index);
+}
+
+#[test]
+fn test_verify_empty() {
+    assert!(verify::verify_unique(&[]).is_ok());
+    assert!(verify::verify_monotonic(&[]).is_ok());
+    assert!(verify::verify_monotonic(&[1]).is_ok());
+    assert!(verify::verify_monotonic(&[1, 1]).is_err());
+}
+
+// ---------------------------------------------------------------- codec
+
+#[test]
+fn test_base62_round_trip() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let mut ids = vec![0, 1, 61, 62, u64::MAX - 1, u64::MAX];
+    ids.extend(gen.iter().take(100).map(|rvt| rvt.unwrap()));
+
+    for id in ids {
+        let encoded = codec::encode_base62(id);
+        assert_eq!(id, codec::decode_base62(&encoded).unwrap());
+    }
+}
+
+#[test]
+fn test_base62_decode_invalid() {
+    assert!(matches!(
+        codec::decode_base62(""),
+        Err(SnowflakeError::EncodingInvalid)
+    ));
+    assert!(matches!(
+        codec::decode_base62("abc-def"),
+        Err(SnowflakeError::EncodingInvalid)
+    ));
+    // u64::MAX + 1
+    assert!(matches!(
+        codec::decode_base62("LygHa16AHYG"),
+        Err(SnowflakeError::EncodingInvalid)
+    ));
+}
+
+#[test]
+fn test_parse_any() {
+    let id = SnowflakeGenerator::builtin().unwrap().next_id().unwrap();
+
+    assert_eq!(id, codec::parse_any(&id.to_string()).unwrap());
+    assert_eq!(
+        id,
+        codec::parse_any(&format!("0x{}", codec::to_hex(id))).unwrap()
+    );
+    assert_eq!(id, codec::parse_any(&format!("0X{:X}", id)).unwrap());
+    assert_eq!(id, codec::parse_any(&codec::encode_base62(id)).unwrap());
+
+    // decimal > hex > base62
+    assert_eq!(10, codec::parse_any("10").unwrap());
+    assert_eq!(16, codec::parse_any("0x10").unwrap());
+    assert_eq!(
+        codec::decode_base62("1a").unwrap(),
+        codec::parse_any("1a").unwrap()
+    );
+}
+
+#[test]
+fn test_sortable_key() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let mut ids = vec![0, 1, 31, 32, u64::MAX - 1, u64::MAX];
+    ids.extend(gen.iter().take(100).map(|rvt| rvt.unwrap()));
+
+    // a deterministic shuffle
+    let mut shuffled = ids.clone();
+    for i in (1..shuffled.len()).rev() {
+        shuffled.swap(i, (i * 7919) % (i + 1));
+    }
+
+    let mut keys: Vec<String> = shuffled
+        .iter()
+        .map(|id| codec::to_sortable_key(*id))
+        .collect();
+    keys.sort();
+    ids.sort();
+
+    let decoded: Vec<u64> = keys
+        .iter()
+        .map(|key| codec::from_sortable_key(key).unwrap())
+        .collect();
+    assert_eq!(ids, decoded);
+    assert!(keys.iter().all(|key| key.len() == codec::SORTABLE_KEY_LEN));
+
+    assert!(codec::from_sortable_key("000000000000U").is_err());
+    assert!(codec::from_sortable_key("00000000000000").is_err());
+}
+
+#[test]
+fn test_next_id_prefixed() {
+    let gen = SnowflakeGenerator::new(3, 7).unwrap();
+    let prefixed = gen.next_id_prefixed("ORD-").unwrap();
+    let id = codec::strip_prefix_parse(&prefixed, "ORD-").unwrap();
+    assert_eq!(format!("ORD-{}", id), prefixed);
+    assert_eq!(
+        (3, 7),
+        (
+            SnowflakeGenerator::decompose(id).center_id,
+            SnowflakeGenerator::decompose(id).worker_id
+        )
+    );
+
+    let prefixed = gen.next_id_prefixed_radix("ORD-", 36).unwrap();
+    let next = codec::strip_prefix_parse_radix(&prefixed, "ORD-", 36).unwrap();
+    assert!(next > id);
+    assert_eq!(
+        SnowflakeError::RadixInvalid,
+        gen.next_id_prefixed_radix("ORD-", 1).unwrap_err()
+    );
+
+    for invalid in [
+        "122235238222008321",
+        "INV-1",
+        "ORD-",
+        "ORD-+1",
+        "ORD-x1",
+        "ord-1",
+    ] {
+        assert_eq!(
+            SnowflakeError::EncodingInvalid,
+            codec::strip_prefix_parse(invalid, "ORD-").unwrap_err()
+        );
+    }
+    assert_eq!(1, codec::strip_prefix_parse("1", "").unwrap());
+}
+
+#[test]
+fn test_id_range_for_time() {
+    let layout = BitLayout::default();
+    let id = SnowflakeGenerator::new(3, 7).unwrap().next_id().unwrap();
+    let millis = SnowflakeGenerator::timestamp_of(id);
+
+    let (min, max) = codec::id_range_for_time(millis - 1, millis + 1, &layout, Constants::EPOCH);
+    assert!(min <= id && id <= max);
+    assert_eq!(
+        (min, max),
+        codec::id_range_for_time(millis + 1, millis - 1, &layout, Constants::EPOCH)
+    );
+
+    // the exact bounds of a millisecond
+    let (min, max) = codec::id_range_for_time(millis, millis, &layout, Constants::EPOCH);
+    assert_eq!(
+        (millis - Constants::EPOCH) << Constants::TIMESTAMP_SHIFT,
+        min
+    );
+    assert_eq!(min + (1 << Constants::TIMESTAMP_SHIFT) - 1, max);
+
+    // the IDs of the neighbouring milliseconds fall outside
+    let (min, max) = codec::id_range_for_time(millis + 1, millis + 2, &layout, Constants::EPOCH);
+    assert!(id < min && id < max);
+
+    // clamped into the epoch
+    let (min, _) = codec::id_range_for_time(0, millis, &layout, Constants::EPOCH);
+    assert_eq!(0, min);
+}
+
+#[test]
+fn test_wait_until_current() {
+    let id = SnowflakeGenerator::builtin().unwrap().next_id().unwrap();
+    let start = Instant::now();
+    codec::wait_until_current(id).unwrap();
+    assert!(start.elapsed() < Duration::from_millis(50));
+
+    // an ID 20ms in the future
+    let now = UNIX_EPOCH.elapsed().unwrap().as_millis() as u64;
+    let id = (now + 20 - Constants::EPOCH) << Constants::TIMESTAMP_SHIFT;
+    codec::wait_until_current(id).unwrap();
+    assert!(UNIX_EPOCH.elapsed().unwrap().as_millis() as u64 >= now + 20);
+}
+
+#[test]
+fn test_decompose_many() {
+    assert!(codec::decompose_many(&[], &BitLayout::default()).is_empty());
+
+    // splitmix64, a deterministic random sample
+    let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+    let ids: Vec<u64> = (0..10_000)
+        .map(|_| {
+            seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        })
+        .collect();
+
+    for layout in [
+        BitLayout::default(),
+        BitLayout::new(3, 7, 12).unwrap(),
+        BitLayout::default().with_tag_bits(4),
+    ] {
+        let parts = codec::decompose_many(&ids, &layout);
+        assert_eq!(ids.len(), parts.len());
+        for (id, part) in ids.iter().zip(parts) {
+            assert_eq!(layout.decompose(*id), part);
+        }
+    }
+}
+
+#[test]
+fn test_parse_any_invalid() {
+    // "18446744073709551616": u64::MAX + 1 in decimal, overflows base62 too
+    for s in [
+        "",
+        "-1",
+        "+1",
+        "0x-1",
+        "abc-def",
+        "LygHa16AHYG",
+        "18446744073709551616",
+    ] {
+        assert!(
+            matches!(codec::parse_any(s), Err(SnowflakeError::EncodingInvalid)),
+            "{}",
+            s
+        );
+    }
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_next_id_i64() {
+    let id = next_id_i64().unwrap();
+    assert!(id > 0);
+
+    let gen = SnowflakeGenerator::builtin().unwrap();
+    let id = gen.next_id().unwrap();
+    let signed = SnowflakeGenerator::as_i64(id).unwrap();
+    assert_eq!(id, signed as u64);
+    assert!(gen.next_id_i64().unwrap() > signed);
+
+    assert_eq!(Some(0), SnowflakeGenerator::as_i64(0));
+    assert_eq!(Some(i64::MAX), SnowflakeGenerator::as_i64(i64::MAX as u64));
+    assert_eq!(None, SnowflakeGenerator::as_i64(u64::MAX));
+}
+
+#[cfg(feature = "global")]
+#[test]
+fn test_next_id_base62() {
+    let rvt = next_id_base62();
+    assert!(rvt.is_ok());
+    assert!(codec::decode_base62(&rvt.unwrap()).is_ok());
+}
+
+#[test]
+fn test_codec_hex_round_trip() {
+    for id in [0, 1, 255, 1 << 32, u64::MAX - 1, u64::MAX] {
+        let encoded = codec::to_hex(id);
+        assert_eq!(16, encoded.len());
+        assert_eq!(id, codec::from_hex(&encoded).unwrap());
+    }
+
+    assert_eq!("00000000000000ff", codec::to_hex(255));
+    assert_eq!("ffffffffffffffff", codec::to_hex(u64::MAX));
+    assert_eq!(255, codec::from_hex("FF").unwrap());
+}
+
+#[test]
+fn test_codec_hex_invalid() {
+    for s in ["", "10000000000000000", "xyz", "-1", "+1", " 1"] {
+        assert!(matches!(
+            codec::from_hex(s),
+            Err(SnowflakeError::EncodingInvalid)
+        ));
+    }
+}
+
+#[test]
+fn test_codec_be_bytes_round_trip() {
+    for id in [0, 1, 255, 1 << 32, u64::MAX - 1, u64::MAX] {
+        assert_eq!(id, codec::id_from_be_bytes(codec::id_to_be_bytes(id)));
+    }
 
-    assert_eq!(12, Constants::WORKER_ID_SHIFT);
-    assert_eq!(17, Constants::CENTER_ID_SHIFT);
-    assert_eq!(22, Constants::TIMESTAMP_SHIFT);
+    assert_eq!([0, 0, 0, 0, 0, 0, 1, 0], codec::id_to_be_bytes(256));
 }
 
 #[test]
-fn test_next_id() {
-    // 122235238222008321
-    let rvt = next_id();
-    assert!(rvt.is_ok());
+fn test_codec_be_bytes_order() {
+    let gen = SnowflakeGenerator::builtin().unwrap();
+
+    let mut last = gen.next_id_bytes().unwrap();
+    for _ in 0..1000 {
+        let bytes = gen.next_id_bytes().unwrap();
+        // the byte-wise order follows the numeric order
+        assert!(last < bytes);
+        assert!(codec::id_from_be_bytes(last) < codec::id_from_be_bytes(bytes));
+        last = bytes;
+    }
+
+    for (a, b) in [(255, 256), (1 << 32, u64::MAX), (0, 1)] {
+        assert!(codec::id_to_be_bytes(a) < codec::id_to_be_bytes(b));
+    }
 }
 
+#[cfg(feature = "global")]
 #[test]
-fn test_next_id_string() {
-    // 122256588529602560
-    let rvt = next_id_string();
-    assert!(rvt.is_ok());
+fn test_next_id_bytes() {
+    let bytes = next_id_bytes().unwrap();
+    assert!(codec::id_from_be_bytes(bytes) > 0);
 }
 
+#[cfg(feature = "global")]
 #[test]
-fn test_generator_new_failed() {
-    let gen = SnowflakeGenerator::new(32, 32);
-    assert!(gen.is_err());
+fn test_codec_radix() {
+    assert_eq!(
+        "00000000000000ff",
+        codec::to_radix(255, 16, 16, '0').unwrap()
+    );
+    assert_eq!(
+        codec::to_hex(u64::MAX),
+        codec::to_radix(u64::MAX, 16, 16, '0').unwrap()
+    );
+    assert_eq!(
+        "3w5e11264sgsf",
+        codec::to_radix(u64::MAX, 36, 13, '0').unwrap()
+    );
+    assert_eq!("0", codec::to_radix(0, 2, 0, '0').unwrap());
+    assert_eq!("  10", codec::to_radix(36, 36, 4, ' ').unwrap());
+
+    let id = next_id().unwrap();
+    let encoded = codec::to_radix(id, 36, 20, '0').unwrap();
+    assert_eq!(20, encoded.len());
+    assert_eq!(id, u64::from_str_radix(&encoded, 36).unwrap());
 }
 
 #[test]
-fn test_generator_new_ok() {
-    let gen = SnowflakeGenerator::new(31, 31);
-    assert!(gen.is_ok());
+fn test_codec_radix_width_too_small() {
+    // never truncated
+    let encoded = codec::to_radix(u64::MAX, 16, 4, '0').unwrap();
+    assert_eq!("ffffffffffffffff", encoded);
 }
 
+#[cfg(feature = "global")]
 #[test]
-fn test_generator_builtin_ok() {
-    let gen = SnowflakeGenerator::builtin();
-    assert!(gen.is_ok());
+fn test_codec_radix_invalid() {
+    for radix in [0, 1, 37] {
+        assert!(matches!(
+            codec::to_radix(1, radix, 16, '0'),
+            Err(SnowflakeError::RadixInvalid)
+        ));
+        assert!(matches!(
+            next_id_radix(radix, 16, '0'),
+            Err(SnowflakeError::RadixInvalid)
+        ));
+    }
 }
 
+#[cfg(feature = "global")]
 #[test]
-fn test_generator_next_id() {
-    // 122235451737247745
-    // 122_235_451_737_247_745 -> 18
-    let rvt = generator().lock().unwrap().as_ref().unwrap().next_id();
-    assert!(rvt.is_ok());
+fn test_next_id_radix() {
+    let id = next_id_radix(16, 16, '0').unwrap();
+    assert_eq!(16, id.len());
+    assert!(codec::from_hex(&id).is_ok());
+
+    let id = next_id_radix(36, 16, '0').unwrap();
+    assert_eq!(16, id.len());
+    assert!(u64::from_str_radix(&id, 36).is_ok());
 }
 
+#[cfg(feature = "global")]
 #[test]
-fn test_custom_new_next_id() {
-    let center_id = 16;
-    let worker_id = 16;
-
-    let gen = SnowflakeGenerator::new(center_id, worker_id);
-    assert!(gen.is_ok());
-    let rvt = gen.unwrap().next_id();
+fn test_next_id_hex() {
+    let rvt = next_id_hex();
     assert!(rvt.is_ok());
+
+    let hex = rvt.unwrap();
+    assert_eq!(16, hex.len());
+    assert!(codec::from_hex(&hex).is_ok());
 }
 
 // ----------------------------------------------------------------
@@ -91,30 +3430,140 @@ fn test_hash_base() {
     assert_eq!(31, hashcode::HASH_BASE);
 }
 
+#[test]
+fn test_hashcode_str() {
+    use crate::hashcode::HashCode;
+
+    assert_eq!("abc".hashcode(), "abc".to_string().hashcode());
+    assert_eq!(96354, "abc".hashcode());
+    assert_eq!(0, "".hashcode());
+    assert_eq!("abc".hashcode(), hashcode::hashcode_of("abc".chars()));
+}
+
+#[test]
+fn test_hashcode_bytes() {
+    use crate::hashcode::HashCode;
+
+    let bytes: &[u8] = b"abc";
+    assert_eq!("abc".hashcode(), bytes.hashcode());
+    assert_eq!(1_024u64, 1_024u64.hashcode());
+}
+
+#[test]
+fn test_hashcode_long_str_not_overflow() {
+    use crate::hashcode::HashCode;
+
+    let s = "a".repeat(128);
+    assert_eq!(s.as_str().hashcode(), s.hashcode());
+
+    // e.g. a long hostname, wraps around in the debug builds instead of panicking
+    let hostname = "node-".repeat(2_000);
+    let wrapped = hostname.bytes().fold(0u64, |hash, byte| {
+        31u64.wrapping_mul(hash).wrapping_add(byte as u64)
+    });
+    assert_eq!(wrapped, hostname.hashcode());
+    assert_eq!(wrapped, hostname.as_bytes().hashcode());
+}
+
+#[test]
+fn test_hashcode_with_base() {
+    use crate::hashcode::HashCode;
+
+    assert_eq!(
+        "abc".hashcode(),
+        hashcode::hashcode_with("abc", hashcode::HASH_BASE)
+    );
+    assert_eq!("abc".hashcode(), "abc".hashcode_with_base(31));
+    assert_eq!(
+        hashcode::hashcode_with("abc", 131),
+        "abc".to_string().hashcode_with_base(131)
+    );
+    assert_eq!(
+        hashcode::hashcode_with("abc", 131),
+        b"abc".as_slice().hashcode_with_base(131)
+    );
+    // 97 * 131^2 + 98 * 131 + 99
+    assert_eq!(1_677_554, hashcode::hashcode_with("abc", 131));
+    // base-independent
+    assert_eq!(1_024u64, 1_024u64.hashcode_with_base(131));
+}
+
+#[test]
+fn test_hashcode_with_base_distribution() {
+    let hostnames: Vec<String> = (0..32)
+        .map(|n| format!("app-node-{:02}.prod.example.com", n))
+        .collect();
+
+    let buckets = |base: u64| -> HashSet<u64> {
+        hostnames
+            .iter()
+            .map(|host| hashcode::hashcode_with(host, base) % (Constants::MAX_WORKER_ID + 1))
+            .collect()
+    };
+
+    // the distinct worker IDs of the hostnames differing in the trailing digits only
+    let base_31 = buckets(31);
+    let base_131 = buckets(131);
+    assert_eq!(13, base_31.len());
+    assert_eq!(16, base_131.len());
+    assert!(base_131.len() >= base_31.len());
+}
+
 // ---------------------------------------------------------------- macros
 
+#[cfg(feature = "global")]
 #[test]
 fn test_macro_snowflake_builtin() {
     let rvt = snowflake_builtin!();
     assert!(rvt.is_ok());
 }
 
+#[cfg(feature = "global")]
 #[test]
 fn test_macro_snowflake_builtin_string() {
     let rvt = snowflake_builtin_string!();
     assert!(rvt.is_ok());
 }
 
+#[test]
+fn test_macro_snowflake_with() {
+    let gen = snowflake_with!(center = 3, worker = 7).unwrap();
+    assert_eq!(3, gen.center_id());
+    assert_eq!(7, gen.worker_id());
+
+    let gen = snowflake_with!(center = 3, worker = 32);
+    assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+}
+
+#[test]
+fn test_macro_snowflake_next() {
+    let id = snowflake_next!(center = 3, worker = 7).unwrap();
+    let parts = SnowflakeGenerator::decompose(id);
+    assert_eq!(3, parts.center_id);
+    assert_eq!(7, parts.worker_id);
+
+    let rvt = snowflake_next!(center = 32, worker = 7,);
+    assert!(matches!(rvt, Err(SnowflakeError::CenterIdInvalid)));
+}
+
 // ----------------------------------------------------------------
 
 #[cfg(test)]
 #[cfg(feature = "dynamic")]
 mod feature_dynamic_tests {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::env::VarError;
+    use std::net::Ipv4Addr;
+    use std::sync::Mutex;
     use std::thread;
 
-    use crate::{dynamic_next_id, dynamic_next_id_string, infras};
+    use crate::hashcode::HashCode;
+    use crate::identity::{HostnameIdentityProvider, MacIdentityProvider};
+    use crate::infras::NetworkInterface;
+    use crate::{dynamic_next_id, dynamic_next_id_string, infras, reset_dynamic};
     // @since 0.3.0
-    use crate::generator::{Constants, Generator, SnowflakeGenerator};
+    use crate::generator::{Constants, Generator, SnowflakeError, SnowflakeGenerator};
 
     #[test]
     fn test_try_get_data_center_id() {
@@ -122,11 +3571,416 @@ mod feature_dynamic_tests {
         assert!(center_id <= Constants::MAX_DATA_CENTER_ID);
     }
 
+    struct MockInterface {
+        name: &'static str,
+        mac: &'static str,
+        up: bool,
+        ipv4s: Vec<Ipv4Addr>,
+    }
+
+    impl NetworkInterface for MockInterface {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn mac(&self) -> &str {
+            self.mac
+        }
+
+        fn is_up(&self) -> bool {
+            self.up
+        }
+
+        fn ipv4s(&self) -> Vec<Ipv4Addr> {
+            self.ipv4s.clone()
+        }
+    }
+
+    fn interface(name: &'static str, mac: &'static str, up: bool) -> MockInterface {
+        MockInterface {
+            name,
+            mac,
+            up,
+            ipv4s: Vec::new(),
+        }
+    }
+
+    fn interface_with_ipv4(name: &'static str, up: bool, ipv4s: &[&str]) -> MockInterface {
+        MockInterface {
+            ipv4s: ipv4s.iter().map(|ipv4| ipv4.parse().unwrap()).collect(),
+            ..interface(name, "aa:bb:cc:dd:ee:ff", up)
+        }
+    }
+
+    #[test]
+    fn test_select_interface_prefers_up_physical() {
+        let interfaces = [
+            interface("Loopback Pseudo-Interface 1", "00-00-00-00-00-01", true),
+            interface("docker0", "02:42:ac:11:00:01", true),
+            interface("eth1", "aa:bb:cc:dd:ee:01", false),
+            interface("vpn0", "00:00:00:00:00:00", true),
+            interface("eth0", "aa:bb:cc:dd:ee:02", true),
+        ];
+
+        let mac = infras::try_select_interface_mac(&interfaces).unwrap();
+        assert_eq!(vec![0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x02], mac);
+    }
+
+    #[test]
+    fn test_select_interface_falls_back_to_virtual() {
+        let interfaces = [
+            interface("eth0", "aa-bb-cc-dd-ee-01", false),
+            interface("docker0", "02-42-ac-11-00-01", true),
+        ];
+
+        let mac = infras::try_select_interface_mac(&interfaces).unwrap();
+        assert_eq!(vec![0x02, 0x42, 0xac, 0x11, 0x00, 0x01], mac);
+    }
+
+    #[test]
+    fn test_select_interface_falls_back_to_first_non_loopback() {
+        let interfaces = [
+            interface("Loopback Pseudo-Interface 1", "00-00-00-00-00-01", true),
+            interface("eth0", "aa-bb-cc-dd-ee-01", false),
+            interface("eth1", "aa-bb-cc-dd-ee-02", false),
+        ];
+
+        let mac = infras::try_select_interface_mac(&interfaces).unwrap();
+        assert_eq!(vec![0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x01], mac);
+
+        let interfaces = [interface("Loopback", "00-00-00-00-00-01", true)];
+        assert!(infras::try_select_interface_mac(&interfaces).is_err());
+    }
+
+    #[test]
+    fn test_select_interface_ipv4() {
+        let interfaces = [
+            interface_with_ipv4("Loopback Pseudo-Interface 1", true, &["10.0.0.1"]),
+            interface_with_ipv4("lo", true, &["127.0.0.1"]),
+            interface_with_ipv4("eth1", false, &["10.0.0.2"]),
+            interface_with_ipv4("eth0", true, &["0.0.0.0", "192.168.1.37"]),
+        ];
+
+        let ipv4 = infras::try_select_interface_ipv4(&interfaces).unwrap();
+        assert_eq!(Ipv4Addr::new(192, 168, 1, 37), ipv4);
+        // 37 % 32
+        assert_eq!(5, infras::data_center_id_of_ipv4(ipv4));
+        assert_eq!(
+            31,
+            infras::data_center_id_of_ipv4(Ipv4Addr::new(10, 0, 0, 255))
+        );
+
+        // falls back to the interfaces that are down
+        let interfaces = [interface_with_ipv4("eth1", false, &["10.0.0.2"])];
+        let ipv4 = infras::try_select_interface_ipv4(&interfaces);
+        assert_eq!(Some(Ipv4Addr::new(10, 0, 0, 2)), ipv4);
+
+        let interfaces = [interface("eth0", "aa:bb:cc:dd:ee:01", true)];
+        assert!(infras::try_select_interface_ipv4(&interfaces).is_none());
+
+        let center_id = infras::try_get_ipv4_data_center_id();
+        assert!(center_id <= Constants::MAX_DATA_CENTER_ID);
+    }
+
+    #[test]
+    fn test_data_center_id_of_short_mac() {
+        let default = Constants::DEFAULT_DATA_CENTER_ID & Constants::MAX_DATA_CENTER_ID;
+
+        // 0-byte
+        let interfaces = [interface("eth0", "", true)];
+        let mac = infras::try_select_interface_mac(&interfaces).unwrap();
+        assert!(mac.is_empty());
+        assert_eq!(default, infras::data_center_id_of(&mac));
+
+        // 1-byte
+        let interfaces = [interface("eth0", "aa", true)];
+        let mac = infras::try_select_interface_mac(&interfaces).unwrap();
+        assert_eq!(vec![0xaa], mac);
+        assert_eq!(default, infras::data_center_id_of(&mac));
+
+        // 2-byte
+        assert_eq!(
+            0xbb & Constants::MAX_DATA_CENTER_ID,
+            infras::data_center_id_of(&[0xaa, 0xbb])
+        );
+    }
+
+    fn env(vars: &[(&'static str, &'static str)]) -> impl Fn(&str) -> Result<String, VarError> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        move |key| {
+            vars.get(key)
+                .map(|value| value.to_string())
+                .ok_or(VarError::NotPresent)
+        }
+    }
+
+    #[test]
+    fn test_generator_dynamic_with_env() {
+        let gen = SnowflakeGenerator::dynamic_with_env(env(&[
+            (infras::SNOWFLAKE_DATACENTER_ID, "3"),
+            (infras::SNOWFLAKE_WORKER_ID, " 7 "),
+        ]))
+        .unwrap();
+        assert_eq!(3, gen.center_id());
+        assert_eq!(7, gen.worker_id());
+
+        let gen = SnowflakeGenerator::dynamic_with_env(env(&[(infras::SNOWFLAKE_WORKER_ID, "7")]))
+            .unwrap();
+        assert_eq!(infras::try_get_data_center_id(), gen.center_id());
+        assert_eq!(7, gen.worker_id());
+    }
+
+    #[test]
+    fn test_generator_dynamic_with_env_invalid() {
+        let gen =
+            SnowflakeGenerator::dynamic_with_env(env(&[(infras::SNOWFLAKE_DATACENTER_ID, "32")]));
+        assert!(matches!(gen, Err(SnowflakeError::CenterIdInvalid)));
+
+        let gen =
+            SnowflakeGenerator::dynamic_with_env(env(&[(infras::SNOWFLAKE_WORKER_ID, "abc")]));
+        assert!(matches!(gen, Err(SnowflakeError::WorkerIdInvalid)));
+
+        let gen = SnowflakeGenerator::dynamic_with_env(|_| {
+            Err(VarError::NotUnicode(std::ffi::OsString::new()))
+        });
+        assert!(matches!(gen, Err(SnowflakeError::CenterIdInvalid)));
+    }
+
+    #[test]
+    fn test_generator_with_mac_identity() {
+        let gen = SnowflakeGenerator::with_identity(&MacIdentityProvider).unwrap();
+
+        let center_id = infras::try_get_data_center_id();
+        assert_eq!(center_id, gen.center_id());
+        assert_eq!(infras::try_get_worker_id(center_id), gen.worker_id());
+    }
+
+    #[test]
+    fn test_identity_cached() {
+        let cache = Mutex::new(None);
+        let lookups = infras::MAC_LOOKUPS.with(Cell::get);
+
+        let identity = infras::identity_of(&cache);
+        assert_eq!(identity, infras::identity_of(&cache));
+        assert_eq!(identity, infras::identity_of(&cache));
+        // the MAC lookup runs only once
+        assert_eq!(1, infras::MAC_LOOKUPS.with(Cell::get) - lookups);
+
+        *cache.lock().unwrap() = None;
+        assert_eq!(identity, infras::identity_of(&cache));
+        assert_eq!(2, infras::MAC_LOOKUPS.with(Cell::get) - lookups);
+    }
+
+    #[test]
+    fn test_generator_dynamic_cached() {
+        infras::clear_identity_cache();
+
+        let a = SnowflakeGenerator::dynamic().unwrap();
+        let b = SnowflakeGenerator::dynamic().unwrap();
+        assert_eq!(
+            (a.center_id(), a.worker_id()),
+            (b.center_id(), b.worker_id())
+        );
+        assert_eq!((a.center_id(), a.worker_id()), infras::try_get_identity());
+    }
+
+    #[test]
+    fn test_reset_dynamic() {
+        assert!(dynamic_next_id().is_ok());
+
+        let rvt = reset_dynamic(3, 7);
+        assert!(rvt.is_ok());
+
+        let parts = SnowflakeGenerator::decompose(dynamic_next_id().unwrap());
+        assert_eq!(3, parts.center_id);
+        assert_eq!(7, parts.worker_id);
+
+        let rvt = reset_dynamic(3, 32);
+        assert!(matches!(rvt, Err(SnowflakeError::WorkerIdInvalid)));
+
+        let parts = SnowflakeGenerator::decompose(dynamic_next_id().unwrap());
+        assert_eq!(7, parts.worker_id);
+    }
+
     #[test]
     fn test_try_get_worker_id() {
         let center_id = infras::try_get_data_center_id();
         let worker_id = infras::try_get_worker_id(center_id);
         assert!(worker_id <= Constants::MAX_WORKER_ID);
+        // the tests never write into the real cache dir
+        assert!(infras::worker_id_cache_file(center_id).starts_with(std::env::temp_dir()));
+    }
+
+    #[test]
+    fn test_claim_worker_id() {
+        let cache_file = std::env::temp_dir()
+            .join(format!("snowflaker-claim-{}", std::process::id()))
+            .join("worker-id-3");
+        // each holder stands for a process
+        let holder = || Mutex::new(Vec::new());
+        let (a, b, c) = (holder(), holder(), holder());
+
+        // the concurrent processes probe the next slot
+        assert_eq!(9, infras::claim_worker_id_with(&cache_file, 9, &a));
+        assert_eq!(10, infras::claim_worker_id_with(&cache_file, 9, &b));
+        assert_eq!(11, infras::claim_worker_id_with(&cache_file, 9, &c));
+
+        // the own slot is kept
+        assert_eq!(9, infras::claim_worker_id_with(&cache_file, 9, &a));
+        assert_eq!(10, infras::claim_worker_id_with(&cache_file, 9, &b));
+        assert_eq!(1, a.lock().unwrap().len());
+
+        // released on exit, the restart claims the same slot again
+        drop(a);
+        let restarted = holder();
+        assert_eq!(9, infras::claim_worker_id_with(&cache_file, 9, &restarted));
+
+        // wraps around
+        let (d, e) = (holder(), holder());
+        assert_eq!(
+            Constants::MAX_WORKER_ID,
+            infras::claim_worker_id_with(&cache_file, Constants::MAX_WORKER_ID, &d)
+        );
+        assert_eq!(
+            0,
+            infras::claim_worker_id_with(&cache_file, Constants::MAX_WORKER_ID, &e)
+        );
+
+        // all taken, the preferred as is
+        let holders: Vec<_> = (0..32).map(|_| holder()).collect();
+        for holder in &holders {
+            infras::claim_worker_id_with(&cache_file, 0, holder);
+        }
+        assert_eq!(9, infras::claim_worker_id_with(&cache_file, 9, &holder()));
+
+        drop((b, c, d, e, restarted, holders));
+        let _ = std::fs::remove_dir_all(cache_file.parent().unwrap());
+    }
+
+    #[test]
+    fn test_try_get_worker_id_persisted() {
+        let cache_file = std::env::temp_dir()
+            .join(format!("snowflaker-test-{}", std::process::id()))
+            .join("worker-id-3");
+        let _ = std::fs::remove_file(&cache_file);
+
+        // the pid-hash as a last resort, persisted
+        let worker_id = infras::worker_id_with(env(&[]), Some(&cache_file), 3);
+        assert!(worker_id <= Constants::MAX_WORKER_ID);
+        assert_eq!(
+            worker_id.to_string(),
+            std::fs::read_to_string(&cache_file).unwrap()
+        );
+
+        // the second call reads the persisted ID
+        std::fs::write(&cache_file, "9").unwrap();
+        assert_eq!(9, infras::worker_id_with(env(&[]), Some(&cache_file), 3));
+
+        // the env var takes precedence, and is persisted
+        let worker_id = infras::worker_id_with(
+            env(&[(infras::SNOWFLAKE_WORKER_ID, "7")]),
+            Some(&cache_file),
+            3,
+        );
+        assert_eq!(7, worker_id);
+        assert_eq!(7, infras::worker_id_with(env(&[]), Some(&cache_file), 3));
+
+        // an invalid env var or a malformed file falls back
+        std::fs::write(&cache_file, "abc").unwrap();
+        let worker_id = infras::worker_id_with(
+            env(&[(infras::SNOWFLAKE_WORKER_ID, "32")]),
+            Some(&cache_file),
+            3,
+        );
+        assert!(worker_id <= Constants::MAX_WORKER_ID);
+        assert_eq!(
+            worker_id,
+            infras::worker_id_with(env(&[]), Some(&cache_file), 3)
+        );
+
+        let _ = std::fs::remove_dir_all(cache_file.parent().unwrap());
+    }
+
+    #[test]
+    fn test_hostname_worker_id() {
+        let hostname_file =
+            std::env::temp_dir().join(format!("snowflaker-hostname-{}", std::process::id()));
+        std::fs::write(&hostname_file, "node-1\n").unwrap();
+
+        // the hostname file first, trimmed
+        let hostname = infras::hostname_with(env(&[("HOSTNAME", "node-2")]), Some(&hostname_file));
+        assert_eq!(Some("node-1".to_string()), hostname);
+
+        // then the env vars
+        let hostname = infras::hostname_with(env(&[("COMPUTERNAME", "NODE-3")]), None);
+        assert_eq!(Some("NODE-3".to_string()), hostname);
+        std::fs::write(&hostname_file, " ").unwrap();
+        let hostname = infras::hostname_with(env(&[("HOSTNAME", "node-2")]), Some(&hostname_file));
+        assert_eq!(Some("node-2".to_string()), hostname);
+        assert_eq!(None, infras::hostname_with(env(&[]), Some(&hostname_file)));
+
+        // deterministic
+        let worker_id = infras::hostname_worker_id("node-1");
+        assert_eq!(
+            ("node-1".hashcode() & 0xFFFF) & Constants::MAX_WORKER_ID,
+            worker_id
+        );
+        assert_eq!(worker_id, infras::hostname_worker_id("node-1"));
+
+        let _ = std::fs::remove_file(&hostname_file);
+    }
+
+    #[test]
+    fn test_generator_with_hostname_identity() {
+        let gen = SnowflakeGenerator::with_identity(&HostnameIdentityProvider).unwrap();
+
+        let center_id = infras::try_get_data_center_id();
+        assert_eq!(center_id, gen.center_id());
+        assert_eq!(
+            infras::try_get_hostname_worker_id(center_id),
+            gen.worker_id()
+        );
+    }
+
+    #[test]
+    fn test_worker_id_cache_file() {
+        let file = infras::worker_id_cache_file(3);
+        assert!(file.ends_with("snowflaker/worker-id-3"));
+
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == key)
+                    .map(|(_, value)| std::ffi::OsString::from(value))
+            }
+        };
+        let file = infras::worker_id_cache_file_with(
+            vars(&[
+                (infras::SNOWFLAKE_CACHE_DIR, "/cache"),
+                ("XDG_CACHE_HOME", "/xdg"),
+                ("HOME", "/home/u"),
+            ]),
+            3,
+        );
+        assert_eq!(
+            std::path::PathBuf::from("/cache/snowflaker/worker-id-3"),
+            file
+        );
+        let file = infras::worker_id_cache_file_with(
+            vars(&[("XDG_CACHE_HOME", "/xdg"), ("HOME", "/home/u")]),
+            3,
+        );
+        assert_eq!(
+            std::path::PathBuf::from("/xdg/snowflaker/worker-id-3"),
+            file
+        );
+        let file = infras::worker_id_cache_file_with(vars(&[("HOME", "/home/u")]), 3);
+        assert_eq!(
+            std::path::PathBuf::from("/home/u/.cache/snowflaker/worker-id-3"),
+            file
+        );
+        let file = infras::worker_id_cache_file_with(vars(&[]), 3);
+        assert!(file.starts_with(std::env::temp_dir()));
     }
 
     #[test]
@@ -197,3 +4051,284 @@ mod feature_dynamic_tests {
     }
 }
 
+// ----------------------------------------------------------------
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod feature_serde_tests {
+    use crate::generator::{Generator, SnowflakeConfig, SnowflakeGenerator, TimePrecision};
+
+    #[test]
+    fn test_config_json_round_trip() {
+        let gen = SnowflakeGenerator::new(3, 7).unwrap();
+        assert!(gen.next_id().is_ok());
+
+        let json = serde_json::to_string(&gen.to_config()).unwrap();
+        assert!(json.starts_with(r#"{"center_id":3,"worker_id":7,"epoch":1680646028000,"#));
+
+        let config: SnowflakeConfig = serde_json::from_str(&json).unwrap();
+        let restored = SnowflakeGenerator::from_config(config).unwrap();
+        assert_eq!(gen.to_config(), restored.to_config());
+        assert_eq!(0, restored.get_sequence());
+
+        let gen = SnowflakeGenerator::compact(7).unwrap();
+        let json = serde_json::to_string(&gen.to_config()).unwrap();
+        let config: SnowflakeConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(gen, SnowflakeGenerator::from_config(config).unwrap());
+    }
+
+    #[test]
+    fn test_config_json_missing_layout() {
+        // persisted before the layout and precision were part of the config
+        let json = r#"{"center_id":3,"worker_id":7,"epoch":1680646028000}"#;
+        let config: SnowflakeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(SnowflakeConfig::default().layout, config.layout);
+        assert_eq!(TimePrecision::Millis, config.precision);
+
+        let json = r#"{"center_id":0,"worker_id":7,"epoch":0,"layout":{"datacenter_bits":0,"worker_id_bits":7}}"#;
+        let config: SnowflakeConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(127, config.layout.max_worker_id());
+        assert_eq!(4095, config.layout.sequence_mask());
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod feature_async_tests {
+    use std::sync::Arc;
+
+    use crate::generator::{
+        ClockBackwardsDecision, Constants, SnowflakeError, SnowflakeGenerator, TimePrecision,
+    };
+    use crate::tests::MockClock;
+
+    #[tokio::test]
+    async fn test_next_id_async() {
+        let gen = SnowflakeGenerator::builtin().unwrap();
+
+        let first = gen.next_id_async().await.unwrap();
+        let second = gen.next_id_async().await.unwrap();
+        assert!(second > first);
+    }
+
+    #[tokio::test]
+    async fn test_next_id_async_sequence_exhausted() {
+        let gen = SnowflakeGenerator::new(3, 7).unwrap();
+
+        let first = gen.next_id_async().await.unwrap();
+        gen.set_sequence(Constants::SEQUENCE_MASK);
+
+        let second = gen.next_id_async().await.unwrap();
+        assert!(second > first);
+        assert!(gen.is_valid(second));
+        assert_eq!(0, SnowflakeGenerator::decompose(second).sequence);
+    }
+
+    #[tokio::test]
+    async fn test_next_id_async_seconds_rollover() {
+        let gen = SnowflakeGenerator::builder()
+            .precision(TimePrecision::Seconds)
+            .build()
+            .unwrap();
+
+        let first = gen.next_id_async().await.unwrap();
+        gen.set_sequence(Constants::SEQUENCE_MASK);
+
+        let second = gen.next_id_async().await.unwrap();
+        assert_eq!(
+            SnowflakeGenerator::decompose(first).timestamp + 1,
+            SnowflakeGenerator::decompose(second).timestamp
+        );
+    }
+
+    #[tokio::test]
+    async fn test_next_id_async_clock_rollback() {
+        let now = Constants::EPOCH + 1000;
+        let clock = MockClock::new(&[now, now - 2, now - 2, now + 1]);
+        let gen = SnowflakeGenerator::new_with_clock(1, 1, Arc::new(clock)).unwrap();
+
+        let first = gen.next_id_async().await.unwrap();
+        let second = gen.next_id_async().await.unwrap();
+        assert!(second > first);
+    }
+
+    #[tokio::test]
+    async fn test_next_id_async_on_clock_backwards() {
+        let now = Constants::EPOCH + 1000;
+        let hooked = |timestamps: &[u64], decision| {
+            SnowflakeGenerator::builder()
+                .clock(Arc::new(MockClock::new(timestamps)))
+                .max_backward_tolerance_millis(1000)
+                .on_clock_backwards(move |_| decision)
+                .build()
+                .unwrap()
+        };
+
+        // fails immediately despite the tolerance
+        let gen = hooked(&[now, now - 5], ClockBackwardsDecision::Fail);
+        gen.next_id_async().await.unwrap();
+        assert_eq!(
+            SnowflakeError::ClockMovedBackwards,
+            gen.next_id_async().await.unwrap_err()
+        );
+
+        let gen = hooked(
+            &[now, now - 5, now - 5, now + 1],
+            ClockBackwardsDecision::WaitThenRetry,
+        );
+        gen.next_id_async().await.unwrap();
+        let parts = SnowflakeGenerator::decompose(gen.next_id_async().await.unwrap());
+        assert_eq!(1001, parts.timestamp);
+
+        let gen = hooked(
+            &[now, now - 5],
+            ClockBackwardsDecision::ProceedWithLastTimestamp,
+        );
+        let first = gen.next_id_async().await.unwrap();
+        let second = gen.next_id_async().await.unwrap();
+        assert!(second > first);
+        let parts = SnowflakeGenerator::decompose(second);
+        assert_eq!((1000, 1), (parts.timestamp, parts.sequence));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "registry")]
+mod feature_registry_tests {
+    use crate::generator::{Generator, SnowflakeError, SnowflakeGenerator};
+    use crate::registry;
+
+    // the registry is process-global, each test uses its own nodes
+
+    #[test]
+    fn test_new_registered_rejects_duplicate_node() {
+        let gen = SnowflakeGenerator::new_registered(11, 21).unwrap();
+        assert!(gen.next_id().is_ok());
+        assert!(registry::is_registered(11, 21));
+
+        let rvt = SnowflakeGenerator::new_registered(11, 21);
+        assert!(matches!(rvt, Err(SnowflakeError::DuplicateNode(11, 21))));
+
+        // another node
+        assert!(SnowflakeGenerator::new_registered(11, 22).is_ok());
+    }
+
+    #[test]
+    fn test_registered_generator_unregisters_on_drop() {
+        let gen = SnowflakeGenerator::new_registered(12, 21).unwrap();
+        assert!(SnowflakeGenerator::new_registered(12, 21).is_err());
+
+        drop(gen);
+        assert!(!registry::is_registered(12, 21));
+        assert!(SnowflakeGenerator::new_registered(12, 21).is_ok());
+    }
+
+    #[test]
+    fn test_register_invalid_node_not_registered() {
+        let rvt = SnowflakeGenerator::new_registered(32, 21);
+        assert!(matches!(rvt, Err(SnowflakeError::CenterIdInvalid)));
+        assert!(!registry::is_registered(32, 21));
+    }
+
+    #[test]
+    fn test_register_built_generator() {
+        let gen = SnowflakeGenerator::builder()
+            .center_id(13)
+            .worker_id(21)
+            .build()
+            .unwrap();
+        let registered = registry::register(gen.clone()).unwrap();
+        assert_eq!(&gen, registered.generator());
+
+        assert!(registry::register(gen).is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "uuid")]
+mod feature_uuid_tests {
+    use crate::codec;
+    use crate::generator::{SnowflakeError, SnowflakeGenerator};
+
+    use super::*;
+
+    #[cfg(feature = "global")]
+    #[test]
+    fn test_next_uuid_like() {
+        let uuid = next_uuid_like().unwrap();
+
+        let parsed = uuid::Uuid::parse_str(&uuid).unwrap();
+        assert_eq!(uuid, parsed.hyphenated().to_string());
+        assert_eq!(Some(uuid::Version::Custom), parsed.get_version());
+        assert_eq!(uuid::Variant::RFC4122, parsed.get_variant());
+
+        let (first, second) = codec::decode_uuid_like(&uuid).unwrap();
+        assert!(second > first);
+        assert_eq!(
+            SnowflakeGenerator::decompose(first).worker_id,
+            SnowflakeGenerator::decompose(second).worker_id
+        );
+
+        let uuids: HashSet<String> = (0..1000).map(|_| next_uuid_like().unwrap()).collect();
+        assert_eq!(1000, uuids.len());
+    }
+
+    #[test]
+    fn test_uuid_like_round_trip() {
+        let gen = SnowflakeGenerator::new(31, 31).unwrap();
+        let now = 1_700_000_000_000;
+        for (first, second) in [
+            (
+                gen.next_id_with_timestamp(now).unwrap(),
+                gen.next_id_with_timestamp(now).unwrap(),
+            ),
+            (
+                gen.next_id_with_timestamp(now).unwrap(),
+                gen.next_id_with_timestamp(now + 1).unwrap(),
+            ),
+            (0, 0),
+            (i64::MAX as u64, i64::MAX as u64),
+        ] {
+            let uuid = codec::encode_uuid_like(first, second).unwrap();
+            assert!(uuid::Uuid::parse_str(&uuid).is_ok());
+            assert_eq!((first, second), codec::decode_uuid_like(&uuid).unwrap());
+            assert_eq!(
+                (first, second),
+                codec::decode_uuid_like(&uuid.to_uppercase()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_uuid_like_invalid() {
+        let first = SnowflakeGenerator::new(3, 7).unwrap().next_id().unwrap();
+        let second = SnowflakeGenerator::new(3, 8).unwrap().next_id().unwrap();
+        assert!(matches!(
+            codec::encode_uuid_like(first, second),
+            Err(SnowflakeError::EncodingInvalid)
+        ));
+        assert!(matches!(
+            codec::encode_uuid_like(u64::MAX, 0),
+            Err(SnowflakeError::EncodingInvalid)
+        ));
+
+        for s in [
+            "",
+            "not-a-uuid",
+            "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "67e5504410b1826f9247bb680e5fe0c8",
+            "67e55044-10b1-826f-c247-bb680e5fe0c8",
+            "ffffffff-ffff-8fff-bfff-ffffffffffff",
+            "67e55044-10b1-826f-9247-bb680e5fe0cx",
+        ] {
+            assert!(
+                matches!(
+                    codec::decode_uuid_like(s),
+                    Err(SnowflakeError::EncodingInvalid)
+                ),
+                "{}",
+                s
+            );
+        }
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! ID verifiers for tests and audits.
+
+// ----------------------------------------------------------------
+
+use alloc::collections::BTreeMap;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::error::Error;
+
+// ----------------------------------------------------------------
+
+/// [`DuplicateError`] A duplicated ID and the indices of its two occurrences.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DuplicateError {
+    /// The duplicated ID.
+    pub id: u64,
+    /// The index of the first occurrence.
+    pub first: usize,
+    /// The index of the second occurrence.
+    pub second: usize,
+}
+
+impl Display for DuplicateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Duplicate ID {} at index {} and {}",
+            self.id, self.first, self.second
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for DuplicateError {}
+
+/// [`MonotonicError`] The offending pair of adjacent IDs which is not strictly increasing.
+///
+/// @since 0.4.0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MonotonicError {
+    /// The previous ID.
+    pub previous: u64,
+    /// The ID not greater than the previous one.
+    pub next: u64,
+    /// The index of the `next` ID.
+    pub index: usize,
+}
+
+impl Display for MonotonicError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ID {} at index {} is not greater than the previous ID {}",
+            self.next, self.index, self.previous
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for MonotonicError {}
+
+// ----------------------------------------------------------------
+
+/// Verifies the given IDs are unique, returns the first duplicated ID found.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::verify;
+///
+/// assert!(verify::verify_unique(&[1, 2, 3]).is_ok());
+///
+/// let err = verify::verify_unique(&[1, 2, 1]).unwrap_err();
+/// assert_eq!((1, 0, 2), (err.id, err.first, err.second));
+/// ```
+///
+/// @since 0.4.0
+pub fn verify_unique(ids: &[u64]) -> Result<(), DuplicateError> {
+    let mut seen = BTreeMap::new();
+    for (index, &id) in ids.iter().enumerate() {
+        if let Some(&first) = seen.get(&id) {
+            return Err(DuplicateError {
+                id,
+                first,
+                second: index,
+            });
+        }
+        seen.insert(id, index);
+    }
+
+    Ok(())
+}
+
+/// Verifies the given IDs are strictly increasing, which implies unique,
+/// returns the first offending pair of adjacent IDs found.
+///
+/// # Examples
+///
+/// ```rust
+/// use snowflaker::verify;
+///
+/// assert!(verify::verify_monotonic(&[1, 2, 3]).is_ok());
+///
+/// let err = verify::verify_monotonic(&[1, 3, 2]).unwrap_err();
+/// assert_eq!((3, 2, 2), (err.previous, err.next, err.index));
+/// ```
+///
+/// @since 0.4.0
+pub fn verify_monotonic(ids: &[u64]) -> Result<(), MonotonicError> {
+    match ids.windows(2).position(|pair| pair[0] >= pair[1]) {
+        Some(position) => Err(MonotonicError {
+            previous: ids[position],
+            next: ids[position + 1],
+            index: position + 1,
+        }),
+        None => Ok(()),
+    }
+}
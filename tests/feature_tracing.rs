@@ -0,0 +1,62 @@
+/*
+ * Copyright © 2024 the original author or authors.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// ----------------------------------------------------------------
+
+//! Notes: features = [`"tracing"`]
+//!
+//! A separate test binary: the subscriber of `tracing-test` is process-global,
+//! it would capture the spans of all the unit tests otherwise.
+
+#![cfg(feature = "tracing")]
+
+use tracing_test::traced_test;
+
+use snowflaker::generator::{BitLayout, Generator, SnowflakeGenerator};
+
+// ----------------------------------------------------------------
+
+#[test]
+#[traced_test]
+fn test_next_id_rollover_traced() {
+    // 2 IDs per millisecond, the sequence rolls over within a few IDs
+    let gen = SnowflakeGenerator::builder()
+        .layout(BitLayout::new(5, 5, 1).unwrap())
+        .build()
+        .unwrap();
+
+    let mut last = 0;
+    for _ in 0..100 {
+        let id = gen.next_id().unwrap();
+        assert!(id > last);
+        last = id;
+    }
+
+    assert!(logs_contain("next_id"));
+    assert!(logs_contain("rollover=true"));
+    assert!(logs_contain(
+        "sequence rollover, waiting for the next millis"
+    ));
+}
+
+#[test]
+#[traced_test]
+fn test_new_clamped_traced() {
+    let gen = SnowflakeGenerator::new_clamped(3, 33);
+    assert_eq!(1, gen.worker_id());
+
+    assert!(logs_contain("worker ID 33 clamped to"));
+}